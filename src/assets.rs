@@ -0,0 +1,82 @@
+use eframe::egui::{self, ColorImage, TextureHandle, TextureOptions};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::c::{Sprites, TerrainCatalog, TerrainType, UnitType};
+
+// Where images and their manifest live, relative to the working directory
+pub const ASSETS_DIR: &str = "assets";
+pub const MANIFEST_FILE: &str = "manifest.toml";
+
+// Image paths for each unit id, relative to the assets directory.
+// Terrain textures are named in the terrain definitions instead.
+#[derive(Debug, Default, Deserialize)]
+struct Manifest {
+    #[serde(default)]
+    units: HashMap<UnitType, String>,
+}
+
+// Sprite textures loaded at startup. Handles must stay alive for the textures to stay on the GPU.
+#[derive(Default)]
+pub struct Assets {
+    terrain: HashMap<TerrainType, TextureHandle>,
+    units: HashMap<UnitType, TextureHandle>,
+}
+
+impl Assets {
+    // Load every terrain texture and everything the manifest lists.
+    // Problems are reported and the affected ids fall back to flat shapes.
+    pub fn load(ctx: &egui::Context, terrain_catalog: Option<&TerrainCatalog>) -> Self {
+        let dir = Path::new(ASSETS_DIR);
+        let manifest_path = dir.join(MANIFEST_FILE);
+        
+        let manifest: Manifest = match fs::read_to_string(&manifest_path) {
+            Ok(text) => toml::from_str(&text).unwrap_or_else(|e| {
+                eprintln!("{}: {}", manifest_path.display(), e);
+                Manifest::default()
+            }),
+            // No manifest means no unit sprites, which is fine
+            Err(_) => Manifest::default(),
+        };
+        
+        let load = |id: String, file: &str| {
+            let path = dir.join(file);
+            match Self::load_image(&path) {
+                Ok(image) => Some(ctx.load_texture(id, image, TextureOptions::LINEAR)),
+                Err(e) => {
+                    eprintln!("{}: {}", path.display(), e);
+                    None
+                },
+            }
+        };
+        
+        let terrain = terrain_catalog.map(|catalog| catalog.terrain.as_slice()).unwrap_or_default().iter()
+            .filter_map(|definition| {
+                let file = definition.texture.as_ref()?;
+                Some((definition.id.clone(), load(format!("terrain-{}", definition.id), file)?))
+            })
+            .collect();
+        let units = manifest.units.iter()
+            .filter_map(|(unit_type, file)| Some((unit_type.clone(), load(format!("unit-{}", unit_type), file)?)))
+            .collect();
+        
+        Self { terrain, units }
+    }
+    
+    // Decode a PNG into an egui image
+    fn load_image(path: &Path) -> Result<ColorImage, String> {
+        let bytes = fs::read(path).map_err(|e| e.to_string())?;
+        let image = image::load_from_memory(&bytes).map_err(|e| e.to_string())?.to_rgba8();
+        let size = [image.width() as usize, image.height() as usize];
+        Ok(ColorImage::from_rgba_unmultiplied(size, image.as_flat_samples().as_slice()))
+    }
+    
+    // Texture ids for the world to draw with
+    pub fn sprites(&self) -> Sprites {
+        Sprites {
+            terrain: self.terrain.iter().map(|(terrain_type, handle)| (terrain_type.clone(), handle.id())).collect(),
+            units: self.units.iter().map(|(unit_type, handle)| (unit_type.clone(), handle.id())).collect(),
+        }
+    }
+}
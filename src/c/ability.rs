@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use crate::c::definitions::DefinitionError;
+
+// Where ability definitions are loaded from, relative to the working directory
+pub const ABILITIES_PATH: &str = "data/abilities.toml";
+
+// What an ability is aimed at
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AbilityTarget {
+    #[serde(rename = "self")]
+    User,  // The unit's own hex, used straight away
+    Ally,  // A hex with a friendly unit, the user included
+    Enemy, // A hex with an enemy unit
+    Hex,   // Any hex in range
+}
+
+// What an ability does when used
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum AbilityEffect {
+    Charge { attack: i32 },             // Passive: extra attack when attacking after moving this turn
+    Damage { power: f32 },              // Enemies in the area take this share of the user's attack
+    Heal { amount: i32 },               // Friendly units in the area regain health
+    Guard { defense: i32, turns: i32 }, // Extra defense for the user, who can't move while it lasts
+}
+
+impl AbilityEffect {
+    // Passive abilities work on their own and never go on the action bar
+    pub fn is_passive(&self) -> bool {
+        matches!(self, AbilityEffect::Charge { .. })
+    }
+}
+
+// One ability as written in the definitions file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbilityDefinition {
+    pub id: String,
+    pub name: String,        // Localization key for the display name
+    pub description: String, // Localization key for the tooltip
+    #[serde(default = "default_target")]
+    pub target: AbilityTarget,
+    #[serde(default)]
+    pub range: i32,    // Hexes from the user to the target
+    #[serde(default)]
+    pub area: i32,     // Units this many hexes from the target are affected too
+    #[serde(default)]
+    pub cooldown: i32, // Own turns to wait before using it again
+    #[serde(default)]
+    pub ammo: i32,     // Ammo used under the supply rule
+    pub effect: AbilityEffect,
+    #[serde(default)]
+    pub status: Option<AbilityStatus>, // Left on every unit the effect reaches that is still standing
+}
+
+// Status effect an ability leaves behind, see data/statuses.toml
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AbilityStatus {
+    pub id: String,
+    pub turns: i32,
+}
+
+fn default_target() -> AbilityTarget {
+    AbilityTarget::User
+}
+
+// Layout of the ability definitions file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AbilityFile {
+    #[serde(default)]
+    ability: Vec<AbilityDefinition>,
+}
+
+// All known abilities, in file order (singleton, on the map settings entity)
+#[derive(Debug, Clone, Default)]
+pub struct AbilityCatalog {
+    pub abilities: Vec<AbilityDefinition>,
+}
+
+impl AbilityCatalog {
+    // Read and check an ability definitions file
+    pub fn load(path: &str) -> Result<Self, Vec<DefinitionError>> {
+        let text = fs::read_to_string(path).map_err(|e| vec![DefinitionError::new(path, "", e.to_string())])?;
+        Self::parse(&text, path)
+    }
+    
+    // Parse and check ability definitions; `file` is only used in error messages
+    pub fn parse(text: &str, file: &str) -> Result<Self, Vec<DefinitionError>> {
+        let parsed: AbilityFile = toml::from_str(text).map_err(|e| vec![DefinitionError::from_toml(file, text, &e)])?;
+        
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        
+        for (index, ability) in parsed.ability.iter().enumerate() {
+            let field = |name: &str| format!("ability[{}].{}", index, name);
+            
+            if ability.id.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("id"), "must not be empty"));
+            } else if !seen.insert(ability.id.clone()) {
+                errors.push(DefinitionError::new(file, field("id"), format!("duplicate id \"{}\"", ability.id)));
+            }
+            
+            for (name, key) in [("name", &ability.name), ("description", &ability.description)] {
+                if key.trim().is_empty() {
+                    errors.push(DefinitionError::new(file, field(name), "must not be empty"));
+                }
+            }
+            
+            let mut checks = vec![
+                ("range", ability.range, 0),
+                ("area", ability.area, 0),
+                ("cooldown", ability.cooldown, 0),
+                ("ammo", ability.ammo, 0),
+            ];
+            match ability.effect {
+                AbilityEffect::Charge { attack } => checks.push(("effect.attack", attack, 1)),
+                AbilityEffect::Damage { power } if power <= 0.0 => {
+                    errors.push(DefinitionError::new(file, field("effect.power"), format!("must be more than 0, got {}", power)));
+                },
+                AbilityEffect::Damage { .. } => {},
+                AbilityEffect::Heal { amount } => checks.push(("effect.amount", amount, 1)),
+                AbilityEffect::Guard { defense, turns } => {
+                    checks.push(("effect.defense", defense, 1));
+                    checks.push(("effect.turns", turns, 1));
+                },
+            }
+            if ability.target == AbilityTarget::Enemy {
+                checks.push(("range", ability.range, 1));
+            }
+            if let Some(status) = &ability.status {
+                checks.push(("status.turns", status.turns, 1));
+            }
+            for (name, value, min) in checks {
+                if value < min {
+                    errors.push(DefinitionError::new(file, field(name), format!("must be at least {}, got {}", min, value)));
+                }
+            }
+            
+            // Guarding only ever covers the user
+            if matches!(ability.effect, AbilityEffect::Guard { .. }) && ability.target != AbilityTarget::User {
+                errors.push(DefinitionError::new(file, field("target"), "guard effects must target \"self\""));
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(Self { abilities: parsed.ability })
+        } else {
+            Err(errors)
+        }
+    }
+    
+    // Definition for an ability id
+    pub fn get(&self, id: &str) -> Option<&AbilityDefinition> {
+        self.abilities.iter().find(|ability| ability.id == id)
+    }
+}
+
+// A guard ability holding, see AbilityEffect::Guard
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Guard {
+    pub defense: i32,
+    pub turns_left: i32, // Own turns it still holds after this one
+}
+
+// Abilities component (on tiles, moves with the unit): cooldowns and lasting effects
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Abilities {
+    pub cooldowns: HashMap<String, i32>, // Own turns left before an ability can be used again
+    pub guard: Option<Guard>,
+}
+
+impl Abilities {
+    pub fn cooldown(&self, id: &str) -> i32 {
+        self.cooldowns.get(id).copied().unwrap_or(0)
+    }
+}
@@ -0,0 +1,60 @@
+use eframe::egui::Color32;
+use crate::e::entity::Entity;
+use crate::c::hex::HexCoord;
+
+// What an animation shows
+#[derive(Debug, Clone)]
+pub enum AnimationKind {
+    Move { path: Vec<HexCoord> },                          // Unit slides hex by hex along the path
+    Attack { from: HexCoord, to: HexCoord, ranged: bool }, // Melee lunge or ranged projectile
+    Damage { at: HexCoord, amount: i32 },                  // Number floating up from the defender
+    Heal { at: HexCoord, amount: i32 },                    // Number floating up from a healed unit
+    Death { at: HexCoord, color: Color32 },                // Destroyed unit fading away
+}
+
+// One running animation. Purely visual: the game state has already changed.
+#[derive(Debug, Clone)]
+pub struct Animation {
+    pub kind: AnimationKind,
+    pub entity: Option<Entity>, // Unit tile drawn by this animation instead of in place
+    pub delay: f32,             // Seconds before it starts
+    pub duration: f32,          // Seconds it runs for
+    pub elapsed: f32,
+}
+
+impl Animation {
+    pub fn new(kind: AnimationKind, entity: Option<Entity>, duration: f32) -> Self {
+        Self { kind, entity, delay: 0.0, duration, elapsed: 0.0 }
+    }
+    
+    pub fn with_delay(mut self, delay: f32) -> Self {
+        self.delay = delay;
+        self
+    }
+    
+    pub fn started(&self) -> bool {
+        self.elapsed >= self.delay
+    }
+    
+    pub fn finished(&self) -> bool {
+        self.elapsed >= self.delay + self.duration
+    }
+    
+    // How far through the animation we are, 0.0 to 1.0
+    pub fn progress(&self) -> f32 {
+        ((self.elapsed - self.delay) / self.duration.max(f32::EPSILON)).clamp(0.0, 1.0)
+    }
+}
+
+// Animation queue component (singleton, on the game state entity)
+#[derive(Debug, Clone)]
+pub struct Animations {
+    pub active: Vec<Animation>,
+    pub speed: f32, // Playback speed multiplier, 0 turns animations off
+}
+
+impl Default for Animations {
+    fn default() -> Self {
+        Self { active: Vec::new(), speed: 1.0 }
+    }
+}
@@ -0,0 +1,336 @@
+use serde::{Deserialize, Serialize};
+use crate::c::hex::HexCoord;
+
+// Which rows or columns of an offset grid get shoved by half a hex
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum OffsetKind {
+    OddQ,  // Flat-top, odd columns shoved down
+    EvenQ, // Flat-top, even columns shoved down
+    OddR,  // Pointy-top, odd rows shoved right
+    EvenR, // Pointy-top, even rows shoved right
+}
+
+impl OffsetKind {
+    pub fn all() -> Vec<OffsetKind> {
+        vec![OffsetKind::OddQ, OffsetKind::EvenQ, OffsetKind::OddR, OffsetKind::EvenR]
+    }
+}
+
+// Column/row coordinate in one of the offset conventions
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OffsetCoord {
+    pub col: i32,
+    pub row: i32,
+    pub kind: OffsetKind,
+}
+
+// Neighbour steps (col, row) for offset grids, indexed by parity of the shoved axis
+const ODD_Q_STEPS: [[(i32, i32); 6]; 2] = [
+    [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (0, 1)],
+    [(1, 1), (1, 0), (0, -1), (-1, 0), (-1, 1), (0, 1)],
+];
+const EVEN_Q_STEPS: [[(i32, i32); 6]; 2] = [
+    [(1, 1), (1, 0), (0, -1), (-1, 0), (-1, 1), (0, 1)],
+    [(1, 0), (1, -1), (0, -1), (-1, -1), (-1, 0), (0, 1)],
+];
+const ODD_R_STEPS: [[(i32, i32); 6]; 2] = [
+    [(1, 0), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1)],
+    [(1, 0), (1, -1), (0, -1), (-1, 0), (0, 1), (1, 1)],
+];
+const EVEN_R_STEPS: [[(i32, i32); 6]; 2] = [
+    [(1, 0), (1, -1), (0, -1), (-1, 0), (0, 1), (1, 1)],
+    [(1, 0), (0, -1), (-1, -1), (-1, 0), (-1, 1), (0, 1)],
+];
+
+impl OffsetCoord {
+    pub fn new(col: i32, row: i32, kind: OffsetKind) -> Self {
+        Self { col, row, kind }
+    }
+    
+    // Convert an axial coordinate into this offset convention
+    pub fn from_hex(hex: HexCoord, kind: OffsetKind) -> Self {
+        let (col, row) = match kind {
+            OffsetKind::OddQ => (hex.q, hex.r + (hex.q - (hex.q & 1)) / 2),
+            OffsetKind::EvenQ => (hex.q, hex.r + (hex.q + (hex.q & 1)) / 2),
+            OffsetKind::OddR => (hex.q + (hex.r - (hex.r & 1)) / 2, hex.r),
+            OffsetKind::EvenR => (hex.q + (hex.r + (hex.r & 1)) / 2, hex.r),
+        };
+        
+        Self { col, row, kind }
+    }
+    
+    // Convert back to axial
+    pub fn to_hex(&self) -> HexCoord {
+        let (col, row) = (self.col, self.row);
+        match self.kind {
+            OffsetKind::OddQ => HexCoord::new(col, row - (col - (col & 1)) / 2),
+            OffsetKind::EvenQ => HexCoord::new(col, row - (col + (col & 1)) / 2),
+            OffsetKind::OddR => HexCoord::new(col - (row - (row & 1)) / 2, row),
+            OffsetKind::EvenR => HexCoord::new(col - (row + (row & 1)) / 2, row),
+        }
+    }
+    
+    // Get all 6 neighbors, in the same order as HexCoord::neighbors
+    pub fn neighbors(&self) -> [OffsetCoord; 6] {
+        let (table, parity) = match self.kind {
+            OffsetKind::OddQ => (&ODD_Q_STEPS, self.col & 1),
+            OffsetKind::EvenQ => (&EVEN_Q_STEPS, self.col & 1),
+            OffsetKind::OddR => (&ODD_R_STEPS, self.row & 1),
+            OffsetKind::EvenR => (&EVEN_R_STEPS, self.row & 1),
+        };
+        
+        table[parity as usize].map(|(dc, dr)| OffsetCoord::new(self.col + dc, self.row + dr, self.kind))
+    }
+}
+
+impl From<OffsetCoord> for HexCoord {
+    fn from(offset: OffsetCoord) -> Self {
+        offset.to_hex()
+    }
+}
+
+impl From<(HexCoord, OffsetKind)> for OffsetCoord {
+    fn from((hex, kind): (HexCoord, OffsetKind)) -> Self {
+        OffsetCoord::from_hex(hex, kind)
+    }
+}
+
+// Which axis a doubled coordinate doubles
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DoubledKind {
+    DoubledHeight, // Flat-top, row steps by 2 between vertical neighbours
+    DoubledWidth,  // Pointy-top, col steps by 2 between horizontal neighbours
+}
+
+impl DoubledKind {
+    pub fn all() -> Vec<DoubledKind> {
+        vec![DoubledKind::DoubledHeight, DoubledKind::DoubledWidth]
+    }
+}
+
+// Doubled coordinate; col + row is always even
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DoubledCoord {
+    pub col: i32,
+    pub row: i32,
+    pub kind: DoubledKind,
+}
+
+const DOUBLED_HEIGHT_STEPS: [(i32, i32); 6] = [(1, 1), (1, -1), (0, -2), (-1, -1), (-1, 1), (0, 2)];
+const DOUBLED_WIDTH_STEPS: [(i32, i32); 6] = [(2, 0), (1, -1), (-1, -1), (-2, 0), (-1, 1), (1, 1)];
+
+impl DoubledCoord {
+    pub fn new(col: i32, row: i32, kind: DoubledKind) -> Self {
+        Self { col, row, kind }
+    }
+    
+    // Only coordinates with an even col + row name a real hex
+    pub fn is_valid(&self) -> bool {
+        (self.col + self.row) % 2 == 0
+    }
+    
+    // Convert an axial coordinate into this doubled convention
+    pub fn from_hex(hex: HexCoord, kind: DoubledKind) -> Self {
+        match kind {
+            DoubledKind::DoubledHeight => Self::new(hex.q, 2 * hex.r + hex.q, kind),
+            DoubledKind::DoubledWidth => Self::new(2 * hex.q + hex.r, hex.r, kind),
+        }
+    }
+    
+    // Convert back to axial
+    pub fn to_hex(&self) -> HexCoord {
+        match self.kind {
+            DoubledKind::DoubledHeight => HexCoord::new(self.col, (self.row - self.col).div_euclid(2)),
+            DoubledKind::DoubledWidth => HexCoord::new((self.col - self.row).div_euclid(2), self.row),
+        }
+    }
+    
+    // Get all 6 neighbors, in the same order as HexCoord::neighbors
+    pub fn neighbors(&self) -> [DoubledCoord; 6] {
+        let table = match self.kind {
+            DoubledKind::DoubledHeight => &DOUBLED_HEIGHT_STEPS,
+            DoubledKind::DoubledWidth => &DOUBLED_WIDTH_STEPS,
+        };
+        
+        table.map(|(dc, dr)| DoubledCoord::new(self.col + dc, self.row + dr, self.kind))
+    }
+}
+
+impl From<DoubledCoord> for HexCoord {
+    fn from(doubled: DoubledCoord) -> Self {
+        doubled.to_hex()
+    }
+}
+
+impl From<(HexCoord, DoubledKind)> for DoubledCoord {
+    fn from((hex, kind): (HexCoord, DoubledKind)) -> Self {
+        DoubledCoord::from_hex(hex, kind)
+    }
+}
+
+// Coordinate convention used for labels and map files
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CoordSystem {
+    Axial,
+    Offset(OffsetKind),
+    Doubled(DoubledKind),
+}
+
+impl CoordSystem {
+    pub fn all() -> Vec<CoordSystem> {
+        let mut systems = vec![CoordSystem::Axial];
+        systems.extend(OffsetKind::all().into_iter().map(CoordSystem::Offset));
+        systems.extend(DoubledKind::all().into_iter().map(CoordSystem::Doubled));
+        systems
+    }
+    
+    // Localization key for this convention's display name
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            CoordSystem::Axial => "coords-axial",
+            CoordSystem::Offset(OffsetKind::OddQ) => "coords-odd-q",
+            CoordSystem::Offset(OffsetKind::EvenQ) => "coords-even-q",
+            CoordSystem::Offset(OffsetKind::OddR) => "coords-odd-r",
+            CoordSystem::Offset(OffsetKind::EvenR) => "coords-even-r",
+            CoordSystem::Doubled(DoubledKind::DoubledHeight) => "coords-doubled-height",
+            CoordSystem::Doubled(DoubledKind::DoubledWidth) => "coords-doubled-width",
+        }
+    }
+    
+    // Name used in map files, e.g. "odd-r"
+    pub fn id(&self) -> &'static str {
+        match self {
+            CoordSystem::Axial => "axial",
+            CoordSystem::Offset(OffsetKind::OddQ) => "odd-q",
+            CoordSystem::Offset(OffsetKind::EvenQ) => "even-q",
+            CoordSystem::Offset(OffsetKind::OddR) => "odd-r",
+            CoordSystem::Offset(OffsetKind::EvenR) => "even-r",
+            CoordSystem::Doubled(DoubledKind::DoubledHeight) => "doubled-height",
+            CoordSystem::Doubled(DoubledKind::DoubledWidth) => "doubled-width",
+        }
+    }
+    
+    pub fn from_id(id: &str) -> Option<CoordSystem> {
+        Self::all().into_iter().find(|system| system.id() == id)
+    }
+    
+    // Express an axial coordinate as a (col, row) pair in this convention
+    pub fn from_hex(&self, hex: HexCoord) -> (i32, i32) {
+        match self {
+            CoordSystem::Axial => (hex.q, hex.r),
+            CoordSystem::Offset(kind) => {
+                let offset = OffsetCoord::from_hex(hex, *kind);
+                (offset.col, offset.row)
+            },
+            CoordSystem::Doubled(kind) => {
+                let doubled = DoubledCoord::from_hex(hex, *kind);
+                (doubled.col, doubled.row)
+            },
+        }
+    }
+    
+    // Read a (col, row) pair written in this convention, e.g. from a map file
+    pub fn to_hex(&self, col: i32, row: i32) -> Option<HexCoord> {
+        match self {
+            CoordSystem::Axial => Some(HexCoord::new(col, row)),
+            CoordSystem::Offset(kind) => Some(OffsetCoord::new(col, row, *kind).to_hex()),
+            CoordSystem::Doubled(kind) => {
+                let doubled = DoubledCoord::new(col, row, *kind);
+                if doubled.is_valid() { Some(doubled.to_hex()) } else { None }
+            },
+        }
+    }
+    
+    // Short label for drawing on a tile
+    pub fn label(&self, hex: HexCoord) -> String {
+        let (col, row) = self.from_hex(hex);
+        format!("{},{}", col, row)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn sample_hexes() -> Vec<HexCoord> {
+        let mut hexes = Vec::new();
+        for q in -12..=12 {
+            for r in -12..=12 {
+                hexes.push(HexCoord::new(q, r));
+            }
+        }
+        hexes
+    }
+    
+    #[test]
+    fn offset_round_trip() {
+        for kind in OffsetKind::all() {
+            for hex in sample_hexes() {
+                let offset: OffsetCoord = (hex, kind).into();
+                assert_eq!(HexCoord::from(offset), hex, "{:?} via {:?}", hex, kind);
+            }
+            
+            for col in -12..=12 {
+                for row in -12..=12 {
+                    let offset = OffsetCoord::new(col, row, kind);
+                    assert_eq!(OffsetCoord::from_hex(offset.to_hex(), kind), offset);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn doubled_round_trip() {
+        for kind in DoubledKind::all() {
+            for hex in sample_hexes() {
+                let doubled: DoubledCoord = (hex, kind).into();
+                assert!(doubled.is_valid());
+                assert_eq!(HexCoord::from(doubled), hex, "{:?} via {:?}", hex, kind);
+            }
+            
+            for col in -12..=12 {
+                for row in -12..=12 {
+                    let doubled = DoubledCoord::new(col, row, kind);
+                    if doubled.is_valid() {
+                        assert_eq!(DoubledCoord::from_hex(doubled.to_hex(), kind), doubled);
+                    }
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn neighbors_match_axial() {
+        for hex in sample_hexes() {
+            let axial = hex.neighbors();
+            
+            for kind in OffsetKind::all() {
+                let offset = OffsetCoord::from_hex(hex, kind).neighbors().map(HexCoord::from);
+                assert_eq!(offset, axial, "{:?} via {:?}", hex, kind);
+            }
+            
+            for kind in DoubledKind::all() {
+                let doubled = DoubledCoord::from_hex(hex, kind).neighbors().map(HexCoord::from);
+                assert_eq!(doubled, axial, "{:?} via {:?}", hex, kind);
+            }
+        }
+    }
+    
+    #[test]
+    fn coord_system_ids() {
+        for system in CoordSystem::all() {
+            assert_eq!(CoordSystem::from_id(system.id()), Some(system));
+        }
+        assert_eq!(CoordSystem::from_id("doubled"), None);
+    }
+    
+    #[test]
+    fn coord_system_round_trip() {
+        for system in CoordSystem::all() {
+            for hex in sample_hexes() {
+                let (col, row) = system.from_hex(hex);
+                assert_eq!(system.to_hex(col, row), Some(hex), "{:?} via {:?}", hex, system);
+            }
+        }
+    }
+}
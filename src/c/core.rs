@@ -1,5 +1,12 @@
 use crate::e::entity::Entity;
+use crate::c::hex::{HexCoord, MapShape};
+use crate::c::definitions::{TerrainType, UnitType};
+use crate::c::coords::CoordSystem;
+use crate::c::layout::HexLayout;
+use eframe::egui::{Mesh, Pos2, Rect, TextureHandle, TextureId, Vec2};
+use serde::{Deserialize, Serialize};
 use std::any::Any;
+use std::collections::HashMap;
 
 // General marker components
 
@@ -15,14 +22,30 @@ pub struct Hovering;
 #[derive(Debug, Clone)]
 pub struct InMovementRange;
 
+// In an enemy's zone of control component (marker, shown while a unit is selected)
+#[derive(Debug, Clone)]
+pub struct InZoneOfControl;
+
 // In attack range component (marker)
 #[derive(Debug, Clone)]
 pub struct InAttackRange;
 
+// Target of the armed ability component (marker), see GameState::armed_ability
+#[derive(Debug, Clone)]
+pub struct InAbilityRange;
+
+// Objective hex component (marker), see VictoryCondition::HoldObjectives
+#[derive(Debug, Clone)]
+pub struct Objective;
+
+// Leader component (marker, moves with the unit), see VictoryCondition::KillLeader
+#[derive(Debug, Clone)]
+pub struct Leader;
+
 // Team component
 #[derive(Debug, Clone)]
 pub struct Team {
-    pub team_id: u8, // 0 for player, 1 for enemy
+    pub team_id: u8, // See TeamInfo::id
 }
 
 // Game state component (singleton)
@@ -30,10 +53,61 @@ pub struct Team {
 pub struct GameState {
     pub selected_entity: Option<Entity>,
     pub hover_entity: Option<Entity>,
-    pub current_turn: u8, // 0 for player, 1 for enemy
-    pub turn_number: i32,
+    pub cursor: Option<HexCoord>, // Keyboard cursor, None until a cursor key is used
+    pub armed_ability: Option<String>, // Ability of the selected unit waiting for a target
+    pub current_turn: u8, // Id of the team whose turn it is
+    pub turn_number: i32, // Round number, goes up once every team has had its turn
+    pub end_turn_requested: bool, // Set by input, the app decides whether to confirm first
     pub game_over: bool,
-    pub player_won: bool,
+    pub winners: Vec<u8>, // Teams left standing once the game is over
+}
+
+// Optional rules for the current game (singleton, on the game state entity)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GameRules {
+    pub zone_of_control: bool, // Entering a hex next to an enemy ends movement
+    pub zoc_exit_cost: i32,    // Extra movement points to leave a hex next to an enemy
+    pub supply: bool,          // Ranged units run out of ammo away from friendly settlements
+    pub turn_limit: Option<i32>, // Last round; the game ends once it is over
+    pub victory: Vec<VictoryCondition>,
+}
+
+impl Default for GameRules {
+    fn default() -> Self {
+        Self { zone_of_control: false, zoc_exit_cost: 1, supply: false, turn_limit: None, victory: vec![VictoryCondition::Eliminate] }
+    }
+}
+
+// How a game can be won, written as `type = "..."` in scenario files.
+// Whatever the conditions, the game also ends once only allies are left standing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum VictoryCondition {
+    Eliminate,                            // Be the last side standing, with your allies
+    HoldObjectives { turns: i32 },        // End this many turns in a row with your side on every objective hex
+    SurviveUntil { team: u8, turn: i32 }, // The team and its allies win if it still has units once round `turn` is over
+    KillLeader,                           // A team whose leader dies is out of the game
+    HighestScore,                         // Once the turn limit is reached the highest score wins instead of a draw
+}
+
+impl VictoryCondition {
+    // Localization key for a description of the condition
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            VictoryCondition::Eliminate => "victory-eliminate",
+            VictoryCondition::HoldObjectives { .. } => "victory-hold-objectives",
+            VictoryCondition::SurviveUntil { .. } => "victory-survive-until",
+            VictoryCondition::KillLeader => "victory-kill-leader",
+            VictoryCondition::HighestScore => "victory-highest-score",
+        }
+    }
+}
+
+// Progress towards the victory conditions (singleton, on the game state entity)
+#[derive(Debug, Clone, Default)]
+pub struct VictoryState {
+    pub scores: HashMap<u8, i32>,     // Damage each team has dealt to its enemies
+    pub held_turns: HashMap<u8, i32>, // Turns in a row each team has ended holding every objective hex
 }
 
 // Map settings component (singleton)
@@ -41,8 +115,116 @@ pub struct GameState {
 pub struct MapSettings {
     pub map_width: i32,
     pub map_height: i32,
-    pub hex_size: f32,
-    pub origin: eframe::egui::Pos2,
+    pub map_shape: MapShape,
+    pub coord_labels: CoordSystem, // Convention used for tile coordinate labels
+    pub show_coord_labels: bool,   // Debug toggle for drawing the labels
+    pub layout: HexLayout,
+}
+
+// Camera component (singleton) - pan and zoom applied on top of the map layout.
+// Screen position = map layout position * zoom + offset.
+#[derive(Debug, Clone)]
+pub struct Camera {
+    pub offset: Vec2,
+    pub zoom: f32,
+    pub viewport: Rect, // Screen area of the board, updated every frame
+    pub framed: bool,   // Whether the map has been centred in the viewport yet
+}
+
+impl Camera {
+    pub const MIN_ZOOM: f32 = 0.25;
+    pub const MAX_ZOOM: f32 = 4.0;
+    
+    pub fn new() -> Self {
+        Self {
+            offset: Vec2::ZERO,
+            zoom: 1.0,
+            viewport: Rect::NOTHING,
+            framed: false,
+        }
+    }
+    
+    // Layout for drawing and hit-testing, with pan and zoom baked in
+    pub fn view_layout(&self, base: &HexLayout) -> HexLayout {
+        HexLayout::new(
+            base.orientation,
+            base.size * self.zoom,
+            (base.origin.to_vec2() * self.zoom + self.offset).to_pos2(),
+        )
+    }
+    
+    // Map layout position to screen position
+    pub fn world_to_screen(&self, pos: Pos2) -> Pos2 {
+        (pos.to_vec2() * self.zoom + self.offset).to_pos2()
+    }
+    
+    // Screen position to map layout position
+    pub fn screen_to_world(&self, pos: Pos2) -> Pos2 {
+        ((pos.to_vec2() - self.offset) / self.zoom).to_pos2()
+    }
+    
+    // Move the camera so a map layout position ends up at a screen position
+    pub fn look_at(&mut self, world_pos: Pos2, screen_pos: Pos2) {
+        self.offset = screen_pos.to_vec2() - world_pos.to_vec2() * self.zoom;
+    }
+    
+    // Change zoom while keeping the point under `anchor` (screen position) still
+    pub fn zoom_around(&mut self, factor: f32, anchor: Pos2) {
+        let world_pos = self.screen_to_world(anchor);
+        self.zoom = (self.zoom * factor).clamp(Self::MIN_ZOOM, Self::MAX_ZOOM);
+        self.look_at(world_pos, anchor);
+    }
+}
+
+impl Default for Camera {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Terrain mesh component (singleton) - static terrain baked in map layout space.
+// Set `dirty` whenever terrain changes and the render system rebuilds it.
+#[derive(Debug, Clone, Default)]
+pub struct TerrainMesh {
+    pub chunks: Vec<TerrainChunk>,
+    pub dirty: bool,
+    pub revision: u64,             // Counts rebuilds, so anything drawn from the chunks knows when to redraw
+    pub bounds: Option<Rect>,      // The whole map in map layout space, measured with the chunks
+    pub view: Option<TerrainView>, // The visible chunks on screen, reused until the camera moves
+}
+
+// The visible terrain in screen space for one camera position
+#[derive(Debug, Clone)]
+pub struct TerrainView {
+    pub revision: u64,
+    pub scale: f32,
+    pub offset: Vec2,
+    pub visible_rect: Rect,
+    pub layers: Vec<Mesh>, // One mesh per texture, then the outlines
+}
+
+// Minimap terrain image (singleton, on the map settings entity), redrawn when the terrain mesh is rebuilt
+#[derive(Clone)]
+pub struct MinimapTexture {
+    pub texture: TextureHandle,
+    pub revision: u64,
+    pub size: [usize; 2], // In pixels
+}
+
+// A block of tiles drawn as one mesh per texture, culled as a whole
+#[derive(Debug, Clone)]
+pub struct TerrainChunk {
+    pub bounds: Rect,
+    pub fill: Vec<Mesh>, // One mesh per texture; flat-coloured tiles use the default one
+    pub outline: Mesh,   // Tile borders, skipped when zoomed out too far to see them
+}
+
+// Loaded sprite textures (singleton, on the map settings entity).
+// Types without an entry are drawn as flat shapes.
+#[derive(Debug, Clone, Default)]
+pub struct Sprites {
+    pub terrain: HashMap<TerrainType, TextureId>,
+    pub units: HashMap<UnitType, TextureId>,
 }
 
 // 一个特征，用于存储和管理组件
@@ -0,0 +1,639 @@
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::fs;
+use crate::c::ability::{AbilityCatalog, ABILITIES_PATH};
+use crate::c::hex::UnitStats;
+use crate::c::status::{StatusCatalog, STATUSES_PATH};
+use crate::c::veterancy::Promotion;
+
+// Where definitions are loaded from, relative to the working directory
+pub const UNITS_PATH: &str = "data/units.toml";
+pub const TERRAIN_PATH: &str = "data/terrain.toml";
+
+// Movement class of units that don't name one
+pub const DEFAULT_UNIT_CLASS: &str = "foot";
+
+// A problem in a definitions file, naming the file and the offending field
+#[derive(Debug, Clone, PartialEq)]
+pub struct DefinitionError {
+    pub file: String,
+    pub field: String, // e.g. "unit[2].movement", or "line 14" for syntax errors
+    pub message: String,
+}
+
+impl DefinitionError {
+    pub fn new(file: &str, field: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { file: file.to_string(), field: field.into(), message: message.into() }
+    }
+    
+    // Turn a TOML parse error into one pointing at the line it happened on
+    pub(crate) fn from_toml(file: &str, text: &str, error: &toml::de::Error) -> Self {
+        let field = match error.span() {
+            Some(span) => format!("line {}", text[..span.start].matches('\n').count() + 1),
+            None => String::new(),
+        };
+        Self::new(file, field, error.message())
+    }
+}
+
+impl fmt::Display for DefinitionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.field.is_empty() {
+            write!(f, "{}: {}", self.file, self.message)
+        } else {
+            write!(f, "{}: {}: {}", self.file, self.field, self.message)
+        }
+    }
+}
+
+// Open unit type id, e.g. "infantry"; the definitions file decides which ids exist
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UnitType(pub String);
+
+impl UnitType {
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+    
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for UnitType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// One unit type as written in the definitions file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct UnitDefinition {
+    pub id: UnitType,
+    pub name: String, // Localization key for the display name
+    #[serde(default = "default_class")]
+    pub class: String, // Movement class, looked up in terrain movement costs
+    pub max_health: i32,
+    pub attack: i32,
+    pub defense: i32,
+    pub movement: i32,
+    pub range: i32,
+    pub sight: i32,
+    #[serde(default)]
+    pub cost: i32,   // Gold to recruit one
+    #[serde(default)]
+    pub upkeep: i32, // Gold per turn to keep one in the field
+    #[serde(default)]
+    pub ammo: i32,   // Ranged shots between resupplies under the supply rule, 0 for none needed
+    #[serde(default = "default_promotions")]
+    pub promotions: Vec<Promotion>, // Choices on reaching a new rank
+    #[serde(default)]
+    pub abilities: Vec<String>, // Ability ids, see data/abilities.toml
+}
+
+fn default_class() -> String {
+    DEFAULT_UNIT_CLASS.to_string()
+}
+
+fn default_promotions() -> Vec<Promotion> {
+    vec![Promotion::Attack, Promotion::Defense, Promotion::Health]
+}
+
+impl UnitDefinition {
+    // Fresh stats for a unit of this type
+    pub fn stats(&self) -> UnitStats {
+        UnitStats {
+            unit_type: self.id.clone(),
+            class: self.class.clone(),
+            max_health: self.max_health,
+            attack: self.attack,
+            defense: self.defense,
+            movement: self.movement,
+            range: self.range,
+            sight: self.sight,
+            max_ammo: self.ammo,
+        }
+    }
+}
+
+// Layout of the unit definitions file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct UnitFile {
+    #[serde(default)]
+    unit: Vec<UnitDefinition>,
+}
+
+// All known unit types, in file order (singleton, on the map settings entity)
+#[derive(Debug, Clone, Default)]
+pub struct UnitCatalog {
+    pub units: Vec<UnitDefinition>,
+}
+
+impl UnitCatalog {
+    // Read and check a unit definitions file
+    pub fn load(path: &str) -> Result<Self, Vec<DefinitionError>> {
+        let text = fs::read_to_string(path).map_err(|e| vec![DefinitionError::new(path, "", e.to_string())])?;
+        Self::parse(&text, path)
+    }
+    
+    // Parse and check unit definitions; `file` is only used in error messages
+    pub fn parse(text: &str, file: &str) -> Result<Self, Vec<DefinitionError>> {
+        let parsed: UnitFile = toml::from_str(text).map_err(|e| vec![DefinitionError::from_toml(file, text, &e)])?;
+        
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        
+        if parsed.unit.is_empty() {
+            errors.push(DefinitionError::new(file, "unit", "no unit types defined"));
+        }
+        
+        for (index, unit) in parsed.unit.iter().enumerate() {
+            let field = |name: &str| format!("unit[{}].{}", index, name);
+            
+            if unit.id.0.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("id"), "must not be empty"));
+            } else if !seen.insert(unit.id.clone()) {
+                errors.push(DefinitionError::new(file, field("id"), format!("duplicate id \"{}\"", unit.id)));
+            }
+            
+            if unit.name.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("name"), "must not be empty"));
+            }
+            
+            if unit.class.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("class"), "must not be empty"));
+            }
+            
+            let checks = [
+                ("max_health", unit.max_health, 1),
+                ("attack", unit.attack, 0),
+                ("defense", unit.defense, 0),
+                ("movement", unit.movement, 0),
+                ("range", unit.range, 1),
+                ("sight", unit.sight, 0),
+                ("cost", unit.cost, 0),
+                ("upkeep", unit.upkeep, 0),
+                ("ammo", unit.ammo, 0),
+            ];
+            for (name, value, min) in checks {
+                if value < min {
+                    errors.push(DefinitionError::new(file, field(name), format!("must be at least {}, got {}", min, value)));
+                }
+            }
+            
+            if unit.promotions.is_empty() {
+                errors.push(DefinitionError::new(file, field("promotions"), "must list at least one promotion"));
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(Self { units: parsed.unit })
+        } else {
+            Err(errors)
+        }
+    }
+    
+    // Definition for a unit type id
+    pub fn get(&self, unit_type: &UnitType) -> Option<&UnitDefinition> {
+        self.units.iter().find(|unit| unit.id == *unit_type)
+    }
+    
+    // Movement classes used by any unit type
+    pub fn classes(&self) -> HashSet<&str> {
+        self.units.iter().map(|unit| unit.class.as_str()).collect()
+    }
+}
+
+// Open terrain type id, e.g. "forest"; the definitions file decides which ids exist
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct TerrainType(pub String);
+
+impl TerrainType {
+    pub fn new(id: &str) -> Self {
+        Self(id.to_string())
+    }
+    
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for TerrainType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+// Movement points to enter a hex, written as a number or "impassable"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "RawMovementCost", into = "RawMovementCost")]
+pub enum MovementCost {
+    Points(i32),
+    Impassable,
+}
+
+impl MovementCost {
+    pub fn points(self) -> Option<i32> {
+        match self {
+            MovementCost::Points(points) => Some(points),
+            MovementCost::Impassable => None,
+        }
+    }
+}
+
+// How a movement cost looks in TOML
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum RawMovementCost {
+    Points(i32),
+    Keyword(String),
+}
+
+impl TryFrom<RawMovementCost> for MovementCost {
+    type Error = String;
+    
+    fn try_from(raw: RawMovementCost) -> Result<Self, Self::Error> {
+        match raw {
+            RawMovementCost::Points(points) => Ok(MovementCost::Points(points)),
+            RawMovementCost::Keyword(keyword) if keyword == "impassable" => Ok(MovementCost::Impassable),
+            RawMovementCost::Keyword(keyword) => Err(format!("expected a number or \"impassable\", got \"{}\"", keyword)),
+        }
+    }
+}
+
+impl From<MovementCost> for RawMovementCost {
+    fn from(cost: MovementCost) -> Self {
+        match cost {
+            MovementCost::Points(points) => RawMovementCost::Points(points),
+            MovementCost::Impassable => RawMovementCost::Keyword("impassable".to_string()),
+        }
+    }
+}
+
+// One terrain type as written in the definitions file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct TerrainDefinition {
+    pub id: TerrainType,
+    pub name: String,               // Localization key for the display name
+    pub color: [u8; 3],
+    #[serde(default)]
+    pub texture: Option<String>,    // Image path relative to the assets directory
+    pub movement_cost: MovementCost,                   // For unit classes not listed below
+    #[serde(default)]
+    pub movement_costs: HashMap<String, MovementCost>, // Per unit class overrides
+    #[serde(default)]
+    pub defense_bonus: i32,
+    #[serde(default)]
+    pub blocks_sight: bool, // Ranged attacks and abilities can't reach past it
+}
+
+impl TerrainDefinition {
+    pub fn color(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+    
+    // Movement points a unit of `class` spends to enter, None if it can't
+    pub fn movement_cost_for(&self, class: &str) -> Option<i32> {
+        self.movement_costs.get(class).copied().unwrap_or(self.movement_cost).points()
+    }
+
+}
+
+// Terrain the random map generator uses for each kind of land
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct GeneratorTerrain {
+    pub open: TerrainType,  // Everywhere else, and spawn areas
+    pub woods: TerrainType, // Wettest ground
+    pub peaks: TerrainType, // Ridges
+    pub lake: TerrainType,  // Lowest ground
+}
+
+// Layout of the terrain definitions file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct TerrainFile {
+    generator: GeneratorTerrain,
+    #[serde(default)]
+    terrain: Vec<TerrainDefinition>,
+}
+
+// All known terrain types, in file order (singleton, on the map settings entity)
+#[derive(Debug, Clone)]
+pub struct TerrainCatalog {
+    pub terrain: Vec<TerrainDefinition>,
+    pub generator: GeneratorTerrain,
+}
+
+impl TerrainCatalog {
+    // Read and check a terrain definitions file
+    pub fn load(path: &str) -> Result<Self, Vec<DefinitionError>> {
+        let text = fs::read_to_string(path).map_err(|e| vec![DefinitionError::new(path, "", e.to_string())])?;
+        Self::parse(&text, path)
+    }
+    
+    // Parse and check terrain definitions; `file` is only used in error messages
+    pub fn parse(text: &str, file: &str) -> Result<Self, Vec<DefinitionError>> {
+        let parsed: TerrainFile = toml::from_str(text).map_err(|e| vec![DefinitionError::from_toml(file, text, &e)])?;
+        
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        
+        if parsed.terrain.is_empty() {
+            errors.push(DefinitionError::new(file, "terrain", "no terrain types defined"));
+        }
+        
+        for (index, terrain) in parsed.terrain.iter().enumerate() {
+            let field = |name: &str| format!("terrain[{}].{}", index, name);
+            
+            if terrain.id.0.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("id"), "must not be empty"));
+            } else if !seen.insert(terrain.id.clone()) {
+                errors.push(DefinitionError::new(file, field("id"), format!("duplicate id \"{}\"", terrain.id)));
+            }
+            
+            if terrain.name.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("name"), "must not be empty"));
+            }
+            
+            let costs = std::iter::once((field("movement_cost"), terrain.movement_cost))
+                .chain(terrain.movement_costs.iter().map(|(class, &cost)| (field(&format!("movement_costs.{}", class)), cost)));
+            for (name, cost) in costs {
+                if let MovementCost::Points(points @ ..=0) = cost {
+                    errors.push(DefinitionError::new(file, name, format!("must be at least 1 or \"impassable\", got {}", points)));
+                }
+            }
+        }
+        
+        // The generator can only use terrain that exists, and spawns need somewhere to stand
+        let generator = &parsed.generator;
+        for (name, id) in [("open", &generator.open), ("woods", &generator.woods), ("peaks", &generator.peaks), ("lake", &generator.lake)] {
+            match parsed.terrain.iter().find(|terrain| terrain.id == *id) {
+                None => errors.push(DefinitionError::new(file, format!("generator.{}", name), format!("unknown terrain \"{}\"", id))),
+                Some(terrain) if name == "open" && terrain.movement_cost == MovementCost::Impassable => {
+                    errors.push(DefinitionError::new(file, "generator.open", format!("\"{}\" must be passable", id)));
+                },
+                Some(_) => {},
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(Self { terrain: parsed.terrain, generator: parsed.generator })
+        } else {
+            Err(errors)
+        }
+    }
+    
+    // Definition for a terrain type id
+    pub fn get(&self, terrain_type: &TerrainType) -> Option<&TerrainDefinition> {
+        self.terrain.iter().find(|terrain| terrain.id == *terrain_type)
+    }
+}
+
+// Everything loaded from the data directory
+#[derive(Debug, Clone)]
+pub struct GameData {
+    pub units: UnitCatalog,
+    pub terrain: TerrainCatalog,
+    pub abilities: AbilityCatalog,
+    pub statuses: StatusCatalog,
+}
+
+impl GameData {
+    // Load all definition files, collecting the problems from every one of them
+    pub fn load() -> Result<Self, Vec<DefinitionError>> {
+        let files = (
+            UnitCatalog::load(UNITS_PATH),
+            TerrainCatalog::load(TERRAIN_PATH),
+            AbilityCatalog::load(ABILITIES_PATH),
+            StatusCatalog::load(STATUSES_PATH),
+        );
+        match files {
+            (Ok(units), Ok(terrain), Ok(abilities), Ok(statuses)) => Self::new(units, terrain, abilities, statuses),
+            (units, terrain, abilities, statuses) => Err(units.err().into_iter()
+                .chain(terrain.err())
+                .chain(abilities.err())
+                .chain(statuses.err())
+                .flatten()
+                .collect()),
+        }
+    }
+    
+    // Check that the files agree with each other
+    pub fn new(units: UnitCatalog, terrain: TerrainCatalog, abilities: AbilityCatalog, statuses: StatusCatalog) -> Result<Self, Vec<DefinitionError>> {
+        // A cost for a class no unit has is almost certainly a typo
+        let classes = units.classes();
+        let mut errors = Vec::new();
+        for (index, definition) in terrain.terrain.iter().enumerate() {
+            let mut unknown: Vec<&String> = definition.movement_costs.keys().filter(|class| !classes.contains(class.as_str())).collect();
+            unknown.sort();
+            for class in unknown {
+                errors.push(DefinitionError::new(
+                    TERRAIN_PATH,
+                    format!("terrain[{}].movement_costs.{}", index, class),
+                    format!("no unit type has class \"{}\"", class),
+                ));
+            }
+        }
+        
+        // Units can only have abilities that are defined
+        for (index, unit) in units.units.iter().enumerate() {
+            for (slot, id) in unit.abilities.iter().enumerate() {
+                if abilities.get(id).is_none() {
+                    errors.push(DefinitionError::new(
+                        UNITS_PATH,
+                        format!("unit[{}].abilities[{}]", index, slot),
+                        format!("unknown ability \"{}\"", id),
+                    ));
+                }
+            }
+        }
+        
+        // Abilities can only leave status effects that are defined
+        for (index, ability) in abilities.abilities.iter().enumerate() {
+            if let Some(status) = ability.status.as_ref().filter(|status| statuses.get(&status.id).is_none()) {
+                errors.push(DefinitionError::new(
+                    ABILITIES_PATH,
+                    format!("ability[{}].status.id", index),
+                    format!("unknown status effect \"{}\"", status.id),
+                ));
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(Self { units, terrain, abilities, statuses })
+        } else {
+            Err(errors)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const UNITS: &str = r#"
+[[unit]]
+id = "infantry"
+name = "unit-type-infantry"
+max_health = 10
+attack = 3
+defense = 2
+movement = 2
+range = 1
+sight = 2
+
+[[unit]]
+id = "cavalry"
+name = "unit-type-cavalry"
+class = "mounted"
+max_health = 12
+attack = 4
+defense = 1
+movement = 4
+range = 1
+sight = 3
+"#;
+    
+    const TERRAIN: &str = r#"
+[generator]
+open = "plains"
+woods = "forest"
+peaks = "mountain"
+lake = "water"
+
+[[terrain]]
+id = "plains"
+name = "terrain-plains"
+color = [120, 180, 80]
+movement_cost = 1
+
+[[terrain]]
+id = "forest"
+name = "terrain-forest"
+color = [40, 110, 40]
+movement_cost = 2
+movement_costs = { mounted = 3 }
+
+[[terrain]]
+id = "mountain"
+name = "terrain-mountain"
+color = [130, 120, 110]
+movement_cost = 3
+movement_costs = { mounted = "impassable" }
+
+[[terrain]]
+id = "water"
+name = "terrain-water"
+color = [60, 110, 200]
+movement_cost = "impassable"
+"#;
+    
+    fn fields(errors: &[DefinitionError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+    
+    #[test]
+    fn sample_definitions_load() {
+        let units = UnitCatalog::parse(UNITS, "units.toml").unwrap();
+        let terrain = TerrainCatalog::parse(TERRAIN, "terrain.toml").unwrap();
+        
+        assert_eq!(units.units[0].class, DEFAULT_UNIT_CLASS);
+        let mountain = terrain.get(&TerrainType::new("mountain")).unwrap();
+        assert_eq!(mountain.movement_cost_for("foot"), Some(3));
+        assert_eq!(mountain.movement_cost_for("mounted"), None);
+        assert!(GameData::new(units, terrain, AbilityCatalog::default(), StatusCatalog::default()).is_ok());
+    }
+    
+    #[test]
+    fn bundled_definitions_load() {
+        if let Err(errors) = GameData::load() {
+            panic!("{:?}", errors);
+        }
+    }
+    
+    #[test]
+    fn duplicate_unit_id() {
+        let text = UNITS.replace("id = \"cavalry\"", "id = \"infantry\"");
+        let errors = UnitCatalog::parse(&text, "units.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["unit[1].id"]);
+        assert!(errors[0].message.contains("duplicate"), "{:?}", errors);
+    }
+    
+    #[test]
+    fn duplicate_terrain_id() {
+        let text = TERRAIN.replace("id = \"water\"", "id = \"plains\"");
+        let errors = TerrainCatalog::parse(&text, "terrain.toml").unwrap_err();
+        // The lake terrain is gone too, so the generator can't find it
+        assert_eq!(fields(&errors), ["terrain[3].id", "generator.lake"]);
+    }
+    
+    #[test]
+    fn stat_below_minimum() {
+        let text = UNITS.replace("max_health = 12", "max_health = 0").replace("movement = 2", "movement = -1");
+        let errors = UnitCatalog::parse(&text, "units.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["unit[0].movement", "unit[1].max_health"]);
+        assert_eq!(errors[1].message, "must be at least 1, got 0");
+    }
+    
+    #[test]
+    fn terrain_cost_below_one() {
+        let text = TERRAIN.replace("movement_costs = { mounted = 3 }", "movement_costs = { mounted = 0 }");
+        let errors = TerrainCatalog::parse(&text, "terrain.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["terrain[1].movement_costs.mounted"]);
+    }
+    
+    #[test]
+    fn unknown_movement_class() {
+        let units = UnitCatalog::parse(UNITS, "units.toml").unwrap();
+        let text = TERRAIN.replace("movement_costs = { mounted = 3 }", "movement_costs = { mounted = 3, wheeled = 4 }");
+        let terrain = TerrainCatalog::parse(&text, "terrain.toml").unwrap();
+        
+        let errors = GameData::new(units, terrain, AbilityCatalog::default(), StatusCatalog::default()).unwrap_err();
+        assert_eq!(fields(&errors), ["terrain[1].movement_costs.wheeled"]);
+        assert_eq!(errors[0].file, TERRAIN_PATH);
+    }
+    
+    #[test]
+    fn impassable_keyword() {
+        let cost: MovementCost = toml::Value::String("impassable".to_string()).try_into().unwrap();
+        assert_eq!(cost, MovementCost::Impassable);
+        
+        let text = TERRAIN.replace("movement_cost = \"impassable\"", "movement_cost = \"blocked\"");
+        let errors = TerrainCatalog::parse(&text, "terrain.toml").unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "line 32");
+        assert!(errors[0].message.contains("\"blocked\""), "{:?}", errors);
+    }
+    
+    #[test]
+    fn unknown_generator_terrain() {
+        let text = TERRAIN.replace("peaks = \"mountain\"", "peaks = \"volcano\"");
+        let errors = TerrainCatalog::parse(&text, "terrain.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["generator.peaks"]);
+        assert_eq!(errors[0].message, "unknown terrain \"volcano\"");
+    }
+    
+    #[test]
+    fn impassable_generator_open() {
+        let text = TERRAIN.replace("open = \"plains\"", "open = \"water\"");
+        let errors = TerrainCatalog::parse(&text, "terrain.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["generator.open"]);
+    }
+    
+    #[test]
+    fn syntax_error_names_the_line() {
+        let text = UNITS.replace("attack = 4", "attack = = 4");
+        let errors = UnitCatalog::parse(&text, "units.toml").unwrap_err();
+        assert_eq!(fields(&errors), ["line 17"]);
+        assert_eq!(errors[0].to_string(), format!("units.toml: line 17: {}", errors[0].message));
+    }
+}
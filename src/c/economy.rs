@@ -0,0 +1,35 @@
+use std::collections::HashMap;
+use crate::c::definitions::UnitType;
+use crate::c::team::Teams;
+
+// Gold each team starts a game with, unless a scenario says otherwise
+pub const STARTING_GOLD: i32 = 20;
+
+// Gold every team gets each turn on top of its settlements' income
+pub const BASE_INCOME: i32 = 2;
+
+// Gold and recruitment options of every team (singleton, on the game state entity)
+#[derive(Debug, Clone, Default)]
+pub struct Treasury {
+    pub gold: HashMap<u8, i32>,
+    pub recruits: HashMap<u8, Vec<UnitType>>, // Unit types a team may buy; teams without an entry may buy any
+}
+
+impl Treasury {
+    // Every team with the starting gold and free choice of units
+    pub fn new(teams: &Teams) -> Self {
+        Self {
+            gold: teams.teams.iter().map(|team| (team.id, STARTING_GOLD)).collect(),
+            recruits: HashMap::new(),
+        }
+    }
+    
+    pub fn gold(&self, team_id: u8) -> i32 {
+        self.gold.get(&team_id).copied().unwrap_or(0)
+    }
+    
+    // Whether a team may buy units of a type
+    pub fn can_recruit(&self, team_id: u8, unit_type: &UnitType) -> bool {
+        self.recruits.get(&team_id).is_none_or(|recruits| recruits.contains(unit_type))
+    }
+}
@@ -1,14 +1,10 @@
-use eframe::egui::{Color32, Pos2};
+use eframe::egui::Pos2;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::e::entity::Entity;
-
-// 单位类型枚举
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum UnitType {
-    Infantry,
-    Archer,
-    Cavalry,
-}
+use crate::c::coords::{OffsetCoord, OffsetKind};
+use crate::c::layout::{HexLayout, HexOrientation};
+use crate::c::definitions::{TerrainType, UnitType};
 
 // Hexagonal coordinate system (using axial coordinates)
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
@@ -52,81 +48,176 @@ impl HexCoord {
         ((x1 - x2).abs() + (y1 - y2).abs() + (z1 - z2).abs()) / 2
     }
     
-    // Convert pixel position to hex coordinate
-    pub fn from_pixel(pos: Pos2, hex_size: f32, origin: Pos2) -> Self {
-        let x = pos.x - origin.x;
-        let y = pos.y - origin.y;
-        
-        // Use formulas that don't require SQRT_3 constant
-        let q_float = (x * (2.0/3.0)) / hex_size;
-        let r_float = ((-x / 3.0) + ((y * (3.0_f32.sqrt())) / 3.0)) / hex_size;
-        
-        // Round to the nearest hex
-        let mut q = q_float.round() as i32;
-        let mut r = r_float.round() as i32;
-        let s = (-q - r) as f32;
+    // Every hex within `radius` steps of this one, itself included
+    pub fn within(&self, radius: i32) -> Vec<HexCoord> {
+        let mut hexes = Vec::new();
+        for dq in -radius..=radius {
+            for dr in (-radius).max(-dq - radius)..=radius.min(-dq + radius) {
+                hexes.push(HexCoord::new(self.q + dq, self.r + dr));
+            }
+        }
+        hexes
+    }
+    
+    // Hexes on the straight line from this hex to another, both ends included. The line is
+    // nudged off the exact centres so where it runs along an edge it always takes the same side.
+    pub fn line_to(&self, other: &HexCoord) -> Vec<HexCoord> {
+        let steps = self.distance(other);
+        (0..=steps)
+            .map(|step| {
+                let t = if steps == 0 { 0.0 } else { step as f32 / steps as f32 };
+                let q = self.q as f32 + (other.q - self.q) as f32 * t + 1e-3;
+                let r = self.r as f32 + (other.r - self.r) as f32 * t + 2e-3;
+                HexCoord::round(q, r)
+            })
+            .collect()
+    }
+    
+    // Round fractional axial coordinates to the nearest hex
+    pub fn round(q_float: f32, r_float: f32) -> Self {
+        // Round all three cube coordinates, then fix the one that moved furthest
+        let s_float = -q_float - r_float;
+        let (q, r, s) = (q_float.round(), r_float.round(), s_float.round());
         
-        let q_diff = (q as f32 - q_float).abs();
-        let r_diff = (r as f32 - r_float).abs();
-        let s_diff = (s - (-q_float - r_float)).abs();
+        let q_diff = (q - q_float).abs();
+        let r_diff = (r - r_float).abs();
+        let s_diff = (s - s_float).abs();
         
         if q_diff > r_diff && q_diff > s_diff {
-            q = -r - s as i32;
+            Self { q: (-r - s) as i32, r: r as i32 }
         } else if r_diff > s_diff {
-            r = -q - s as i32;
+            Self { q: q as i32, r: (-q - s) as i32 }
+        } else {
+            Self { q: q as i32, r: r as i32 }
         }
-        
-        Self { q, r }
+    }
+    
+    // Convert pixel position to hex coordinate
+    pub fn from_pixel(pos: Pos2, layout: &HexLayout) -> Self {
+        layout.pixel_to_hex(pos)
     }
     
     // Convert hex coordinate to pixel position
-    pub fn to_pixel(&self, hex_size: f32, origin: Pos2) -> Pos2 {
-        let x = hex_size * (3.0/2.0) * self.q as f32;
-        let y = hex_size * (3.0_f32.sqrt()) * (self.r as f32 + self.q as f32 / 2.0);
-        
-        Pos2::new(origin.x + x, origin.y + y)
+    pub fn to_pixel(&self, layout: &HexLayout) -> Pos2 {
+        layout.hex_to_pixel(self)
     }
 }
 
-// Terrain types for hex tiles
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-pub enum TerrainType {
-    Plain,
-    Forest, 
-    Mountain,
-    Water,
+// Overall outline of the map
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MapShape {
+    RectangleOddQ,  // Rectangle in odd-q offset layout (odd columns shoved down), for flat-top hexes
+    RectangleEvenQ, // Rectangle in even-q offset layout (even columns shoved down), for flat-top hexes
+    RectangleOddR,  // Rectangle in odd-r offset layout (odd rows shoved right), for pointy-top hexes
+    RectangleEvenR, // Rectangle in even-r offset layout (even rows shoved right), for pointy-top hexes
+    Hexagon,        // Regular hexagon, radius is half the smaller dimension
+    Parallelogram,  // Axial q/r ranges, skewed on screen
+    Triangle,       // Triangle with side length equal to the width
 }
 
-impl TerrainType {
-    // Get color for this terrain type
-    pub fn color(&self) -> Color32 {
+impl MapShape {
+    pub fn all() -> Vec<MapShape> {
+        vec![
+            MapShape::RectangleOddQ,
+            MapShape::RectangleEvenQ,
+            MapShape::RectangleOddR,
+            MapShape::RectangleEvenR,
+            MapShape::Hexagon,
+            MapShape::Parallelogram,
+            MapShape::Triangle,
+        ]
+    }
+    
+    // Localization key for this shape's display name
+    pub fn name_key(&self) -> &'static str {
         match self {
-            TerrainType::Plain => Color32::from_rgb(124, 252, 0),    // Light green
-            TerrainType::Forest => Color32::from_rgb(34, 139, 34),    // Forest green
-            TerrainType::Mountain => Color32::from_rgb(128, 128, 128), // Gray
-            TerrainType::Water => Color32::from_rgb(65, 105, 225),    // Royal blue
+            MapShape::RectangleOddQ => "shape-rectangle-odd-q",
+            MapShape::RectangleEvenQ => "shape-rectangle-even-q",
+            MapShape::RectangleOddR => "shape-rectangle-odd-r",
+            MapShape::RectangleEvenR => "shape-rectangle-even-r",
+            MapShape::Hexagon => "shape-hexagon",
+            MapShape::Parallelogram => "shape-parallelogram",
+            MapShape::Triangle => "shape-triangle",
         }
     }
     
-    // Get movement cost for this terrain
-    pub fn movement_cost(&self) -> i32 {
+    // The same shape with its rectangle rows or columns shoved to suit an orientation;
+    // a column-offset rectangle of pointy-top hexes comes out as a jagged rhombus
+    pub fn for_orientation(&self, orientation: HexOrientation) -> MapShape {
+        match (self, orientation) {
+            (MapShape::RectangleOddQ, HexOrientation::Pointy) => MapShape::RectangleOddR,
+            (MapShape::RectangleEvenQ, HexOrientation::Pointy) => MapShape::RectangleEvenR,
+            (MapShape::RectangleOddR, HexOrientation::Flat) => MapShape::RectangleOddQ,
+            (MapShape::RectangleEvenR, HexOrientation::Flat) => MapShape::RectangleEvenQ,
+            (shape, _) => *shape,
+        }
+    }
+    
+    // List every hex coordinate that belongs to a map of this shape
+    pub fn coords(&self, width: i32, height: i32) -> Vec<HexCoord> {
+        let mut coords = Vec::new();
+        
+        let mut rectangle = |kind: OffsetKind| {
+            for col in 0..width {
+                for row in 0..height {
+                    coords.push(OffsetCoord::new(col, row, kind).into());
+                }
+            }
+        };
+        
         match self {
-            TerrainType::Plain => 1,
-            TerrainType::Forest => 2,
-            TerrainType::Mountain => 3,
-            TerrainType::Water => 5,
+            MapShape::RectangleOddQ => rectangle(OffsetKind::OddQ),
+            MapShape::RectangleEvenQ => rectangle(OffsetKind::EvenQ),
+            MapShape::RectangleOddR => rectangle(OffsetKind::OddR),
+            MapShape::RectangleEvenR => rectangle(OffsetKind::EvenR),
+            MapShape::Hexagon => {
+                let radius = Self::hexagon_radius(width, height);
+                for q in -radius..=radius {
+                    let r_min = (-radius).max(-q - radius);
+                    let r_max = radius.min(-q + radius);
+                    for r in r_min..=r_max {
+                        coords.push(HexCoord::new(q, r));
+                    }
+                }
+            },
+            MapShape::Parallelogram => {
+                for q in 0..width {
+                    for r in 0..height {
+                        coords.push(HexCoord::new(q, r));
+                    }
+                }
+            },
+            MapShape::Triangle => {
+                for q in 0..width {
+                    for r in 0..(width - q) {
+                        coords.push(HexCoord::new(q, r));
+                    }
+                }
+            },
         }
+        
+        coords
     }
     
-    // Get terrain name as string
-    pub fn name(&self) -> &'static str {
+    // The hex at a column and row of the map's grid, counted from the top-left corner.
+    // Rectangles use their offset layout, the other shapes axial q and r from their smallest values.
+    pub fn grid_to_hex(&self, width: i32, height: i32, col: i32, row: i32) -> HexCoord {
         match self {
-            TerrainType::Plain => "Plain",
-            TerrainType::Forest => "Forest",
-            TerrainType::Mountain => "Mountain",
-            TerrainType::Water => "Water",
+            MapShape::RectangleOddQ => OffsetCoord::new(col, row, OffsetKind::OddQ).into(),
+            MapShape::RectangleEvenQ => OffsetCoord::new(col, row, OffsetKind::EvenQ).into(),
+            MapShape::RectangleOddR => OffsetCoord::new(col, row, OffsetKind::OddR).into(),
+            MapShape::RectangleEvenR => OffsetCoord::new(col, row, OffsetKind::EvenR).into(),
+            MapShape::Hexagon => {
+                let radius = Self::hexagon_radius(width, height);
+                HexCoord::new(col - radius, row - radius)
+            },
+            MapShape::Parallelogram | MapShape::Triangle => HexCoord::new(col, row),
         }
     }
+    
+    fn hexagon_radius(width: i32, height: i32) -> i32 {
+        (width.min(height) / 2).max(1)
+    }
 }
 
 // ===== HEX MAP COMPONENTS =====
@@ -144,26 +235,64 @@ pub struct Terrain {
 }
 
 // Unit stats component
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UnitStats {
     pub unit_type: UnitType,
+    pub class: String, // Movement class, see TerrainDefinition::movement_cost_for
     pub max_health: i32,
     pub attack: i32,
     pub defense: i32,
     pub movement: i32,
     pub range: i32,
+    pub sight: i32,
+    pub max_ammo: i32, // Ranged shots between resupplies under the supply rule, 0 for none needed
 }
 
+// Morale of a fresh unit; at zero it routs
+pub const MAX_MORALE: i32 = 10;
+
 // Current state of a unit (health, movement left, etc.)
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct UnitState {
     pub health: i32,
     pub movement_left: i32,
     pub has_acted: bool,
+    pub rested: bool, // Hasn't moved or fought since its turn began
+    pub ammo: i32,
+    pub morale: i32,  // 0 to MAX_MORALE
+}
+
+impl UnitState {
+    // Out of the player's control, fleeing from the enemy
+    pub fn routing(&self) -> bool {
+        self.morale <= 0
+    }
 }
 
 // Hex entity map component - stores mapping between HexCoord and Entity
 #[derive(Clone, Debug)]
 pub struct HexEntityMap {
     pub map: HashMap<HexCoord, Entity>,
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    #[test]
+    fn lines_step_one_hex_at_a_time() {
+        let from = HexCoord::new(-2, 1);
+        for to in from.within(5) {
+            let line = from.line_to(&to);
+            assert_eq!(line.len() as i32, from.distance(&to) + 1);
+            assert_eq!((line[0], line[line.len() - 1]), (from, to));
+            assert!(line.windows(2).all(|pair| pair[0].distance(&pair[1]) == 1), "{:?} to {:?}: {:?}", from, to, line);
+        }
+    }
+    
+    #[test]
+    fn lines_along_an_edge_keep_to_one_side() {
+        // Straight along a row, then between two rows where the line runs along hex edges
+        assert_eq!(HexCoord::new(0, 0).line_to(&HexCoord::new(3, 0)), [(0, 0), (1, 0), (2, 0), (3, 0)].map(|(q, r)| HexCoord::new(q, r)));
+        assert_eq!(HexCoord::new(0, 0).line_to(&HexCoord::new(1, 1)), [(0, 0), (0, 1), (1, 1)].map(|(q, r)| HexCoord::new(q, r)));
+    }
+}
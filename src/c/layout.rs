@@ -0,0 +1,207 @@
+use eframe::egui::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+use crate::c::hex::HexCoord;
+
+// Whether hexes have a flat edge or a corner at the top
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HexOrientation {
+    Flat,
+    Pointy,
+}
+
+impl HexOrientation {
+    pub fn all() -> Vec<HexOrientation> {
+        vec![HexOrientation::Flat, HexOrientation::Pointy]
+    }
+    
+    // Localization key for this orientation's display name
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            HexOrientation::Flat => "orientation-flat",
+            HexOrientation::Pointy => "orientation-pointy",
+        }
+    }
+    
+    // Axial to pixel matrix (f0, f1, f2, f3) for a hex of size 1
+    fn forward(&self) -> [f32; 4] {
+        let sqrt3 = 3.0_f32.sqrt();
+        match self {
+            HexOrientation::Flat => [1.5, 0.0, sqrt3 / 2.0, sqrt3],
+            HexOrientation::Pointy => [sqrt3, sqrt3 / 2.0, 0.0, 1.5],
+        }
+    }
+    
+    // Pixel to axial matrix (b0, b1, b2, b3), the inverse of forward
+    fn backward(&self) -> [f32; 4] {
+        let sqrt3 = 3.0_f32.sqrt();
+        match self {
+            HexOrientation::Flat => [2.0 / 3.0, 0.0, -1.0 / 3.0, sqrt3 / 3.0],
+            HexOrientation::Pointy => [sqrt3 / 3.0, -1.0 / 3.0, 0.0, 2.0 / 3.0],
+        }
+    }
+    
+    // Angle of the first corner, in units of 60 degrees
+    fn start_angle(&self) -> f32 {
+        match self {
+            HexOrientation::Flat => 0.0,
+            HexOrientation::Pointy => 0.5,
+        }
+    }
+}
+
+// Maps between hex coordinates and screen pixels
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct HexLayout {
+    pub orientation: HexOrientation,
+    pub size: Vec2, // Corner radius along x and y, unequal sizes stretch the hexes
+    pub origin: Pos2, // Pixel position of hex (0, 0)
+}
+
+impl HexLayout {
+    pub fn new(orientation: HexOrientation, size: Vec2, origin: Pos2) -> Self {
+        Self { orientation, size, origin }
+    }
+    
+    // Convert hex coordinate to the pixel position of its centre
+    pub fn hex_to_pixel(&self, hex: &HexCoord) -> Pos2 {
+        let [f0, f1, f2, f3] = self.orientation.forward();
+        let (q, r) = (hex.q as f32, hex.r as f32);
+        let x = (f0 * q + f1 * r) * self.size.x;
+        let y = (f2 * q + f3 * r) * self.size.y;
+        
+        Pos2::new(self.origin.x + x, self.origin.y + y)
+    }
+    
+    // Convert pixel position to fractional axial coordinates (q, r)
+    pub fn pixel_to_fractional(&self, pos: Pos2) -> (f32, f32) {
+        let [b0, b1, b2, b3] = self.orientation.backward();
+        let x = (pos.x - self.origin.x) / self.size.x;
+        let y = (pos.y - self.origin.y) / self.size.y;
+        
+        (b0 * x + b1 * y, b2 * x + b3 * y)
+    }
+    
+    // Convert pixel position to the hex that contains it
+    pub fn pixel_to_hex(&self, pos: Pos2) -> HexCoord {
+        let (q, r) = self.pixel_to_fractional(pos);
+        HexCoord::round(q, r)
+    }
+    
+    // Offset from a hex centre to corner `i` (0-5), scaled by `scale`
+    pub fn corner_offset(&self, i: usize, scale: f32) -> Vec2 {
+        let angle = std::f32::consts::PI / 3.0 * (self.orientation.start_angle() + i as f32);
+        Vec2::new(self.size.x * angle.cos(), self.size.y * angle.sin()) * scale
+    }
+    
+    // Corners of a hex drawn around `center`, scaled by `scale` (1.0 is a full tile)
+    pub fn corners_at(&self, center: Pos2, scale: f32) -> [Pos2; 6] {
+        std::array::from_fn(|i| center + self.corner_offset(i, scale))
+    }
+    
+    // Corners of the given hex
+    pub fn corners(&self, hex: &HexCoord) -> [Pos2; 6] {
+        self.corners_at(self.hex_to_pixel(hex), 1.0)
+    }
+    
+    // The two corners of the edge shared with the neighbor in `direction` (index into HexCoord::neighbors)
+    pub fn edge(&self, hex: &HexCoord, direction: usize) -> [Pos2; 2] {
+        let corners = self.corners(hex);
+        let neighbor = self.hex_to_pixel(&hex.neighbors()[direction % 6]);
+        
+        // The shared edge is made of the two corners closest to the neighbor's centre
+        let mut by_distance = corners;
+        by_distance.sort_by(|a, b| a.distance_sq(neighbor).total_cmp(&b.distance_sq(neighbor)));
+        [by_distance[0], by_distance[1]]
+    }
+    
+    // Smaller of the two radii, for sizing round things like units
+    pub fn min_size(&self) -> f32 {
+        self.size.x.min(self.size.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::coords::{OffsetCoord, OffsetKind};
+    use crate::c::hex::MapShape;
+    
+    // Stretched wider than tall, and off the screen origin
+    fn layout(orientation: HexOrientation) -> HexLayout {
+        HexLayout::new(orientation, Vec2::new(30.0, 18.0), Pos2::new(-140.0, 75.0))
+    }
+    
+    fn sample_hexes() -> Vec<HexCoord> {
+        MapShape::Hexagon.coords(13, 13).into_iter().chain([HexCoord::new(-40, 25), HexCoord::new(100, -100)]).collect()
+    }
+    
+    #[test]
+    fn pixel_round_trip() {
+        for orientation in HexOrientation::all() {
+            let layout = layout(orientation);
+            for hex in sample_hexes() {
+                assert_eq!(layout.pixel_to_hex(layout.hex_to_pixel(&hex)), hex, "{:?} {:?}", hex, orientation);
+            }
+        }
+    }
+    
+    #[test]
+    fn corners_stay_in_their_hex() {
+        // Points just inside each corner still belong to the hex
+        for orientation in HexOrientation::all() {
+            let layout = layout(orientation);
+            for hex in sample_hexes() {
+                for i in 0..6 {
+                    let point = layout.hex_to_pixel(&hex) + layout.corner_offset(i, 0.9);
+                    assert_eq!(layout.pixel_to_hex(point), hex, "{:?} corner {} {:?}", hex, i, orientation);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn neighbors_share_an_edge() {
+        for orientation in HexOrientation::all() {
+            let layout = layout(orientation);
+            let hex = HexCoord::new(2, -1);
+            for (direction, neighbor) in hex.neighbors().iter().enumerate() {
+                let mut edge = layout.edge(&hex, direction);
+                let mut other = layout.edge(neighbor, (direction + 3) % 6);
+                edge.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+                other.sort_by(|a, b| a.x.total_cmp(&b.x).then(a.y.total_cmp(&b.y)));
+                for (a, b) in edge.iter().zip(&other) {
+                    assert!(a.distance(*b) < 0.01, "{:?} direction {} {:?}", hex, direction, orientation);
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn rectangles_line_up_with_their_orientation() {
+        // Flat-top rectangles have straight columns, pointy-top ones straight rows
+        for (shape, kind, orientation) in [
+            (MapShape::RectangleOddQ, OffsetKind::OddQ, HexOrientation::Flat),
+            (MapShape::RectangleEvenQ, OffsetKind::EvenQ, HexOrientation::Flat),
+            (MapShape::RectangleOddR, OffsetKind::OddR, HexOrientation::Pointy),
+            (MapShape::RectangleEvenR, OffsetKind::EvenR, HexOrientation::Pointy),
+        ] {
+            assert_eq!(shape.for_orientation(orientation), shape);
+            let layout = layout(orientation);
+            for line in 0..4 {
+                let positions: Vec<f32> = (0..5)
+                    .map(|step| {
+                        let (col, row) = if orientation == HexOrientation::Flat { (line, step) } else { (step, line) };
+                        let pixel = layout.hex_to_pixel(&OffsetCoord::new(col, row, kind).into());
+                        if orientation == HexOrientation::Flat { pixel.x } else { pixel.y }
+                    })
+                    .collect();
+                assert!(positions.iter().all(|&p| (p - positions[0]).abs() < 0.01), "{:?} line {}: {:?}", shape, line, positions);
+            }
+            assert_eq!(shape.coords(5, 5).len(), 25);
+        }
+        
+        assert_eq!(MapShape::RectangleOddQ.for_orientation(HexOrientation::Pointy), MapShape::RectangleOddR);
+        assert_eq!(MapShape::RectangleEvenR.for_orientation(HexOrientation::Flat), MapShape::RectangleEvenQ);
+        assert_eq!(MapShape::Hexagon.for_orientation(HexOrientation::Pointy), MapShape::Hexagon);
+    }
+}
@@ -2,6 +2,30 @@
 
 mod hex;
 mod core;
+mod coords;
+mod layout;
+mod animation;
+mod definitions;
+mod team;
+mod scenario;
+mod settlement;
+mod economy;
+mod veterancy;
+mod ability;
+mod status;
+mod save;
 
 pub use hex::*;
-pub use core::*; 
\ No newline at end of file
+pub use core::*;
+pub use coords::*;
+pub use layout::*;
+pub use animation::*;
+pub use definitions::*;
+pub use team::*;
+pub use scenario::*;
+pub use settlement::*;
+pub use economy::*;
+pub use veterancy::*;
+pub use ability::*;
+pub use status::*;
+pub use save::*;
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use crate::c::core::GameRules;
+use crate::c::ability::Abilities;
+use crate::c::definitions::{GameData, TerrainType, UnitType};
+use crate::c::hex::{MapShape, UnitState, UnitStats};
+use crate::c::settlement::Settlement;
+use crate::c::status::StatusEffects;
+use crate::c::team::Controller;
+use crate::c::veterancy::Veterancy;
+
+// Where the game is saved, relative to the working directory
+pub const SAVE_PATH: &str = "savegame.toml";
+
+// A game in progress, with everything needed to carry on playing it.
+// Hexes are written as axial [q, r] so saves don't depend on the map shape's grid.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveGame {
+    #[serde(default)]
+    pub scenario: Option<String>, // File of the scenario being played, None on random maps
+    pub current_turn: u8,
+    pub turn_number: i32,
+    pub rules: GameRules,
+    pub map: SavedMap,
+    #[serde(default)]
+    pub alliances: Vec<[u8; 2]>,
+    #[serde(default, rename = "team")]
+    pub teams: Vec<SavedTeam>,
+    #[serde(default, rename = "settlement")]
+    pub settlements: Vec<SavedSettlement>,
+    #[serde(default, rename = "unit")]
+    pub units: Vec<SavedUnit>,
+}
+
+// The board: its shape and the terrain of every hex in MapShape::coords order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedMap {
+    pub shape: MapShape,
+    pub width: i32,
+    pub height: i32,
+    pub terrain: Vec<TerrainType>,
+    #[serde(default)]
+    pub objectives: Vec<[i32; 2]>,
+}
+
+// One team with its treasury and progress towards victory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedTeam {
+    pub id: u8,
+    pub name: String,
+    pub color: [u8; 3],
+    pub controller: Controller,
+    pub gold: i32,
+    #[serde(default)]
+    pub recruits: Option<Vec<UnitType>>, // Unit types it may buy, None for any
+    #[serde(default)]
+    pub score: i32,
+    #[serde(default)]
+    pub held_turns: i32,
+}
+
+// A settlement with its owner and yields
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedSettlement {
+    pub at: [i32; 2],
+    #[serde(flatten)]
+    pub settlement: Settlement,
+}
+
+// A unit as it stands, including the experience, ranks and promotions it has earned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SavedUnit {
+    pub at: [i32; 2],
+    pub team: u8,
+    #[serde(default)]
+    pub leader: bool,
+    pub stats: UnitStats,
+    pub state: UnitState,
+    #[serde(default)]
+    pub veterancy: Veterancy,
+    #[serde(default)]
+    pub abilities: Abilities,
+    #[serde(default)]
+    pub statuses: StatusEffects,
+}
+
+impl SaveGame {
+    // Read and check the save file
+    pub fn load(data: &GameData) -> Result<Self, String> {
+        let text = fs::read_to_string(SAVE_PATH).map_err(|e| format!("{}: {}", SAVE_PATH, e))?;
+        Self::parse(&text, data).map_err(|e| format!("{}: {}", SAVE_PATH, e))
+    }
+    
+    // Parse a save and check it against the loaded definitions
+    pub fn parse(text: &str, data: &GameData) -> Result<Self, String> {
+        let save: SaveGame = toml::from_str(text).map_err(|e| e.message().to_string())?;
+        
+        let map = &save.map;
+        let hexes = map.shape.coords(map.width, map.height).len();
+        if map.terrain.len() != hexes {
+            return Err(format!("map.terrain: {} hexes for a map of {}", map.terrain.len(), hexes));
+        }
+        if let Some(terrain) = map.terrain.iter().find(|terrain| data.terrain.get(terrain).is_none()) {
+            return Err(format!("map.terrain: unknown terrain \"{}\"", terrain));
+        }
+        if let Some(unit) = save.units.iter().find(|unit| data.units.get(&unit.stats.unit_type).is_none()) {
+            return Err(format!("unit: unknown unit type \"{}\"", unit.stats.unit_type));
+        }
+        if save.teams.is_empty() {
+            return Err("team: no teams".to_string());
+        }
+        
+        Ok(save)
+    }
+    
+    // Write the save file, replacing any earlier save
+    pub fn save(&self) -> Result<(), String> {
+        let text = toml::to_string(self).map_err(|e| e.to_string())?;
+        fs::write(SAVE_PATH, text).map_err(|e| format!("{}: {}", SAVE_PATH, e))
+    }
+}
@@ -0,0 +1,717 @@
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use crate::c::coords::CoordSystem;
+use crate::c::core::{GameRules, VictoryCondition};
+use crate::c::definitions::{DefinitionError, GameData, MovementCost, TerrainType, UnitType};
+use crate::c::hex::{HexCoord, MapShape};
+use crate::c::economy::Treasury;
+use crate::c::settlement::{Settlement, SettlementKind};
+use crate::c::team::{Controller, TeamInfo, Teams, MAX_TEAMS, MIN_TEAMS, TEAM_COLORS};
+
+// Where scenario files are listed from, relative to the working directory
+pub const SCENARIOS_DIR: &str = "scenarios";
+
+// Language used for texts without a translation into the current one
+pub const FALLBACK_LANGUAGE: &str = "en-US";
+
+// Largest map width or height a scenario may ask for, same as the settings slider
+pub const MAX_SCENARIO_MAP_SIZE: i32 = 200;
+
+// Text written once, or once per language code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum LocalizedText {
+    Plain(String),
+    Translated(HashMap<String, String>),
+}
+
+impl LocalizedText {
+    // The text in a language, falling back to English and then to the first translation by code
+    pub fn get(&self, language: &str) -> &str {
+        match self {
+            LocalizedText::Plain(text) => text,
+            LocalizedText::Translated(texts) => texts.get(language)
+                .or_else(|| texts.get(FALLBACK_LANGUAGE))
+                .or_else(|| texts.iter().min_by_key(|(code, _)| *code).map(|(_, text)| text))
+                .map_or("", |text| text),
+        }
+    }
+    
+    pub fn is_blank(&self) -> bool {
+        match self {
+            LocalizedText::Plain(text) => text.trim().is_empty(),
+            LocalizedText::Translated(texts) => texts.values().all(|text| text.trim().is_empty()),
+        }
+    }
+}
+
+impl Default for LocalizedText {
+    fn default() -> Self {
+        LocalizedText::Plain(String::new())
+    }
+}
+
+// Optional rules a scenario turns on
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ScenarioRules {
+    pub zone_of_control: bool,
+    pub supply: bool,
+}
+
+// Hand-drawn map: a shape filled with one terrain, painted over by rows of symbols
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioMap {
+    pub shape: MapShape,
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub coords: Option<String>, // Convention for rows and positions, e.g. "odd-r"; the shape's own grid if left out
+    pub terrain: TerrainType, // Every hex the rows don't paint
+    #[serde(default)]
+    pub legend: HashMap<String, TerrainType>, // Single character symbol to terrain id
+    #[serde(default)]
+    pub rows: Vec<String>, // One symbol per hex in grid order, spaces leave the hex as it is
+    #[serde(default)]
+    pub objectives: Vec<[i32; 2]>, // Objective hexes by column and row
+    #[serde(default, rename = "settlement")]
+    pub settlements: Vec<ScenarioSettlement>,
+}
+
+// One settlement placed by a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioSettlement {
+    pub kind: SettlementKind,
+    pub at: [i32; 2], // Column and row in the map grid
+    #[serde(default)]
+    pub owner: Option<u8>, // Team, counting from 0; neutral if left out
+    #[serde(default)]
+    pub income: Option<i32>, // Overrides the usual yields for the kind
+    #[serde(default)]
+    pub healing: Option<i32>,
+}
+
+impl ScenarioMap {
+    // All hexes of the map
+    pub fn coords(&self) -> Vec<HexCoord> {
+        self.shape.coords(self.width, self.height)
+    }
+    
+    // Coordinate convention the map file is written in, None for the shape's own grid
+    pub fn coord_system(&self) -> Option<CoordSystem> {
+        self.coords.as_deref().and_then(CoordSystem::from_id)
+    }
+    
+    // The hex at a column and row, the grid both `rows` and unit positions use.
+    // None for positions the convention has no hex for, like odd sums in doubled coordinates.
+    pub fn grid_to_hex(&self, col: i32, row: i32) -> Option<HexCoord> {
+        match self.coord_system() {
+            Some(system) => system.to_hex(col, row),
+            None => Some(self.shape.grid_to_hex(self.width, self.height, col, row)),
+        }
+    }
+    
+    // Objective hexes on the hex grid
+    pub fn objective_coords(&self) -> Vec<HexCoord> {
+        self.objectives.iter().filter_map(|&[col, row]| self.grid_to_hex(col, row)).collect()
+    }
+    
+    // Settlements with their hexes, owners given as team ids
+    pub fn settlements(&self) -> Vec<(HexCoord, Settlement)> {
+        self.settlements.iter()
+            .filter_map(|site| {
+                let mut settlement = Settlement::new(site.kind, site.owner);
+                settlement.income = site.income.unwrap_or(settlement.income);
+                settlement.healing = site.healing.unwrap_or(settlement.healing);
+                Some((self.grid_to_hex(site.at[0], site.at[1])?, settlement))
+            })
+            .collect()
+    }
+    
+    // Terrain for every hex of the map; symbols missing from the legend and hexes off the map are skipped
+    pub fn terrain(&self) -> HashMap<HexCoord, TerrainType> {
+        let mut terrain: HashMap<HexCoord, TerrainType> = self.coords().into_iter().map(|coord| (coord, self.terrain.clone())).collect();
+        
+        for (row, symbol, col) in self.symbols() {
+            let tile = self.grid_to_hex(col, row).and_then(|coord| terrain.get_mut(&coord));
+            if let (Some(tile), Some(terrain_type)) = (tile, self.legend.get(&symbol.to_string())) {
+                *tile = terrain_type.clone();
+            }
+        }
+        
+        terrain
+    }
+    
+    // Every painted symbol in the rows, with its row and column
+    fn symbols(&self) -> impl Iterator<Item = (i32, char, i32)> + '_ {
+        self.rows.iter().enumerate().flat_map(|(row, line)| {
+            line.chars()
+                .enumerate()
+                .filter(|(_, symbol)| !symbol.is_whitespace())
+                .map(move |(col, symbol)| (row as i32, symbol, col as i32))
+        })
+    }
+}
+
+// One unit placed by a scenario
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioUnit {
+    #[serde(rename = "type")]
+    pub unit_type: UnitType,
+    pub at: [i32; 2], // Column and row in the map grid
+    #[serde(default)]
+    pub leader: bool, // See VictoryCondition::KillLeader
+}
+
+// One side in a scenario, in turn order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioTeam {
+    pub name: LocalizedText,
+    #[serde(default)]
+    pub color: Option<[u8; 3]>, // Defaults to the usual colour for the team's place in the list
+    #[serde(default = "default_controller")]
+    pub controller: Controller,
+    #[serde(default)]
+    pub alliance: u8, // Teams sharing a non-zero alliance are allied
+    #[serde(default)]
+    pub gold: Option<i32>, // Starting gold, STARTING_GOLD if not given
+    #[serde(default)]
+    pub recruit: Option<Vec<UnitType>>, // Unit types the team may buy, any if not given
+    #[serde(default, rename = "unit")]
+    pub units: Vec<ScenarioUnit>,
+}
+
+fn default_controller() -> Controller {
+    Controller::Human
+}
+
+fn default_victory() -> Vec<VictoryCondition> {
+    vec![VictoryCondition::Eliminate]
+}
+
+// A hand-made battle loaded from the scenarios directory
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Scenario {
+    pub name: LocalizedText,
+    #[serde(default)]
+    pub briefing: LocalizedText,
+    #[serde(default)]
+    pub turn_limit: Option<i32>,
+    #[serde(default)]
+    pub rules: ScenarioRules,
+    #[serde(default = "default_victory")]
+    pub victory: Vec<VictoryCondition>,
+    pub map: ScenarioMap,
+    #[serde(default, rename = "team")]
+    pub teams: Vec<ScenarioTeam>,
+    #[serde(skip)]
+    pub path: String, // File it was loaded from
+}
+
+impl Scenario {
+    // Read and check every scenario file in a directory, sorted by file name.
+    // Broken files are reported instead of stopping the others from loading.
+    pub fn load_all(dir: &str, data: &GameData) -> (Vec<Scenario>, Vec<DefinitionError>) {
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) => return (Vec::new(), vec![DefinitionError::new(dir, "", e.to_string())]),
+        };
+        
+        let mut paths: Vec<_> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().is_some_and(|extension| extension == "toml"))
+            .collect();
+        paths.sort();
+        
+        let mut scenarios = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            match Self::load(&path, data) {
+                Ok(scenario) => scenarios.push(scenario),
+                Err(file_errors) => errors.extend(file_errors),
+            }
+        }
+        
+        (scenarios, errors)
+    }
+    
+    // Read and check one scenario file
+    pub fn load(path: &Path, data: &GameData) -> Result<Self, Vec<DefinitionError>> {
+        let file = path.display().to_string();
+        let text = fs::read_to_string(path).map_err(|e| vec![DefinitionError::new(&file, "", e.to_string())])?;
+        Self::parse(&text, &file, data)
+    }
+    
+    // Parse and check a scenario against the loaded definitions; `file` names it in errors and is kept as its path
+    pub fn parse(text: &str, file: &str, data: &GameData) -> Result<Self, Vec<DefinitionError>> {
+        let mut scenario: Scenario = toml::from_str(text).map_err(|e| vec![DefinitionError::from_toml(file, text, &e)])?;
+        scenario.path = file.to_string();
+        
+        let errors = scenario.validate(file, data);
+        if errors.is_empty() {
+            Ok(scenario)
+        } else {
+            Err(errors)
+        }
+    }
+    
+    fn validate(&self, file: &str, data: &GameData) -> Vec<DefinitionError> {
+        let mut errors = Vec::new();
+        let mut error = |field: String, message: String| errors.push(DefinitionError::new(file, field, message));
+        
+        if self.name.is_blank() {
+            error("name".into(), "must not be empty".into());
+        }
+        
+        if let Some(limit @ ..=0) = self.turn_limit {
+            error("turn_limit".into(), format!("must be at least 1, got {}", limit));
+        }
+        
+        if self.victory.is_empty() {
+            error("victory".into(), "needs at least one condition".into());
+        }
+        
+        for (index, condition) in self.victory.iter().enumerate() {
+            let field = format!("victory[{}]", index);
+            match *condition {
+                VictoryCondition::Eliminate => {},
+                VictoryCondition::HoldObjectives { turns } => {
+                    if turns < 1 {
+                        error(format!("{}.turns", field), format!("must be at least 1, got {}", turns));
+                    }
+                    if self.map.objectives.is_empty() {
+                        error(field, "needs objective hexes in map.objectives".into());
+                    }
+                },
+                VictoryCondition::SurviveUntil { team, turn } => {
+                    if team as usize >= self.teams.len() {
+                        error(format!("{}.team", field), format!("there is no team {}, teams count from 0", team));
+                    }
+                    if turn < 1 {
+                        error(format!("{}.turn", field), format!("must be at least 1, got {}", turn));
+                    }
+                },
+                VictoryCondition::KillLeader => {
+                    if !self.teams.iter().flat_map(|team| &team.units).any(|unit| unit.leader) {
+                        error(field, "needs a unit with leader = true".into());
+                    }
+                },
+                VictoryCondition::HighestScore => {
+                    if self.turn_limit.is_none() {
+                        error(field, "needs a turn_limit".into());
+                    }
+                },
+            }
+        }
+        
+        // Map size first, everything else on the map depends on it
+        let map = &self.map;
+        let mut map_ok = true;
+        for (name, value) in [("width", map.width), ("height", map.height)] {
+            if !(1..=MAX_SCENARIO_MAP_SIZE).contains(&value) {
+                error(format!("map.{}", name), format!("must be between 1 and {}, got {}", MAX_SCENARIO_MAP_SIZE, value));
+                map_ok = false;
+            }
+        }
+        
+        if let Some(id) = map.coords.as_ref().filter(|_| map.coord_system().is_none()) {
+            let known: Vec<&str> = CoordSystem::all().iter().map(|system| system.id()).collect();
+            error("map.coords".into(), format!("unknown convention \"{}\", expected one of {}", id, known.join(", ")));
+        }
+        
+        if data.terrain.get(&map.terrain).is_none() {
+            error("map.terrain".into(), format!("unknown terrain \"{}\"", map.terrain));
+        }
+        
+        let mut legend: Vec<(&String, &TerrainType)> = map.legend.iter().collect();
+        legend.sort_by_key(|(symbol, _)| *symbol);
+        for (symbol, terrain_type) in legend {
+            let field = format!("map.legend.\"{}\"", symbol);
+            if symbol.chars().count() != 1 || symbol.trim().is_empty() {
+                error(field, "must be a single character other than a space".into());
+            } else if data.terrain.get(terrain_type).is_none() {
+                error(field, format!("unknown terrain \"{}\"", terrain_type));
+            }
+        }
+        
+        let tiles: HashSet<HexCoord> = if map_ok { map.coords().into_iter().collect() } else { HashSet::new() };
+        
+        // The hex at a position, or why there isn't one
+        let convention = map.coord_system().map_or("grid", |system| system.id());
+        let locate = |col: i32, row: i32| match map.grid_to_hex(col, row) {
+            None => Err(format!("[{}, {}] is not a valid {} position", col, row, convention)),
+            Some(coord) if map_ok && !tiles.contains(&coord) => Err(format!("[{}, {}] is off the map", col, row)),
+            Some(coord) => Ok(coord),
+        };
+        
+        // One problem per row is enough to find it
+        let mut bad_rows = HashSet::new();
+        for (row, symbol, col) in map.symbols() {
+            let problem = if !map.legend.contains_key(&symbol.to_string()) {
+                format!("column {}: \"{}\" is not in the legend", col + 1, symbol)
+            } else if let Err(problem) = locate(col, row) {
+                format!("column {}: \"{}\" at {}", col + 1, symbol, problem)
+            } else {
+                continue;
+            };
+            if bad_rows.insert(row) {
+                error(format!("map.rows[{}]", row), problem);
+            }
+        }
+        
+        let mut objectives = HashSet::new();
+        for (index, &[col, row]) in map.objectives.iter().enumerate() {
+            let field = format!("map.objectives[{}]", index);
+            match locate(col, row) {
+                Err(problem) => error(field, problem),
+                Ok(coord) if !objectives.insert(coord) => error(field, format!("[{}, {}] is listed twice", col, row)),
+                Ok(_) => {},
+            }
+        }
+        
+        // Settlements need land under them
+        let terrain = if map_ok { map.terrain() } else { HashMap::new() };
+        let mut sites = HashSet::new();
+        for (index, site) in map.settlements.iter().enumerate() {
+            let field = format!("map.settlement[{}]", index);
+            let [col, row] = site.at;
+            match locate(col, row) {
+                Err(problem) => error(format!("{}.at", field), problem),
+                Ok(coord) if !sites.insert(coord) => error(format!("{}.at", field), format!("[{}, {}] already has a settlement", col, row)),
+                Ok(coord) => {
+                    let definition = terrain.get(&coord).and_then(|terrain_type| data.terrain.get(terrain_type));
+                    if let Some(definition) = definition.filter(|definition| definition.movement_cost == MovementCost::Impassable) {
+                        error(format!("{}.at", field), format!("[{}, {}] is {}, which is impassable", col, row, definition.id));
+                    }
+                },
+            }
+            
+            if let Some(owner) = site.owner.filter(|&owner| owner as usize >= self.teams.len()) {
+                error(format!("{}.owner", field), format!("there is no team {}, teams count from 0", owner));
+            }
+            for (name, value) in [("income", site.income), ("healing", site.healing)] {
+                if let Some(value @ ..=-1) = value {
+                    error(format!("{}.{}", field, name), format!("must not be negative, got {}", value));
+                }
+            }
+        }
+        
+        if !(MIN_TEAMS..=MAX_TEAMS).contains(&self.teams.len()) {
+            error("team".into(), format!("expected {} to {} teams, got {}", MIN_TEAMS, MAX_TEAMS, self.teams.len()));
+        }
+        
+        // Units need a free hex they are able to stand on
+        let mut placed: HashMap<HexCoord, String> = HashMap::new();
+        for (team_index, team) in self.teams.iter().enumerate() {
+            let team_field = format!("team[{}]", team_index);
+            
+            if team.name.is_blank() {
+                error(format!("{}.name", team_field), "must not be empty".into());
+            }
+            
+            if team.units.is_empty() {
+                error(format!("{}.unit", team_field), "no units placed".into());
+            }
+            
+            if team.units.iter().filter(|unit| unit.leader).count() > 1 {
+                error(format!("{}.unit", team_field), "only one unit can be the leader".into());
+            }
+            
+            if let Some(gold @ ..=-1) = team.gold {
+                error(format!("{}.gold", team_field), format!("must not be negative, got {}", gold));
+            }
+            
+            for (index, unit_type) in team.recruit.iter().flatten().enumerate() {
+                if data.units.get(unit_type).is_none() {
+                    error(format!("{}.recruit[{}]", team_field, index), format!("unknown unit type \"{}\"", unit_type));
+                }
+            }
+            
+            for (unit_index, unit) in team.units.iter().enumerate() {
+                let unit_field = format!("{}.unit[{}]", team_field, unit_index);
+                let definition = data.units.get(&unit.unit_type);
+                if definition.is_none() {
+                    error(format!("{}.type", unit_field), format!("unknown unit type \"{}\"", unit.unit_type));
+                }
+                
+                if !map_ok {
+                    continue;
+                }
+                
+                let [col, row] = unit.at;
+                let at_field = format!("{}.at", unit_field);
+                let coord = match locate(col, row) {
+                    Ok(coord) => coord,
+                    Err(problem) => {
+                        error(at_field, problem);
+                        continue;
+                    },
+                };
+                if let Some(other) = placed.get(&coord) {
+                    error(at_field, format!("[{}, {}] is already taken by {}", col, row, other));
+                } else {
+                    let terrain_definition = terrain.get(&coord).and_then(|terrain_type| data.terrain.get(terrain_type));
+                    if let (Some(definition), Some(terrain_definition)) = (definition, terrain_definition) {
+                        if terrain_definition.movement_cost_for(&definition.class).is_none() {
+                            error(at_field, format!("[{}, {}] is {}, which {} units can't enter", col, row, terrain_definition.id, definition.class));
+                        }
+                    }
+                    placed.insert(coord, unit_field);
+                }
+            }
+        }
+        
+        errors
+    }
+    
+    // Rules for a game of this scenario
+    pub fn rules(&self) -> GameRules {
+        GameRules {
+            zone_of_control: self.rules.zone_of_control,
+            supply: self.rules.supply,
+            turn_limit: self.turn_limit,
+            victory: self.victory.clone(),
+            ..GameRules::default()
+        }
+    }
+    
+    // Starting gold and recruitment lists of the teams that set them
+    pub fn apply_economy(&self, treasury: &mut Treasury) {
+        for (index, team) in self.teams.iter().enumerate() {
+            if let Some(gold) = team.gold {
+                treasury.gold.insert(index as u8, gold);
+            }
+            if let Some(recruit) = &team.recruit {
+                treasury.recruits.insert(index as u8, recruit.clone());
+            }
+        }
+    }
+    
+    // Teams in turn order, with names in a language; team ids are their place in the list
+    pub fn build_teams(&self, language: &str) -> Teams {
+        let infos = self.teams.iter().enumerate().map(|(index, team)| {
+            let [r, g, b] = team.color.unwrap_or(TEAM_COLORS[index % TEAM_COLORS.len()]);
+            TeamInfo {
+                id: index as u8,
+                name: team.name.get(language).to_string(),
+                color: Color32::from_rgb(r, g, b),
+                controller: team.controller,
+            }
+        });
+        let mut teams = Teams::new(infos.collect());
+        
+        for (a, first) in self.teams.iter().enumerate() {
+            for (b, second) in self.teams.iter().enumerate().skip(a + 1) {
+                if first.alliance != 0 && first.alliance == second.alliance {
+                    teams.set_allied(a as u8, b as u8);
+                }
+            }
+        }
+        
+        teams
+    }
+    
+    // Every unit to place, with its team id and hex
+    pub fn units(&self) -> impl Iterator<Item = (u8, HexCoord, &ScenarioUnit)> + '_ {
+        self.teams.iter().enumerate().flat_map(move |(index, team)| {
+            team.units.iter().filter_map(move |unit| Some((index as u8, self.map.grid_to_hex(unit.at[0], unit.at[1])?, unit)))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::c::coords::DoubledKind;
+    
+    // Two teams on a small map, with water at [2, 0] and mountains at [3, 0]
+    const SCENARIO: &str = r#"
+name = "Test"
+
+[map]
+shape = "RectangleOddQ"
+width = 4
+height = 3
+terrain = "plain"
+legend = { "." = "plain", "~" = "water", "^" = "mountain" }
+rows = [
+    "..~^",
+    "....",
+]
+objectives = [[1, 2]]
+
+[[map.settlement]]
+kind = "village"
+at = [1, 1]
+
+[[team]]
+name = "Red"
+
+[[team.unit]]
+type = "infantry"
+at = [0, 0]
+
+[[team]]
+name = "Blue"
+
+[[team.unit]]
+type = "cavalry"
+at = [3, 2]
+"#;
+    
+    fn data() -> GameData {
+        GameData::load().expect("bundled definitions load")
+    }
+    
+    // Fields of the errors a scenario text has, in the order they were found
+    fn error_fields(text: &str) -> Vec<String> {
+        match Scenario::parse(text, "test.toml", &data()) {
+            Ok(_) => Vec::new(),
+            Err(errors) => errors.into_iter().map(|error| error.field).collect(),
+        }
+    }
+    
+    #[test]
+    fn sample_scenario_is_valid() {
+        assert_eq!(error_fields(SCENARIO), Vec::<String>::new());
+    }
+    
+    #[test]
+    fn rows_off_the_map() {
+        let text = SCENARIO.replace("\"....\",", "\".....\",\n    \"....\",\n    \"....\",");
+        assert_eq!(error_fields(&text), ["map.rows[1]", "map.rows[3]"]);
+    }
+    
+    #[test]
+    fn unknown_row_symbol() {
+        let text = SCENARIO.replace("\"....\",", "\"..x.\",");
+        assert_eq!(error_fields(&text), ["map.rows[1]"]);
+    }
+    
+    #[test]
+    fn duplicate_placements() {
+        let text = SCENARIO
+            .replace("objectives = [[1, 2]]", "objectives = [[1, 2], [1, 2]]")
+            .replace("type = \"cavalry\"\nat = [3, 2]", "type = \"cavalry\"\nat = [0, 0]")
+            + "\n[[map.settlement]]\nkind = \"city\"\nat = [1, 1]\n";
+        assert_eq!(error_fields(&text), ["map.objectives[1]", "map.settlement[1].at", "team[1].unit[0].at"]);
+    }
+    
+    #[test]
+    fn units_off_the_map() {
+        let text = SCENARIO.replace("at = [0, 0]", "at = [4, 0]");
+        assert_eq!(error_fields(&text), ["team[0].unit[0].at"]);
+    }
+    
+    #[test]
+    fn impassable_unit_hexes() {
+        // Nobody can stand in water, and only mounted units are kept off mountains
+        let text = SCENARIO.replace("at = [0, 0]", "at = [2, 0]").replace("at = [3, 2]", "at = [3, 0]");
+        assert_eq!(error_fields(&text), ["team[0].unit[0].at", "team[1].unit[0].at"]);
+        
+        let text = SCENARIO.replace("at = [0, 0]", "at = [3, 0]");
+        assert_eq!(error_fields(&text), Vec::<String>::new());
+    }
+    
+    #[test]
+    fn impassable_settlement_hex() {
+        let text = SCENARIO.replace("at = [1, 1]", "at = [2, 0]");
+        assert_eq!(error_fields(&text), ["map.settlement[0].at"]);
+    }
+    
+    #[test]
+    fn team_count() {
+        let one_team = &SCENARIO[..SCENARIO.find("[[team]]\nname = \"Blue\"").unwrap()];
+        assert_eq!(error_fields(one_team), ["team"]);
+        
+        let extra = "\n[[team]]\nname = \"Extra\"\n\n[[team.unit]]\ntype = \"infantry\"\nat = [0, 2]\n";
+        let too_many = SCENARIO.to_string() + &extra.repeat(MAX_TEAMS - 1);
+        assert_eq!(error_fields(&too_many), ["team", "team[3].unit[0].at", "team[4].unit[0].at", "team[5].unit[0].at", "team[6].unit[0].at", "team[7].unit[0].at", "team[8].unit[0].at"]);
+    }
+    
+    #[test]
+    fn survive_team_index() {
+        let victory = |team: u8| format!("\n[[victory]]\ntype = \"survive_until\"\nteam = {}\nturn = 5\n", team);
+        assert_eq!(error_fields(&(SCENARIO.to_string() + &victory(1))), Vec::<String>::new());
+        assert_eq!(error_fields(&(SCENARIO.to_string() + &victory(2))), ["victory[0].team"]);
+    }
+    
+    #[test]
+    fn settlement_owner_index() {
+        let text = SCENARIO.replace("at = [1, 1]", "at = [1, 1]\nowner = 2");
+        assert_eq!(error_fields(&text), ["map.settlement[0].owner"]);
+    }
+    
+    // A parallelogram with a unit, an objective, a settlement and a painted hex at `hex`,
+    // every position written in a coordinate convention
+    fn scenario_at(system: CoordSystem, hex: HexCoord) -> String {
+        let (col, row) = system.from_hex(hex);
+        let mut rows = vec![String::new(); row as usize];
+        rows.push(format!("{}~", " ".repeat(col as usize)));
+        let rows: Vec<String> = rows.iter().map(|row| format!("\"{}\"", row)).collect();
+        format!(
+            "name = \"Test\"\n[map]\nshape = \"Parallelogram\"\nwidth = 6\nheight = 6\ncoords = \"{}\"\nterrain = \"plain\"\n\
+             legend = {{ \"~\" = \"desert\" }}\nrows = [{}]\nobjectives = [[{col}, {row}]]\n\
+             [[map.settlement]]\nkind = \"village\"\nat = [{col}, {row}]\n\
+             [[team]]\nname = \"Red\"\n[[team.unit]]\ntype = \"infantry\"\nat = [{col}, {row}]\n\
+             [[team]]\nname = \"Blue\"\n[[team.unit]]\ntype = \"infantry\"\nat = [0, 0]\n",
+            system.id(), rows.join(", "), col = col, row = row,
+        )
+    }
+    
+    #[test]
+    fn positions_in_any_convention() {
+        let data = data();
+        for system in CoordSystem::all() {
+            for hex in [HexCoord::new(3, 2), HexCoord::new(5, 5), HexCoord::new(1, 4)] {
+                let scenario = Scenario::parse(&scenario_at(system, hex), "test.toml", &data)
+                    .unwrap_or_else(|errors| panic!("{:?} {:?}: {:?}", system, hex, errors));
+                let map = &scenario.map;
+                
+                assert_eq!(map.coord_system(), Some(system));
+                assert_eq!(map.objective_coords(), [hex], "{:?}", system);
+                assert_eq!(map.settlements()[0].0, hex, "{:?}", system);
+                assert_eq!(scenario.units().next().unwrap().1, hex, "{:?}", system);
+                assert_eq!(map.terrain()[&hex], TerrainType::new("desert"), "{:?}", system);
+                assert_eq!(map.terrain().values().filter(|terrain| terrain.as_str() == "desert").count(), 1);
+            }
+        }
+    }
+    
+    #[test]
+    fn positions_without_a_hex() {
+        // Doubled coordinates only have hexes where the column and row add up to an even number
+        let text = scenario_at(CoordSystem::Doubled(DoubledKind::DoubledWidth), HexCoord::new(3, 2))
+            .replace("at = [0, 0]", "at = [1, 0]");
+        let errors = Scenario::parse(&text, "test.toml", &data()).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "team[1].unit[0].at");
+        assert_eq!(errors[0].message, "[1, 0] is not a valid doubled-width position");
+    }
+    
+    #[test]
+    fn unknown_convention() {
+        let text = scenario_at(CoordSystem::Axial, HexCoord::new(2, 1)).replace("coords = \"axial\"", "coords = \"doubled\"");
+        assert_eq!(error_fields(&text), ["map.coords"]);
+    }
+    
+    #[test]
+    fn bundled_scenarios_load() {
+        let count = fs::read_dir(SCENARIOS_DIR).unwrap()
+            .filter(|entry| entry.as_ref().unwrap().path().extension().is_some_and(|extension| extension == "toml"))
+            .count();
+        let (scenarios, errors) = Scenario::load_all(SCENARIOS_DIR, &data());
+        
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(scenarios.len(), count);
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+// Kinds of settlement, each with its own icon and yields
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SettlementKind {
+    Village, // Small income, some healing
+    City,    // Large income
+    Fort,    // No income, best healing
+}
+
+impl SettlementKind {
+    pub fn all() -> [SettlementKind; 3] {
+        [SettlementKind::Village, SettlementKind::City, SettlementKind::Fort]
+    }
+    
+    // Localization key for the display name
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            SettlementKind::Village => "settlement-village",
+            SettlementKind::City => "settlement-city",
+            SettlementKind::Fort => "settlement-fort",
+        }
+    }
+    
+    // Gold per turn for the owner, unless a scenario says otherwise
+    pub fn default_income(&self) -> i32 {
+        match self {
+            SettlementKind::Village => 1,
+            SettlementKind::City => 3,
+            SettlementKind::Fort => 0,
+        }
+    }
+    
+    // Health a friendly unit regains at the start of its turn here, unless a scenario says otherwise
+    pub fn default_healing(&self) -> i32 {
+        match self {
+            SettlementKind::Village => 2,
+            SettlementKind::City => 2,
+            SettlementKind::Fort => 4,
+        }
+    }
+}
+
+// Settlement component (on tiles). An enemy of the owner takes it by ending its turn there.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Settlement {
+    pub kind: SettlementKind,
+    pub owner: Option<u8>, // Team id, None while neutral
+    pub income: i32,
+    pub healing: i32,
+}
+
+impl Settlement {
+    // A settlement with the usual yields for its kind
+    pub fn new(kind: SettlementKind, owner: Option<u8>) -> Self {
+        Self { kind, owner, income: kind.default_income(), healing: kind.default_healing() }
+    }
+}
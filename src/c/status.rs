@@ -0,0 +1,236 @@
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use crate::c::definitions::DefinitionError;
+
+// Where status effect definitions are loaded from, relative to the working directory
+pub const STATUSES_PATH: &str = "data/statuses.toml";
+
+// What happens when a unit that already has a status gets it again
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Stacking {
+    Refresh,   // Keeps the longer of the two durations
+    Extend,    // Adds the new duration to what is left
+    Intensify, // Adds a stack, up to max_stacks, and keeps the longer duration
+}
+
+// One status effect as written in the definitions file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StatusDefinition {
+    pub id: String,
+    pub name: String,        // Localization key for the display name
+    pub description: String, // Localization key for the tooltip
+    pub symbol: String,      // Drawn on the icon next to the unit
+    pub color: [u8; 3],      // Icon background
+    // Changes to the unit's stats for each stack
+    #[serde(default)]
+    pub attack: i32,
+    #[serde(default)]
+    pub defense: i32,
+    #[serde(default)]
+    pub movement: i32,
+    #[serde(default)]
+    pub range: i32,
+    #[serde(default)]
+    pub damage: i32, // Health lost for each stack at the end of the unit's turn, never below 1
+    #[serde(default)]
+    pub ends_on_move: bool,
+    #[serde(default = "default_stacking")]
+    pub stacking: Stacking,
+    #[serde(default = "default_max_stacks")]
+    pub max_stacks: i32,
+}
+
+fn default_stacking() -> Stacking {
+    Stacking::Refresh
+}
+
+fn default_max_stacks() -> i32 {
+    1
+}
+
+impl StatusDefinition {
+    pub fn color(&self) -> Color32 {
+        let [r, g, b] = self.color;
+        Color32::from_rgb(r, g, b)
+    }
+}
+
+// Layout of the status effect definitions file
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct StatusFile {
+    #[serde(default)]
+    status: Vec<StatusDefinition>,
+}
+
+// All known status effects, in file order (singleton, on the map settings entity)
+#[derive(Debug, Clone, Default)]
+pub struct StatusCatalog {
+    pub statuses: Vec<StatusDefinition>,
+}
+
+impl StatusCatalog {
+    // Read and check a status effect definitions file
+    pub fn load(path: &str) -> Result<Self, Vec<DefinitionError>> {
+        let text = fs::read_to_string(path).map_err(|e| vec![DefinitionError::new(path, "", e.to_string())])?;
+        Self::parse(&text, path)
+    }
+    
+    // Parse and check status effect definitions; `file` is only used in error messages
+    pub fn parse(text: &str, file: &str) -> Result<Self, Vec<DefinitionError>> {
+        let parsed: StatusFile = toml::from_str(text).map_err(|e| vec![DefinitionError::from_toml(file, text, &e)])?;
+        
+        let mut errors = Vec::new();
+        let mut seen = HashSet::new();
+        
+        for (index, status) in parsed.status.iter().enumerate() {
+            let field = |name: &str| format!("status[{}].{}", index, name);
+            
+            if status.id.trim().is_empty() {
+                errors.push(DefinitionError::new(file, field("id"), "must not be empty"));
+            } else if !seen.insert(status.id.clone()) {
+                errors.push(DefinitionError::new(file, field("id"), format!("duplicate id \"{}\"", status.id)));
+            }
+            
+            for (name, value) in [("name", &status.name), ("description", &status.description), ("symbol", &status.symbol)] {
+                if value.trim().is_empty() {
+                    errors.push(DefinitionError::new(file, field(name), "must not be empty"));
+                }
+            }
+            
+            for (name, value, min) in [("damage", status.damage, 0), ("max_stacks", status.max_stacks, 1)] {
+                if value < min {
+                    errors.push(DefinitionError::new(file, field(name), format!("must be at least {}, got {}", min, value)));
+                }
+            }
+        }
+        
+        if errors.is_empty() {
+            Ok(Self { statuses: parsed.status })
+        } else {
+            Err(errors)
+        }
+    }
+    
+    // Definition for a status effect id
+    pub fn get(&self, id: &str) -> Option<&StatusDefinition> {
+        self.statuses.iter().find(|status| status.id == id)
+    }
+}
+
+// One status effect on a unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub id: String,
+    pub turns_left: i32, // Own turns it still lasts, counting the current one
+    pub stacks: i32,
+}
+
+// Status effects component (on tiles, moves with the unit), in the order they were gained
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StatusEffects {
+    pub effects: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    pub fn get(&self, id: &str) -> Option<&StatusEffect> {
+        self.effects.iter().find(|effect| effect.id == id)
+    }
+    
+    // Gain a status for a number of own turns, following its stacking rule if it is already there
+    pub fn apply(&mut self, definition: &StatusDefinition, turns: i32) {
+        let effect = match self.effects.iter_mut().find(|effect| effect.id == definition.id) {
+            Some(effect) => effect,
+            None => {
+                self.effects.push(StatusEffect { id: definition.id.clone(), turns_left: turns, stacks: 1 });
+                return;
+            },
+        };
+        
+        match definition.stacking {
+            Stacking::Refresh => effect.turns_left = effect.turns_left.max(turns),
+            Stacking::Extend => effect.turns_left += turns,
+            Stacking::Intensify => {
+                effect.stacks = (effect.stacks + 1).min(definition.max_stacks);
+                effect.turns_left = effect.turns_left.max(turns);
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn definition(stacking: Stacking, max_stacks: i32) -> StatusDefinition {
+        StatusDefinition {
+            id: "test".to_string(),
+            name: "status-test".to_string(),
+            description: "status-test-description".to_string(),
+            symbol: "T".to_string(),
+            color: [0, 0, 0],
+            attack: 0,
+            defense: 0,
+            movement: 0,
+            range: 0,
+            damage: 0,
+            ends_on_move: false,
+            stacking,
+            max_stacks,
+        }
+    }
+    
+    // Turns left and stacks after gaining a status for each number of turns in turn
+    fn gain(definition: &StatusDefinition, turns: &[i32]) -> (i32, i32) {
+        let mut statuses = StatusEffects::default();
+        for &turns in turns {
+            statuses.apply(definition, turns);
+        }
+        assert_eq!(statuses.effects.len(), 1);
+        let effect = statuses.get("test").unwrap();
+        (effect.turns_left, effect.stacks)
+    }
+    
+    #[test]
+    fn refresh_keeps_the_longer_duration() {
+        let refresh = definition(Stacking::Refresh, 3);
+        assert_eq!(gain(&refresh, &[2]), (2, 1));
+        assert_eq!(gain(&refresh, &[2, 3]), (3, 1));
+        assert_eq!(gain(&refresh, &[3, 1]), (3, 1));
+    }
+    
+    #[test]
+    fn extend_adds_the_durations() {
+        let extend = definition(Stacking::Extend, 3);
+        assert_eq!(gain(&extend, &[2, 3]), (5, 1));
+        assert_eq!(gain(&extend, &[1, 1, 1]), (3, 1));
+    }
+    
+    #[test]
+    fn intensify_adds_stacks_up_to_the_limit() {
+        let intensify = definition(Stacking::Intensify, 3);
+        assert_eq!(gain(&intensify, &[2, 1]), (2, 2));
+        assert_eq!(gain(&intensify, &[1, 3]), (3, 2));
+        assert_eq!(gain(&intensify, &[1, 1, 1, 1, 1]), (1, 3));
+        
+        let single = definition(Stacking::Intensify, 1);
+        assert_eq!(gain(&single, &[1, 1]), (1, 1));
+    }
+    
+    #[test]
+    fn statuses_keep_the_order_they_were_gained_in() {
+        let mut later = definition(Stacking::Refresh, 1);
+        later.id = "later".to_string();
+        let mut statuses = StatusEffects::default();
+        statuses.apply(&definition(Stacking::Refresh, 1), 1);
+        statuses.apply(&later, 1);
+        statuses.apply(&definition(Stacking::Refresh, 1), 2);
+        
+        let ids: Vec<&str> = statuses.effects.iter().map(|effect| effect.id.as_str()).collect();
+        assert_eq!(ids, ["test", "later"]);
+    }
+}
@@ -0,0 +1,160 @@
+use eframe::egui::Color32;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+// Limits on the number of teams in a game
+pub const MIN_TEAMS: usize = 2;
+pub const MAX_TEAMS: usize = 8;
+
+// Default team colours, in team order
+pub const TEAM_COLORS: [[u8; 3]; MAX_TEAMS] = [
+    [0, 0, 255],     // Blue
+    [255, 0, 0],     // Red
+    [0, 160, 0],     // Green
+    [230, 190, 0],   // Yellow
+    [150, 60, 200],  // Purple
+    [255, 130, 0],   // Orange
+    [0, 180, 200],   // Cyan
+    [120, 80, 40],   // Brown
+];
+
+// Who gives a team its orders
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Controller {
+    Human,  // Someone at this computer
+    Ai,     // The computer
+    Remote, // A player elsewhere; played at this computer until there is networking
+}
+
+impl Controller {
+    pub fn all() -> [Controller; 3] {
+        [Controller::Human, Controller::Ai, Controller::Remote]
+    }
+    
+    // Localization key for the display name
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            Controller::Human => "controller-human",
+            Controller::Ai => "controller-ai",
+            Controller::Remote => "controller-remote",
+        }
+    }
+}
+
+// One side in the game
+#[derive(Debug, Clone)]
+pub struct TeamInfo {
+    pub id: u8, // Matches Team::team_id on its units
+    pub name: String,
+    pub color: Color32,
+    pub controller: Controller,
+}
+
+// All teams in turn order, and who is allied with whom (singleton, on the game state entity)
+#[derive(Debug, Clone, Default)]
+pub struct Teams {
+    pub teams: Vec<TeamInfo>,
+    pub alliances: HashSet<(u8, u8)>, // Allied pairs, lower id first
+}
+
+impl Teams {
+    pub fn new(teams: Vec<TeamInfo>) -> Self {
+        Self { teams, alliances: HashSet::new() }
+    }
+    
+    pub fn get(&self, team_id: u8) -> Option<&TeamInfo> {
+        self.teams.iter().find(|team| team.id == team_id)
+    }
+    
+    // Colour to draw a team's units in
+    pub fn color(&self, team_id: u8) -> Color32 {
+        self.get(team_id).map_or(Color32::GRAY, |team| team.color)
+    }
+    
+    // Display name of a team
+    pub fn name(&self, team_id: u8) -> String {
+        self.get(team_id).map_or_else(|| team_id.to_string(), |team| team.name.clone())
+    }
+    
+    pub fn set_allied(&mut self, a: u8, b: u8) {
+        if a != b {
+            self.alliances.insert((a.min(b), a.max(b)));
+        }
+    }
+    
+    // A team is always allied with itself
+    pub fn are_allied(&self, a: u8, b: u8) -> bool {
+        a == b || self.alliances.contains(&(a.min(b), a.max(b)))
+    }
+    
+    pub fn are_enemies(&self, a: u8, b: u8) -> bool {
+        !self.are_allied(a, b)
+    }
+    
+    // The team after `current` in turn order among `alive`, and whether a new round starts with it
+    pub fn next_team(&self, current: u8, alive: &HashSet<u8>) -> Option<(u8, bool)> {
+        let start = self.teams.iter().position(|team| team.id == current).unwrap_or(0);
+        let count = self.teams.len();
+        
+        (1..=count)
+            .map(|step| (start + step) % count)
+            .find(|&index| alive.contains(&self.teams[index].id))
+            .map(|index| (self.teams[index].id, index <= start))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn teams(ids: &[u8]) -> Teams {
+        Teams::new(ids.iter().map(|&id| TeamInfo {
+            id,
+            name: id.to_string(),
+            color: Color32::GRAY,
+            controller: Controller::Human,
+        }).collect())
+    }
+    
+    #[test]
+    fn next_team_goes_in_turn_order() {
+        let teams = teams(&[0, 1, 2]);
+        let alive = HashSet::from([0, 1, 2]);
+        
+        assert_eq!(teams.next_team(0, &alive), Some((1, false)));
+        assert_eq!(teams.next_team(1, &alive), Some((2, false)));
+    }
+    
+    #[test]
+    fn next_team_wraps_to_a_new_round() {
+        // Turn order comes from the list, not from the ids
+        let teams = teams(&[3, 0, 5]);
+        let alive = HashSet::from([0, 3, 5]);
+        
+        assert_eq!(teams.next_team(0, &alive), Some((5, false)));
+        assert_eq!(teams.next_team(5, &alive), Some((3, true)));
+    }
+    
+    #[test]
+    fn next_team_skips_dead_teams() {
+        let teams = teams(&[0, 1, 2, 3]);
+        
+        assert_eq!(teams.next_team(0, &HashSet::from([0, 2, 3])), Some((2, false)));
+        assert_eq!(teams.next_team(1, &HashSet::from([0, 3])), Some((3, false)));
+        // Skipping past the end of the list still starts a new round
+        assert_eq!(teams.next_team(2, &HashSet::from([1, 2])), Some((1, true)));
+        assert_eq!(teams.next_team(3, &HashSet::from([2])), Some((2, true)));
+    }
+    
+    #[test]
+    fn next_team_with_one_team_left() {
+        let teams = teams(&[0, 1]);
+        
+        // The last team standing follows itself, a round later
+        assert_eq!(teams.next_team(1, &HashSet::from([1])), Some((1, true)));
+        // The current team may have just been wiped out
+        assert_eq!(teams.next_team(0, &HashSet::from([1])), Some((1, false)));
+        assert_eq!(teams.next_team(0, &HashSet::new()), None);
+    }
+}
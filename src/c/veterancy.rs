@@ -0,0 +1,82 @@
+use serde::{Deserialize, Serialize};
+use crate::c::hex::{UnitStats, UnitState};
+
+// Experience needed for each rank, counted from zero
+pub const RANK_XP: [i32; 3] = [8, 20, 36];
+
+// Experience for finishing off an enemy, on top of the damage dealt
+pub const KILL_XP: i32 = 4;
+
+// What every new rank gives a unit before its promotion
+pub const RANK_ATTACK: i32 = 1;
+pub const RANK_HEALTH: i32 = 2;
+
+// Improvement a unit picks when it reaches a new rank; unit definitions list which ones their type can have
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Promotion {
+    Attack,    // +1 attack
+    Defense,   // +1 defense
+    Health,    // +3 max health
+    Movement,  // +1 movement, once
+    Range,     // +1 range, once
+    IgnoreZoc, // Moves through enemy zones of control, once
+}
+
+impl Promotion {
+    // Localization key for the display name
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            Promotion::Attack => "promotion-attack",
+            Promotion::Defense => "promotion-defense",
+            Promotion::Health => "promotion-health",
+            Promotion::Movement => "promotion-movement",
+            Promotion::Range => "promotion-range",
+            Promotion::IgnoreZoc => "promotion-ignore-zoc",
+        }
+    }
+    
+    // Whether a unit can take this more than once
+    pub fn repeatable(&self) -> bool {
+        matches!(self, Promotion::Attack | Promotion::Defense | Promotion::Health)
+    }
+    
+    // Change a unit's stats for this promotion
+    pub fn apply(&self, stats: &mut UnitStats, state: &mut UnitState) {
+        match self {
+            Promotion::Attack => stats.attack += 1,
+            Promotion::Defense => stats.defense += 1,
+            Promotion::Health => {
+                stats.max_health += 3;
+                state.health += 3;
+            },
+            Promotion::Movement => stats.movement += 1,
+            Promotion::Range => stats.range += 1,
+            Promotion::IgnoreZoc => {},
+        }
+    }
+}
+
+// Veterancy component (on tiles, moves with the unit). Serializable so it can be kept with the unit.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct Veterancy {
+    pub xp: i32,
+    pub rank: u8,
+    pub promotions: Vec<Promotion>, // Chosen so far, one per rank at most
+}
+
+impl Veterancy {
+    // Experience needed for the next rank, None at the top rank
+    pub fn next_rank_xp(&self) -> Option<i32> {
+        RANK_XP.get(self.rank as usize).copied()
+    }
+    
+    // Ranks reached without a promotion picked yet
+    pub fn pending(&self) -> usize {
+        (self.rank as usize).saturating_sub(self.promotions.len())
+    }
+    
+    pub fn has(&self, promotion: Promotion) -> bool {
+        self.promotions.contains(&promotion)
+    }
+}
@@ -32,6 +32,7 @@ impl<T: 'static> ComponentVec for Vec<Option<T>> {
 }
 
 // The ECS World
+#[derive(Default)]
 pub struct World {
     entities: Vec<Entity>,
     next_entity: Entity,
@@ -1,177 +1,429 @@
-use std::collections::HashMap;
+use eframe::egui::{Color32, Vec2};
+use std::collections::{HashMap, HashSet};
 use crate::e::entity::{Entity, World};
+use crate::e::mapgen::{MapGenConfig, MapGenerator};
 use crate::c::*;
-use eframe::egui::Pos2;
 
 // Factory for creating and managing hex map entities
 pub struct HexMapFactory;
 
 impl HexMapFactory {
-    // Create a new hex map with specified dimensions
-    pub fn create_map(world: &mut World, width: i32, height: i32, hex_size: f32, origin: Pos2) {
+    // Create a new hex map with specified shape and dimensions, with units for every team
+    pub fn create_map(world: &mut World, shape: MapShape, (width, height): (i32, i32), layout: HexLayout, gen_config: &MapGenConfig, data: &GameData, teams: Teams) {
+        let team_ids: Vec<u8> = teams.teams.iter().map(|team| team.id).collect();
+        let map_settings_entity = Self::create_singletons(world, shape, (width, height), layout, data, teams, GameRules::default());
+        
+        // All tiles that make up a map of this shape
+        let coords = shape.coords(width, height);
+        
+        // Starting positions for each team, spread around the edge of the map, one of every unit type each
+        let lineup = &data.units.units;
+        let mut taken = HashSet::new();
+        let spawns: Vec<Vec<(HexCoord, &UnitDefinition)>> = (0..team_ids.len())
+            .map(|index| {
+                // The first team starts top-left, the rest follow evenly around the map
+                let angle = 1.25 * std::f32::consts::PI + std::f32::consts::TAU * index as f32 / team_ids.len() as f32;
+                let positions = Self::spawn_positions(&coords, &layout, lineup.len(), Vec2::angled(angle), &taken);
+                taken.extend(positions.iter().copied());
+                positions.into_iter().zip(lineup).collect()
+            })
+            .collect();
+        
+        let spawn_areas: Vec<Vec<HexCoord>> = spawns.iter()
+            .map(|spawn| spawn.iter().map(|(coord, _)| *coord).collect())
+            .collect();
+        let generator = MapGenerator::new(gen_config.clone());
+        let terrain_map = generator.generate(&coords, &spawn_areas, data);
+        
+        Self::create_tiles(world, map_settings_entity, coords, &terrain_map, &data.terrain.generator.open);
+        
+        // Each team starts with a city, the rest are up for grabs
+        for (coord, kind, owner) in generator.place_settlements(&terrain_map, &spawn_areas, data) {
+            if let Some(entity) = Self::get_tile_entity(world, &coord) {
+                world.add_component(entity, Settlement::new(kind, owner.map(|index| team_ids[index])));
+            }
+        }
+        
+        // Add each team's units
+        for (team_id, spawn) in team_ids.into_iter().zip(spawns) {
+            for (coord, definition) in spawn {
+                Self::add_unit(world, coord, definition, team_id);
+            }
+        }
+    }
+    
+    // Create the map, teams, rules and units a scenario describes; the scenario must have passed validation
+    pub fn create_scenario(world: &mut World, scenario: &Scenario, layout: HexLayout, data: &GameData, teams: Teams) {
+        let map = &scenario.map;
+        let map_settings_entity = Self::create_singletons(world, map.shape, (map.width, map.height), layout, data, teams, scenario.rules());
+        if let Some(treasury) = Self::get_game_state_entity(world).and_then(|entity| world.get_component_mut::<Treasury>(entity)) {
+            scenario.apply_economy(treasury);
+        }
+        
+        Self::create_tiles(world, map_settings_entity, map.coords(), &map.terrain(), &map.terrain);
+        
+        for (coord, settlement) in map.settlements() {
+            if let Some(entity) = Self::get_tile_entity(world, &coord) {
+                world.add_component(entity, settlement);
+            }
+        }
+        
+        for coord in map.objective_coords() {
+            if let Some(entity) = Self::get_tile_entity(world, &coord) {
+                world.add_component(entity, Objective);
+            }
+        }
+        
+        for (team_id, coord, unit) in scenario.units() {
+            if let Some(definition) = data.units.get(&unit.unit_type) {
+                Self::add_unit(world, coord, definition, team_id);
+            }
+            if let Some(entity) = Self::get_tile_entity(world, &coord).filter(|_| unit.leader) {
+                world.add_component(entity, Leader);
+            }
+        }
+    }
+    
+    // Recreate a saved game; the save must have passed SaveGame::parse
+    pub fn create_saved_game(world: &mut World, save: &SaveGame, layout: HexLayout, data: &GameData) {
+        let mut teams = Teams::new(save.teams.iter()
+            .map(|team| TeamInfo {
+                id: team.id,
+                name: team.name.clone(),
+                color: Color32::from_rgb(team.color[0], team.color[1], team.color[2]),
+                controller: team.controller,
+            })
+            .collect());
+        for &[a, b] in &save.alliances {
+            teams.set_allied(a, b);
+        }
+        
+        let map = &save.map;
+        let map_settings_entity = Self::create_singletons(world, map.shape, (map.width, map.height), layout, data, teams, save.rules.clone());
+        if let Some(game_state_entity) = Self::get_game_state_entity(world) {
+            if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+                game_state.current_turn = save.current_turn;
+                game_state.turn_number = save.turn_number;
+            }
+            if let Some(treasury) = world.get_component_mut::<Treasury>(game_state_entity) {
+                for team in &save.teams {
+                    treasury.gold.insert(team.id, team.gold);
+                    if let Some(recruits) = &team.recruits {
+                        treasury.recruits.insert(team.id, recruits.clone());
+                    }
+                }
+            }
+            if let Some(victory) = world.get_component_mut::<VictoryState>(game_state_entity) {
+                for team in &save.teams {
+                    victory.scores.insert(team.id, team.score);
+                    victory.held_turns.insert(team.id, team.held_turns);
+                }
+            }
+        }
+        
+        let coords = map.shape.coords(map.width, map.height);
+        let terrain_map = coords.iter().copied().zip(map.terrain.iter().cloned()).collect();
+        Self::create_tiles(world, map_settings_entity, coords, &terrain_map, &data.terrain.generator.open);
+        
+        for &[q, r] in &map.objectives {
+            if let Some(entity) = Self::get_tile_entity(world, &HexCoord::new(q, r)) {
+                world.add_component(entity, Objective);
+            }
+        }
+        
+        for site in &save.settlements {
+            if let Some(entity) = Self::get_tile_entity(world, &HexCoord::new(site.at[0], site.at[1])) {
+                world.add_component(entity, site.settlement.clone());
+            }
+        }
+        
+        for unit in &save.units {
+            if let Some(entity) = Self::get_tile_entity(world, &HexCoord::new(unit.at[0], unit.at[1])) {
+                world.add_component(entity, unit.stats.clone());
+                world.add_component(entity, unit.state.clone());
+                world.add_component(entity, Team { team_id: unit.team });
+                world.add_component(entity, unit.veterancy.clone());
+                world.add_component(entity, unit.abilities.clone());
+                world.add_component(entity, unit.statuses.clone());
+                if unit.leader {
+                    world.add_component(entity, Leader);
+                }
+            }
+        }
+    }
+    
+    // Everything about the game in progress that a save needs, None without a game
+    pub fn save_game(world: &World, scenario: Option<String>) -> Option<SaveGame> {
+        let game_state_entity = Self::get_game_state_entity(world)?;
+        let game_state = world.get_component::<GameState>(game_state_entity)?;
+        let teams = world.get_component::<Teams>(game_state_entity)?;
+        let treasury = world.get_component::<Treasury>(game_state_entity).cloned().unwrap_or_default();
+        let victory = world.get_component::<VictoryState>(game_state_entity).cloned().unwrap_or_default();
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        let settings = world.get_component::<MapSettings>(map_settings_entity)?;
+        
+        let coords = settings.map_shape.coords(settings.map_width, settings.map_height);
+        let terrain = coords.iter()
+            .map(|coord| {
+                Self::get_tile_entity(world, coord)
+                    .and_then(|entity| world.get_component::<Terrain>(entity))
+                    .map(|terrain| terrain.terrain_type.clone())
+            })
+            .collect::<Option<Vec<_>>>()?;
+        
+        // Tiles in map order so the same game always saves the same way
+        let tiles: Vec<(HexCoord, Entity)> = coords.iter()
+            .filter_map(|coord| Self::get_tile_entity(world, coord).map(|entity| (*coord, entity)))
+            .collect();
+        
+        let mut alliances: Vec<[u8; 2]> = teams.alliances.iter().map(|&(a, b)| [a, b]).collect();
+        alliances.sort();
+        
+        Some(SaveGame {
+            scenario,
+            current_turn: game_state.current_turn,
+            turn_number: game_state.turn_number,
+            rules: Self::get_game_rules(world),
+            map: SavedMap {
+                shape: settings.map_shape,
+                width: settings.map_width,
+                height: settings.map_height,
+                terrain,
+                objectives: tiles.iter()
+                    .filter(|(_, entity)| world.get_component::<Objective>(*entity).is_some())
+                    .map(|(coord, _)| [coord.q, coord.r])
+                    .collect(),
+            },
+            alliances,
+            teams: teams.teams.iter()
+                .map(|team| SavedTeam {
+                    id: team.id,
+                    name: team.name.clone(),
+                    color: [team.color.r(), team.color.g(), team.color.b()],
+                    controller: team.controller,
+                    gold: treasury.gold(team.id),
+                    recruits: treasury.recruits.get(&team.id).cloned(),
+                    score: victory.scores.get(&team.id).copied().unwrap_or(0),
+                    held_turns: victory.held_turns.get(&team.id).copied().unwrap_or(0),
+                })
+                .collect(),
+            settlements: tiles.iter()
+                .filter_map(|(coord, entity)| {
+                    let settlement = world.get_component::<Settlement>(*entity)?;
+                    Some(SavedSettlement { at: [coord.q, coord.r], settlement: settlement.clone() })
+                })
+                .collect(),
+            units: tiles.iter()
+                .filter_map(|(coord, entity)| {
+                    Some(SavedUnit {
+                        at: [coord.q, coord.r],
+                        team: world.get_component::<Team>(*entity)?.team_id,
+                        leader: world.get_component::<Leader>(*entity).is_some(),
+                        stats: world.get_component::<UnitStats>(*entity)?.clone(),
+                        state: world.get_component::<UnitState>(*entity)?.clone(),
+                        veterancy: world.get_component::<Veterancy>(*entity).cloned().unwrap_or_default(),
+                        abilities: world.get_component::<Abilities>(*entity).cloned().unwrap_or_default(),
+                        statuses: world.get_component::<StatusEffects>(*entity).cloned().unwrap_or_default(),
+                    })
+                })
+                .collect(),
+        })
+    }
+    
+    // Create the game state and map settings entities, returning the map settings entity
+    fn create_singletons(world: &mut World, shape: MapShape, (width, height): (i32, i32), layout: HexLayout, data: &GameData, teams: Teams, rules: GameRules) -> Entity {
+        let first_team = teams.teams.first().map_or(0, |team| team.id);
+        
         // Create the game state entity
         let game_state_entity = world.create_entity();
         world.add_component(game_state_entity, GameState {
             selected_entity: None,
             hover_entity: None,
-            current_turn: 0, // Player starts
+            cursor: None,
+            armed_ability: None,
+            current_turn: first_team,
             turn_number: 1,
+            end_turn_requested: false,
             game_over: false,
-            player_won: false,
+            winners: Vec::new(),
         });
+        world.add_component(game_state_entity, Animations::default());
+        world.add_component(game_state_entity, VictoryState::default());
+        world.add_component(game_state_entity, rules);
+        world.add_component(game_state_entity, Treasury::new(&teams));
+        world.add_component(game_state_entity, teams);
         
         // Create map settings entity
         let map_settings_entity = world.create_entity();
         world.add_component(map_settings_entity, MapSettings {
             map_width: width,
             map_height: height,
-            hex_size,
-            origin,
+            map_shape: shape,
+            coord_labels: CoordSystem::Axial,
+            show_coord_labels: false,
+            layout,
         });
+        world.add_component(map_settings_entity, Camera::new());
+        world.add_component(map_settings_entity, TerrainMesh { dirty: true, ..Default::default() });
+        world.add_component(map_settings_entity, Sprites::default());
+        world.add_component(map_settings_entity, data.units.clone());
+        world.add_component(map_settings_entity, data.terrain.clone());
+        world.add_component(map_settings_entity, data.abilities.clone());
+        world.add_component(map_settings_entity, data.statuses.clone());
         
+        map_settings_entity
+    }
+    
+    // Create a tile entity for every hex, with `fallback` terrain where the terrain map has none
+    fn create_tiles(world: &mut World, map_settings_entity: Entity, coords: Vec<HexCoord>, terrain_map: &HashMap<HexCoord, TerrainType>, fallback: &TerrainType) {
         // Create a HashMap to store hex coordinates to entity mapping
         let mut hex_entity_map = HashMap::new();
         
         // Generate hex tiles
-        for q in 0..width {
-            for r in 0..height {
-                let coord = HexCoord { q, r };
-                let entity = world.create_entity();
-                
-                // Position component
-                world.add_component(entity, Position { coord });
-                
-                // Random terrain (simplified for this example)
-                let terrain_type = match (q + r) % 4 {
-                    0 => TerrainType::Plain,
-                    1 => TerrainType::Forest,
-                    2 => TerrainType::Mountain,
-                    _ => TerrainType::Water,
-                };
-                
-                world.add_component(entity, Terrain { terrain_type });
-                
-                // Add to mapping
-                hex_entity_map.insert(coord, entity);
-            }
+        for coord in coords {
+            let entity = world.create_entity();
+            
+            // Position component
+            world.add_component(entity, Position { coord });
+            
+            let terrain_type = terrain_map.get(&coord).cloned().unwrap_or_else(|| fallback.clone());
+            world.add_component(entity, Terrain { terrain_type });
+            
+            // Add to mapping
+            hex_entity_map.insert(coord, entity);
         }
         
         // Add the hex entity map to the map settings entity
         world.add_component(map_settings_entity, HexEntityMap { map: hex_entity_map });
+    }
+    
+    // Pick free spawn tiles clustered around the edge of the map furthest in a screen direction
+    fn spawn_positions(coords: &[HexCoord], layout: &HexLayout, count: usize, direction: Vec2, taken: &HashSet<HexCoord>) -> Vec<HexCoord> {
+        let tiles: HashSet<HexCoord> = coords.iter().copied().collect();
+        let free: Vec<HexCoord> = coords.iter().filter(|coord| !taken.contains(coord)).copied().collect();
         
-        // Add player units
-        Self::add_player_unit(world, HexCoord { q: 1, r: 1 }, UnitType::Infantry);
-        Self::add_player_unit(world, HexCoord { q: 2, r: 2 }, UnitType::Archer);
-        Self::add_player_unit(world, HexCoord { q: 3, r: 1 }, UnitType::Cavalry);
-        
-        // Add enemy units
-        Self::add_enemy_unit(world, HexCoord { q: width - 2, r: height - 2 }, UnitType::Infantry);
-        Self::add_enemy_unit(world, HexCoord { q: width - 3, r: height - 3 }, UnitType::Archer);
-        Self::add_enemy_unit(world, HexCoord { q: width - 4, r: height - 2 }, UnitType::Cavalry);
-    }
-    
-    // Add a player unit at the specified coordinate
-    fn add_player_unit(world: &mut World, coord: HexCoord, unit_type: UnitType) {
-        let hex_entities = Self::get_hex_entity_map(world);
-        
-        if let Some(&entity) = hex_entities.get(&coord) {
-            // Add unit components based on type
-            match unit_type {
-                UnitType::Infantry => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 3,
-                        defense: 2,
-                        movement: 2,
-                        range: 1,
-                        max_health: 10,
-                    });
-                },
-                UnitType::Archer => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 4,
-                        defense: 1,
-                        movement: 2,
-                        range: 2,
-                        max_health: 8,
-                    });
-                },
-                UnitType::Cavalry => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 5,
-                        defense: 1,
-                        movement: 4,
-                        range: 1,
-                        max_health: 12,
-                    });
-                },
-            }
+        // Screen-space distance towards the edge, so the corners match what the player sees
+        let reach = |coord: &HexCoord| coord.to_pixel(layout).to_vec2().dot(direction);
+        
+        // Prefer an anchor one step in from the edge so units aren't pinned against it
+        let inner: Vec<HexCoord> = free.iter()
+            .filter(|coord| coord.neighbors().iter().all(|n| tiles.contains(n)))
+            .copied()
+            .collect();
+        let candidates = if inner.is_empty() { &free[..] } else { &inner[..] };
+        
+        let anchor = match candidates.iter().max_by(|a, b| reach(a).total_cmp(&reach(b))) {
+            Some(&anchor) => anchor,
+            None => return Vec::new(),
+        };
+        
+        let mut nearest = free;
+        nearest.sort_by(|a, b| {
+            // Break ties towards the middle of the map
+            a.distance(&anchor).cmp(&b.distance(&anchor))
+                .then(reach(a).total_cmp(&reach(b)))
+        });
+        nearest.truncate(count);
+        nearest
+    }
+    
+    // Add a unit of the given type for a team at the specified coordinate
+    pub fn add_unit(world: &mut World, coord: HexCoord, definition: &UnitDefinition, team_id: u8) {
+        if let Some(entity) = Self::get_tile_entity(world, &coord) {
+            let stats = definition.stats();
             
-            // Add unit state
-            let movement = world.get_component::<UnitStats>(entity).unwrap().movement;
             world.add_component(entity, UnitState {
-                health: world.get_component::<UnitStats>(entity).unwrap().max_health,
-                movement_left: movement,
+                health: stats.max_health,
+                movement_left: stats.movement,
                 has_acted: false,
+                rested: true,
+                ammo: stats.max_ammo,
+                morale: MAX_MORALE,
             });
-            
-            // Add team component
-            world.add_component(entity, Team { team_id: 0 }); // Player team
+            world.add_component(entity, stats);
+            world.add_component(entity, Team { team_id });
+            world.add_component(entity, Veterancy::default());
+            world.add_component(entity, Abilities::default());
+            world.add_component(entity, StatusEffects::default());
         }
     }
     
-    // Add an enemy unit at the specified coordinate
-    fn add_enemy_unit(world: &mut World, coord: HexCoord, unit_type: UnitType) {
-        let hex_entities = Self::get_hex_entity_map(world);
-        
-        if let Some(&entity) = hex_entities.get(&coord) {
-            // Add unit components based on type
-            match unit_type {
-                UnitType::Infantry => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 3,
-                        defense: 2,
-                        movement: 2,
-                        range: 1,
-                        max_health: 10,
-                    });
-                },
-                UnitType::Archer => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 4,
-                        defense: 1,
-                        movement: 2,
-                        range: 2,
-                        max_health: 8,
-                    });
-                },
-                UnitType::Cavalry => {
-                    world.add_component(entity, UnitStats {
-                        unit_type,
-                        attack: 5,
-                        defense: 1,
-                        movement: 4,
-                        range: 1,
-                        max_health: 12,
-                    });
-                },
-            }
-            
-            // Add unit state
-            let movement = world.get_component::<UnitStats>(entity).unwrap().movement;
-            world.add_component(entity, UnitState {
-                health: world.get_component::<UnitStats>(entity).unwrap().max_health,
-                movement_left: movement,
-                has_acted: false,
-            });
-            
-            // Add team component
-            world.add_component(entity, Team { team_id: 1 }); // Enemy team
+    // Move everything that makes up a unit from one tile to another
+    pub fn transfer_unit(world: &mut World, from: Entity, to: Entity) {
+        if let Some(unit_stats) = world.get_component::<UnitStats>(from).cloned() {
+            world.add_component(to, unit_stats);
+        }
+        if let Some(unit_state) = world.get_component::<UnitState>(from).cloned() {
+            world.add_component(to, unit_state);
+        }
+        if let Some(team) = world.get_component::<Team>(from).cloned() {
+            world.add_component(to, team);
+        }
+        if world.get_component::<Leader>(from).is_some() {
+            world.add_component(to, Leader);
+        }
+        if let Some(veterancy) = world.get_component::<Veterancy>(from).cloned() {
+            world.add_component(to, veterancy);
         }
+        if let Some(abilities) = world.get_component::<Abilities>(from).cloned() {
+            world.add_component(to, abilities);
+        }
+        if let Some(statuses) = world.get_component::<StatusEffects>(from).cloned() {
+            world.add_component(to, statuses);
+        }
+        
+        Self::remove_unit(world, from);
+    }
+    
+    // Clear a unit off its tile
+    pub fn remove_unit(world: &mut World, entity: Entity) {
+        world.remove_component::<UnitStats>(entity);
+        world.remove_component::<UnitState>(entity);
+        world.remove_component::<Team>(entity);
+        world.remove_component::<Leader>(entity);
+        world.remove_component::<Veterancy>(entity);
+        world.remove_component::<Abilities>(entity);
+        world.remove_component::<StatusEffects>(entity);
+    }
+    
+    // Unit type definitions for the current map
+    pub fn get_unit_catalog(world: &World) -> Option<&UnitCatalog> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        world.get_component::<UnitCatalog>(map_settings_entity)
+    }
+    
+    // Ability definitions for the current map
+    pub fn get_ability_catalog(world: &World) -> Option<&AbilityCatalog> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        world.get_component::<AbilityCatalog>(map_settings_entity)
+    }
+    
+    // Status effect definitions for the current map
+    pub fn get_status_catalog(world: &World) -> Option<&StatusCatalog> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        world.get_component::<StatusCatalog>(map_settings_entity)
+    }
+    
+    // Terrain type definitions for the current map
+    pub fn get_terrain_catalog(world: &World) -> Option<&TerrainCatalog> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        world.get_component::<TerrainCatalog>(map_settings_entity)
+    }
+    
+    // Terrain definition for the tile at a coordinate
+    pub fn terrain_at(world: &World, coord: HexCoord) -> Option<&TerrainDefinition> {
+        let entity = Self::get_tile_entity(world, &coord)?;
+        let terrain = world.get_component::<Terrain>(entity)?;
+        Self::get_terrain_catalog(world)?.get(&terrain.terrain_type)
+    }
+    
+    // Whether nothing between two hexes blocks sight; the hexes at either end never do
+    pub fn line_of_sight(world: &World, from: HexCoord, to: HexCoord) -> bool {
+        let line = from.line_to(&to);
+        line.iter()
+            .skip(1)
+            .take(line.len().saturating_sub(2))
+            .all(|coord| Self::terrain_at(world, *coord).is_none_or(|terrain| !terrain.blocks_sight))
     }
     
     // Get the hex entity map from the world
@@ -187,24 +439,43 @@ impl HexMapFactory {
         HashMap::new()
     }
     
+    // Get the tile entity at a coordinate without copying the whole map
+    pub fn get_tile_entity(world: &World, coord: &HexCoord) -> Option<Entity> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        let hex_entity_map = world.get_component::<HexEntityMap>(map_settings_entity)?;
+        hex_entity_map.map.get(coord).copied()
+    }
+    
+    // Ask the render system to rebuild the cached terrain mesh
+    pub fn mark_terrain_dirty(world: &mut World) {
+        if let Some(map_settings_entity) = Self::get_map_settings_entity(world) {
+            if let Some(mesh) = world.get_component_mut::<TerrainMesh>(map_settings_entity) {
+                mesh.dirty = true;
+            }
+        }
+    }
+    
+    // Get the map layout as seen through the camera
+    pub fn get_view_layout(world: &World) -> Option<HexLayout> {
+        let map_settings_entity = Self::get_map_settings_entity(world)?;
+        let settings = world.get_component::<MapSettings>(map_settings_entity)?;
+        
+        match world.get_component::<Camera>(map_settings_entity) {
+            Some(camera) => Some(camera.view_layout(&settings.layout)),
+            None => Some(settings.layout),
+        }
+    }
+    
     // Get the game state entity
     pub fn get_game_state_entity(world: &World) -> Option<Entity> {
         // Query for entities with a GameState component
-        for (entity, _) in world.query::<GameState>() {
-            return Some(entity);
-        }
-        
-        None
+        world.query::<GameState>().into_iter().next().map(|(entity, _)| entity)
     }
     
     // Get the map settings entity
     pub fn get_map_settings_entity(world: &World) -> Option<Entity> {
         // Query for entities with a MapSettings component
-        for (entity, _) in world.query::<MapSettings>() {
-            return Some(entity);
-        }
-        
-        None
+        world.query::<MapSettings>().into_iter().next().map(|(entity, _)| entity)
     }
     
     // Reset team units for a new turn
@@ -228,36 +499,239 @@ impl HexMapFactory {
                 if let Some(unit_state) = world.get_component_mut::<UnitState>(entity) {
                     unit_state.movement_left = movement;
                     unit_state.has_acted = false;
+                    unit_state.rested = true;
                 }
             }
         }
     }
     
-    // Check if the game is over (one team has no units left)
-    pub fn check_game_over(world: &World) -> (bool, bool) {
-        let mut player_units = 0;
-        let mut enemy_units = 0;
-        
-        // Count units for each team with health > 0
-        for (entity, team) in world.query::<Team>() {
-            if let Some(unit_state) = world.get_component::<UnitState>(entity) {
-                if unit_state.health > 0 {
-                    if team.team_id == 0 {
-                        player_units += 1;
-                    } else {
-                        enemy_units += 1;
-                    }
-                }
-            }
+    // Living units of a team under orders that can still move and haven't acted this turn, in entity order
+    pub fn idle_units(world: &World, team_id: u8) -> Vec<Entity> {
+        world.query::<UnitState>()
+            .into_iter()
+            .filter(|(entity, state)| {
+                state.health > 0
+                    && state.movement_left > 0
+                    && !state.has_acted
+                    && !state.routing()
+                    && world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id)
+            })
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+    
+    // Rules for the current game
+    pub fn get_game_rules(world: &World) -> GameRules {
+        Self::get_game_state_entity(world)
+            .and_then(|entity| world.get_component::<GameRules>(entity))
+            .cloned()
+            .unwrap_or_default()
+    }
+    
+    // Teams in the current game
+    pub fn get_teams(world: &World) -> Option<&Teams> {
+        let game_state_entity = Self::get_game_state_entity(world)?;
+        world.get_component::<Teams>(game_state_entity)
+    }
+    
+    // Whether two teams are at war, according to the alliance table
+    pub fn are_enemies(world: &World, a: u8, b: u8) -> bool {
+        match Self::get_teams(world) {
+            Some(teams) => teams.are_enemies(a, b),
+            None => a != b,
+        }
+    }
+    
+    // Colour of a team's units
+    pub fn team_color(world: &World, team_id: u8) -> Color32 {
+        Self::get_teams(world).map_or(Color32::GRAY, |teams| teams.color(team_id))
+    }
+    
+    // Hexes next to a living unit of an enemy of the given team
+    pub fn zone_of_control(world: &World, team_id: u8) -> HashSet<HexCoord> {
+        world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| {
+                Self::are_enemies(world, team.team_id, team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+            })
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity))
+            .flat_map(|position| position.coord.neighbors())
+            .filter(|coord| Self::get_tile_entity(world, coord).is_some())
+            .collect()
+    }
+    
+    // Teams that still have a living unit
+    pub fn living_teams(world: &World) -> HashSet<u8> {
+        world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, _)| world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0))
+            .map(|(_, team)| team.team_id)
+            .collect()
+    }
+    
+    // Check if the game is over (everyone left standing is allied), returning the winners in turn order
+    pub fn check_game_over(world: &World) -> Option<Vec<u8>> {
+        let living = Self::living_teams(world);
+        let at_war = living.iter().any(|&a| living.iter().any(|&b| Self::are_enemies(world, a, b)));
+        if at_war {
+            return None;
         }
         
-        // Game is over if one team has no units
-        if player_units == 0 {
-            (true, false) // Enemy won
-        } else if enemy_units == 0 {
-            (true, true) // Player won
-        } else {
-            (false, false) // Game continues
+        let mut winners: Vec<u8> = living.into_iter().collect();
+        match Self::get_teams(world) {
+            Some(teams) => winners.sort_by_key(|&id| teams.teams.iter().position(|team| team.id == id)),
+            None => winners.sort(),
         }
+        Some(winners)
+    }
+} 
+
+// Worlds for system tests, built from inline scenario text
+#[cfg(test)]
+impl HexMapFactory {
+    // A world with the bundled definitions; the scenario must pass validation
+    pub fn test_world(scenario: &str) -> World {
+        Self::test_world_with(scenario, &GameData::load().unwrap())
+    }
+    
+    // A world with custom definitions, e.g. the bundled ones plus a test-only status
+    pub fn test_world_with(scenario: &str, data: &GameData) -> World {
+        let scenario = Scenario::parse(scenario, "test.toml", data).unwrap();
+        let layout = HexLayout::new(HexOrientation::Flat, eframe::egui::vec2(30.0, 30.0), eframe::egui::Pos2::ZERO);
+        let mut world = World::new();
+        Self::create_scenario(&mut world, &scenario, layout, data, scenario.build_teams("en-US"));
+        world
+    }
+    
+    // The tile at a column of a one-row Parallelogram map
+    pub fn test_tile(world: &World, q: i32) -> Entity {
+        Self::get_tile_entity(world, &HexCoord::new(q, 0)).unwrap()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    const SCENARIO: &str = r#"
+name = "Test"
+turn_limit = 9
+[rules]
+supply = true
+[map]
+shape = "Parallelogram"
+width = 6
+height = 1
+terrain = "plain"
+legend = { "f" = "forest" }
+rows = ["  f   "]
+objectives = [[3, 0]]
+[[map.settlement]]
+kind = "fort"
+at = [1, 0]
+owner = 0
+[[team]]
+name = "Blue"
+alliance = 1
+[[team.unit]]
+type = "cavalry"
+at = [0, 0]
+leader = true
+[[team]]
+name = "Red"
+recruit = ["infantry"]
+[[team.unit]]
+type = "archer"
+at = [5, 0]
+[[team]]
+name = "Green"
+alliance = 1
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+"#;
+    
+    // Save a world to text and load it into a fresh one
+    fn reload(world: &World) -> World {
+        let data = GameData::load().unwrap();
+        let text = toml::to_string(&HexMapFactory::save_game(world, Some("test.toml".to_string())).unwrap()).unwrap();
+        let save = SaveGame::parse(&text, &data).unwrap();
+        assert_eq!(save.scenario.as_deref(), Some("test.toml"));
+        
+        let layout = HexLayout::new(HexOrientation::Flat, eframe::egui::vec2(30.0, 30.0), eframe::egui::Pos2::ZERO);
+        let mut loaded = World::new();
+        HexMapFactory::create_saved_game(&mut loaded, &save, layout, &data);
+        loaded
+    }
+    
+    #[test]
+    fn saves_keep_experience_ranks_and_promotions() {
+        let mut world = HexMapFactory::test_world(SCENARIO);
+        let cavalry = HexMapFactory::test_tile(&world, 0);
+        world.add_component(cavalry, Veterancy { xp: 23, rank: 2, promotions: vec![Promotion::Movement, Promotion::IgnoreZoc] });
+        world.get_component_mut::<UnitStats>(cavalry).unwrap().movement += 1;
+        world.get_component_mut::<UnitState>(cavalry).unwrap().health = 3;
+        
+        let loaded = reload(&world);
+        let cavalry = HexMapFactory::test_tile(&loaded, 0);
+        let veterancy = loaded.get_component::<Veterancy>(cavalry).unwrap();
+        assert_eq!((veterancy.xp, veterancy.rank), (23, 2));
+        assert_eq!(veterancy.promotions, [Promotion::Movement, Promotion::IgnoreZoc]);
+        assert_eq!(loaded.get_component::<UnitStats>(cavalry).unwrap().movement, 5);
+        assert_eq!(loaded.get_component::<UnitState>(cavalry).unwrap().health, 3);
+        assert!(loaded.get_component::<Leader>(cavalry).is_some());
+        
+        // Fresh units load as fresh
+        let archer = HexMapFactory::test_tile(&loaded, 5);
+        assert_eq!(loaded.get_component::<Veterancy>(archer).unwrap().xp, 0);
+        assert_eq!(loaded.get_component::<Team>(archer).unwrap().team_id, 1);
+    }
+    
+    #[test]
+    fn saves_keep_the_game_around_the_units() {
+        let mut world = HexMapFactory::test_world(SCENARIO);
+        let game_state_entity = HexMapFactory::get_game_state_entity(&world).unwrap();
+        world.get_component_mut::<GameState>(game_state_entity).unwrap().current_turn = 2;
+        world.get_component_mut::<GameState>(game_state_entity).unwrap().turn_number = 4;
+        world.get_component_mut::<VictoryState>(game_state_entity).unwrap().scores.insert(1, 7);
+        world.get_component_mut::<Treasury>(game_state_entity).unwrap().gold.insert(0, 3);
+        
+        let loaded = reload(&world);
+        let game_state_entity = HexMapFactory::get_game_state_entity(&loaded).unwrap();
+        let game_state = loaded.get_component::<GameState>(game_state_entity).unwrap();
+        assert_eq!((game_state.current_turn, game_state.turn_number), (2, 4));
+        assert_eq!(loaded.get_component::<VictoryState>(game_state_entity).unwrap().scores.get(&1), Some(&7));
+        
+        let treasury = loaded.get_component::<Treasury>(game_state_entity).unwrap();
+        assert_eq!(treasury.gold(0), 3);
+        assert!(!treasury.can_recruit(1, &UnitType::new("archer")));
+        assert!(treasury.can_recruit(0, &UnitType::new("archer")));
+        
+        let rules = HexMapFactory::get_game_rules(&loaded);
+        assert!(rules.supply);
+        assert_eq!(rules.turn_limit, Some(9));
+        assert!(!HexMapFactory::are_enemies(&loaded, 0, 2));
+        assert!(HexMapFactory::are_enemies(&loaded, 0, 1));
+        
+        let terrain = |q| loaded.get_component::<Terrain>(HexMapFactory::test_tile(&loaded, q)).unwrap().terrain_type.clone();
+        assert_eq!(terrain(2), TerrainType::new("forest"));
+        assert_eq!(terrain(3), TerrainType::new("plain"));
+        assert!(loaded.get_component::<Objective>(HexMapFactory::test_tile(&loaded, 3)).is_some());
+        let fort = loaded.get_component::<Settlement>(HexMapFactory::test_tile(&loaded, 1)).unwrap();
+        assert_eq!((fort.kind, fort.owner), (SettlementKind::Fort, Some(0)));
+    }
+    
+    #[test]
+    fn broken_saves_are_refused() {
+        let data = GameData::load().unwrap();
+        let mut save = HexMapFactory::save_game(&HexMapFactory::test_world(SCENARIO), None).unwrap();
+        save.map.terrain.pop();
+        let error = SaveGame::parse(&toml::to_string(&save).unwrap(), &data).unwrap_err();
+        assert!(error.starts_with("map.terrain"), "{}", error);
+        
+        let mut save = HexMapFactory::save_game(&HexMapFactory::test_world(SCENARIO), None).unwrap();
+        save.units[0].stats.unit_type = UnitType::new("dragon");
+        let error = SaveGame::parse(&toml::to_string(&save).unwrap(), &data).unwrap_err();
+        assert!(error.contains("dragon"), "{}", error);
     }
-} 
\ No newline at end of file
+}
@@ -0,0 +1,391 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use crate::c::*;
+
+// Tunable parameters for procedural terrain generation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MapGenConfig {
+    #[serde(skip)] // A fresh seed is picked for each game, and TOML can't hold every u64
+    pub seed: u64,
+    pub water_percent: f32,    // Share of tiles that become lakes (0-100)
+    pub mountain_percent: f32, // Share of tiles that become mountain ranges (0-100)
+    pub forest_percent: f32,   // Share of tiles that become forest (0-100)
+    pub feature_scale: f32,    // Size of terrain features in hexes, bigger means smoother
+    pub settlement_percent: f32, // Share of tiles that get a neutral settlement (0-100)
+}
+
+impl Default for MapGenConfig {
+    fn default() -> Self {
+        Self {
+            seed: 0,
+            water_percent: 10.0,
+            mountain_percent: 12.0,
+            forest_percent: 25.0,
+            feature_scale: 4.0,
+            settlement_percent: 3.0,
+        }
+    }
+}
+
+// Generates terrain from seeded elevation and moisture noise
+pub struct MapGenerator {
+    config: MapGenConfig,
+}
+
+impl MapGenerator {
+    pub fn new(config: MapGenConfig) -> Self {
+        Self { config }
+    }
+    
+    // Generate terrain for every coordinate on the map, using the terrain catalog's generator terrain.
+    // Each entry of `spawn_areas` is one team's spawn hexes; they are kept clear
+    // and every one of them is linked to the others by a path of passable land.
+    pub fn generate(&self, coords: &[HexCoord], spawn_areas: &[Vec<HexCoord>], data: &GameData) -> HashMap<HexCoord, TerrainType> {
+        let kinds = &data.terrain.generator;
+        let mut terrain = HashMap::new();
+        if coords.is_empty() {
+            return terrain;
+        }
+        
+        // Sample the noise fields
+        let elevation_seed = self.config.seed;
+        let moisture_seed = self.config.seed ^ 0x9E37_79B9_7F4A_7C15;
+        let ridge_seed = self.config.seed ^ 0xD1B5_4A32_D192_ED03;
+        
+        let mut elevation = HashMap::new();
+        let mut moisture = HashMap::new();
+        let mut ridges = HashMap::new();
+        
+        for coord in coords {
+            let (x, y) = self.sample_point(coord);
+            elevation.insert(*coord, fractal_noise(elevation_seed, x, y, 3));
+            moisture.insert(*coord, fractal_noise(moisture_seed, x, y, 2));
+            
+            // Ridged noise peaks along thin lines, which gives mountain ranges instead of blobs
+            let ridge = 1.0 - (fractal_noise(ridge_seed, x * 0.7, y * 0.7, 2) * 2.0 - 1.0).abs();
+            ridges.insert(*coord, ridge * 0.6 + elevation[coord] * 0.4);
+        }
+        
+        let total = coords.len();
+        let water_count = Self::percent_of(total, self.config.water_percent);
+        let mountain_count = Self::percent_of(total, self.config.mountain_percent);
+        let forest_count = Self::percent_of(total, self.config.forest_percent);
+        
+        // Lakes fill the lowest ground
+        let mut by_elevation: Vec<HexCoord> = coords.to_vec();
+        by_elevation.sort_by(|a, b| elevation[a].total_cmp(&elevation[b]).then(Self::coord_order(a, b)));
+        for coord in by_elevation.iter().take(water_count) {
+            terrain.insert(*coord, kinds.lake.clone());
+        }
+        
+        // Mountains follow the highest ridges that aren't already water
+        let mut by_ridge: Vec<HexCoord> = coords.iter().filter(|c| !terrain.contains_key(c)).copied().collect();
+        by_ridge.sort_by(|a, b| ridges[b].total_cmp(&ridges[a]).then(Self::coord_order(a, b)));
+        for coord in by_ridge.iter().take(mountain_count) {
+            terrain.insert(*coord, kinds.peaks.clone());
+        }
+        
+        // Forests grow where it's wettest
+        let mut by_moisture: Vec<HexCoord> = coords.iter().filter(|c| !terrain.contains_key(c)).copied().collect();
+        by_moisture.sort_by(|a, b| moisture[b].total_cmp(&moisture[a]).then(Self::coord_order(a, b)));
+        for coord in by_moisture.iter().take(forest_count) {
+            terrain.insert(*coord, kinds.woods.clone());
+        }
+        
+        for coord in coords {
+            terrain.entry(*coord).or_insert_with(|| kinds.open.clone());
+        }
+        
+        // Keep spawn areas clear
+        for area in spawn_areas {
+            for coord in area {
+                for hex in std::iter::once(*coord).chain(coord.neighbors()) {
+                    if let Some(t) = terrain.get_mut(&hex) {
+                        if !Self::is_passable(data, t) {
+                            *t = kinds.open.clone();
+                        }
+                    }
+                }
+            }
+        }
+        
+        // Make sure every spawn hex can reach the first one over land
+        if let Some(&first) = spawn_areas.first().and_then(|area| area.first()) {
+            for &target in spawn_areas.iter().flatten().skip(1) {
+                Self::connect(&mut terrain, data, first, target);
+            }
+        }
+        
+        terrain
+    }
+    
+    // Pick settlement sites on generated terrain: a city for each team on its first spawn hex,
+    // then neutral villages, every fourth one a city, on open land away from the spawns and each other.
+    // Returns the site, its kind and the index of the spawn area that owns it.
+    pub fn place_settlements(&self, terrain: &HashMap<HexCoord, TerrainType>, spawn_areas: &[Vec<HexCoord>], data: &GameData) -> Vec<(HexCoord, SettlementKind, Option<usize>)> {
+        let mut sites: Vec<(HexCoord, SettlementKind, Option<usize>)> = spawn_areas.iter()
+            .enumerate()
+            .filter_map(|(index, area)| area.first().map(|&coord| (coord, SettlementKind::City, Some(index))))
+            .collect();
+        
+        let spawns: Vec<HexCoord> = spawn_areas.iter().flatten().copied().collect();
+        let mut candidates: Vec<HexCoord> = terrain.iter()
+            .filter(|(coord, terrain_type)| Self::is_passable(data, terrain_type) && spawns.iter().all(|spawn| spawn.distance(coord) > 2))
+            .map(|(coord, _)| *coord)
+            .collect();
+        
+        // A seeded shuffle, so the same seed gives the same villages
+        let seed = self.config.seed ^ 0x5851_F42D_4C95_7F2D;
+        candidates.sort_by_key(|coord| (hash64(seed ^ hash64((coord.q as u32 as u64) << 32 | (coord.r as u32 as u64))), coord.q, coord.r));
+        
+        let count = Self::percent_of(terrain.len(), self.config.settlement_percent);
+        let mut placed = 0;
+        for coord in candidates {
+            if placed >= count {
+                break;
+            }
+            if sites.iter().all(|(site, _, _)| site.distance(&coord) > 2) {
+                let kind = if placed % 4 == 3 { SettlementKind::City } else { SettlementKind::Village };
+                sites.push((coord, kind, None));
+                placed += 1;
+            }
+        }
+        
+        sites
+    }
+    
+    // Whether every unit class can cross this terrain, and foot units at a reasonable cost,
+    // when checking spawn connectivity
+    pub fn is_passable(data: &GameData, terrain: &TerrainType) -> bool {
+        let definition = match data.terrain.get(terrain) {
+            Some(definition) => definition,
+            None => return false,
+        };
+        
+        definition.movement_cost_for(DEFAULT_UNIT_CLASS).is_some_and(|cost| cost <= 2)
+            && data.units.units.iter().all(|unit| definition.movement_cost_for(&unit.class).is_some())
+    }
+    
+    // Project an axial coordinate onto a plane so features come out round on screen
+    fn sample_point(&self, coord: &HexCoord) -> (f32, f32) {
+        let scale = self.config.feature_scale.max(0.5);
+        let x = 1.5 * coord.q as f32;
+        let y = 3.0_f32.sqrt() * (coord.r as f32 + coord.q as f32 / 2.0);
+        (x / scale, y / scale)
+    }
+    
+    fn percent_of(total: usize, percent: f32) -> usize {
+        ((total as f32) * percent.clamp(0.0, 100.0) / 100.0).round() as usize
+    }
+    
+    // Tie-breaker so equal noise values always sort the same way
+    fn coord_order(a: &HexCoord, b: &HexCoord) -> std::cmp::Ordering {
+        (a.q, a.r).cmp(&(b.q, b.r))
+    }
+    
+    // Check reachability over passable land and carve a path if there is none
+    fn connect(terrain: &mut HashMap<HexCoord, TerrainType>, data: &GameData, from: HexCoord, to: HexCoord) {
+        let mut seen = HashSet::new();
+        let mut queue = VecDeque::new();
+        seen.insert(from);
+        queue.push_back(from);
+        
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                return;
+            }
+            for neighbor in current.neighbors() {
+                if let Some(t) = terrain.get(&neighbor) {
+                    if Self::is_passable(data, t) && seen.insert(neighbor) {
+                        queue.push_back(neighbor);
+                    }
+                }
+            }
+        }
+        
+        // Cheapest path where blocked tiles are expensive, so we carve as few as possible
+        let mut dist: HashMap<HexCoord, i32> = HashMap::new();
+        let mut came_from: HashMap<HexCoord, HexCoord> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        dist.insert(from, 0);
+        heap.push(Reverse((0, from.q, from.r)));
+        
+        while let Some(Reverse((cost, q, r))) = heap.pop() {
+            let current = HexCoord::new(q, r);
+            if current == to {
+                break;
+            }
+            if cost > dist[&current] {
+                continue;
+            }
+            for neighbor in current.neighbors() {
+                if let Some(t) = terrain.get(&neighbor) {
+                    let step = if Self::is_passable(data, t) { 1 } else { 10 };
+                    let next_cost = cost + step;
+                    if dist.get(&neighbor).is_none_or(|&d| next_cost < d) {
+                        dist.insert(neighbor, next_cost);
+                        came_from.insert(neighbor, current);
+                        heap.push(Reverse((next_cost, neighbor.q, neighbor.r)));
+                    }
+                }
+            }
+        }
+        
+        // Turn blocked tiles along the path into open ground
+        let mut current = to;
+        while let Some(&prev) = came_from.get(&current) {
+            if let Some(t) = terrain.get_mut(&current) {
+                if !Self::is_passable(data, t) {
+                    *t = data.terrain.generator.open.clone();
+                }
+            }
+            current = prev;
+        }
+    }
+}
+
+// SplitMix64 finaliser, used as a stateless hash
+fn hash64(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9E37_79B9_7F4A_7C15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+    x ^ (x >> 31)
+}
+
+// Random value in [0, 1) for an integer lattice point
+fn lattice_value(seed: u64, x: i32, y: i32) -> f32 {
+    let h = hash64(seed ^ hash64((x as u32 as u64) << 32 | (y as u32 as u64)));
+    (h >> 40) as f32 / (1u64 << 24) as f32
+}
+
+// Smoothly interpolated value noise in [0, 1)
+fn value_noise(seed: u64, x: f32, y: f32) -> f32 {
+    let x0 = x.floor();
+    let y0 = y.floor();
+    let tx = x - x0;
+    let ty = y - y0;
+    
+    // Smoothstep fade
+    let fx = tx * tx * (3.0 - 2.0 * tx);
+    let fy = ty * ty * (3.0 - 2.0 * ty);
+    
+    let (xi, yi) = (x0 as i32, y0 as i32);
+    let v00 = lattice_value(seed, xi, yi);
+    let v10 = lattice_value(seed, xi + 1, yi);
+    let v01 = lattice_value(seed, xi, yi + 1);
+    let v11 = lattice_value(seed, xi + 1, yi + 1);
+    
+    let top = v00 + (v10 - v00) * fx;
+    let bottom = v01 + (v11 - v01) * fx;
+    top + (bottom - top) * fy
+}
+
+// Several octaves of value noise summed together, normalised back to [0, 1)
+fn fractal_noise(seed: u64, x: f32, y: f32, octaves: u32) -> f32 {
+    let mut total = 0.0;
+    let mut amplitude = 1.0;
+    let mut frequency = 1.0;
+    let mut max = 0.0;
+    
+    for octave in 0..octaves {
+        total += value_noise(seed.wrapping_add(octave as u64), x * frequency, y * frequency) * amplitude;
+        max += amplitude;
+        amplitude *= 0.5;
+        frequency *= 2.0;
+    }
+    
+    total / max
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Hexes a unit class can reach from a hex
+    fn reachable(terrain: &HashMap<HexCoord, TerrainType>, catalog: &TerrainCatalog, class: &str, from: HexCoord) -> HashSet<HexCoord> {
+        let mut seen = HashSet::from([from]);
+        let mut queue = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            for neighbor in current.neighbors() {
+                if terrain.get(&neighbor).and_then(|t| catalog.get(t)).is_some_and(|definition| definition.movement_cost_for(class).is_some()) && seen.insert(neighbor) {
+                    queue.push_back(neighbor);
+                }
+            }
+        }
+        seen
+    }
+    
+    // Three clusters of spawn hexes in different corners of the map
+    fn spawn_areas(coords: &[HexCoord]) -> Vec<Vec<HexCoord>> {
+        let mut sorted = coords.to_vec();
+        [(1, 1), (-1, -1), (-1, 1)].iter()
+            .map(|&(dq, dr)| {
+                sorted.sort_by_key(|c| (dq * c.q + dr * c.r, c.q, c.r));
+                sorted.iter().take(3).copied().collect()
+            })
+            .collect()
+    }
+    
+    #[test]
+    fn every_spawn_hex_is_reachable_from_every_other_by_every_class() {
+        let data = GameData::load().expect("bundled definitions load");
+        
+        // The same definitions with forests that foot units walk through but cavalry can't enter
+        let mut walled = data.clone();
+        for definition in walled.terrain.terrain.iter_mut().filter(|definition| definition.id == data.terrain.generator.woods) {
+            definition.movement_costs.insert("mounted".to_string(), MovementCost::Impassable);
+        }
+        
+        let shapes = [MapShape::RectangleOddQ, MapShape::RectangleOddR, MapShape::Hexagon, MapShape::Parallelogram, MapShape::Triangle];
+        for data in [&data, &walled] {
+            for shape in shapes {
+                let coords = shape.coords(16, 12);
+                let areas = spawn_areas(&coords);
+                for seed in 0..20 {
+                    let config = MapGenConfig { seed, water_percent: 30.0, mountain_percent: 30.0, forest_percent: 30.0, ..Default::default() };
+                    let terrain = MapGenerator::new(config).generate(&coords, &areas, data);
+                    
+                    let spawns: Vec<HexCoord> = areas.iter().flatten().copied().collect();
+                    for class in data.units.classes() {
+                        for &from in &spawns {
+                            let seen = reachable(&terrain, &data.terrain, class, from);
+                            for to in &spawns {
+                                assert!(seen.contains(to), "{:?} seed {}: {} units at {:?} can't reach {:?}", shape, seed, class, from, to);
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+    
+    #[test]
+    fn settlements_keep_to_open_land_away_from_the_spawns() {
+        let data = GameData::load().expect("bundled definitions load");
+        
+        for shape in [MapShape::RectangleOddQ, MapShape::Hexagon, MapShape::Triangle] {
+            let coords = shape.coords(16, 12);
+            let areas = spawn_areas(&coords);
+            let spawns: Vec<HexCoord> = areas.iter().flatten().copied().collect();
+            for seed in 0..20 {
+                let config = MapGenConfig { seed, water_percent: 30.0, mountain_percent: 30.0, settlement_percent: 10.0, ..Default::default() };
+                let generator = MapGenerator::new(config);
+                let terrain = generator.generate(&coords, &areas, &data);
+                let sites = generator.place_settlements(&terrain, &areas, &data);
+                
+                // A city on each team's first spawn hex, then the neutral ones
+                let capitals: Vec<_> = areas.iter().enumerate().map(|(index, area)| (area[0], SettlementKind::City, Some(index))).collect();
+                assert_eq!(sites[..areas.len()], capitals[..], "{:?} seed {}", shape, seed);
+                assert!(sites.len() > areas.len(), "{:?} seed {}: no neutral settlements", shape, seed);
+                
+                for &(coord, _, owner) in &sites[areas.len()..] {
+                    assert_eq!(owner, None);
+                    assert!(MapGenerator::is_passable(&data, &terrain[&coord]), "{:?} seed {}: settlement on {} at {:?}", shape, seed, terrain[&coord], coord);
+                    assert!(spawns.iter().all(|spawn| spawn.distance(&coord) > 2), "{:?} seed {}: settlement at {:?} next to a spawn", shape, seed, coord);
+                }
+            }
+        }
+    }
+}
@@ -2,6 +2,8 @@
 
 pub mod entity;
 pub mod factory;
+pub mod mapgen;
 
 pub use entity::*;
-pub use factory::*; 
\ No newline at end of file
+pub use factory::*;
+pub use mapgen::*; 
\ No newline at end of file
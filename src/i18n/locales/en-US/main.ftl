@@ -2,24 +2,115 @@
 app-title = OpenVictoria
 app-subtitle = Hexagonal Turn-Based Strategy Game
 start-game = Start Game
+scenarios = Scenarios
+load-game = Load Game
 game-settings = Game Settings
 exit = Exit
 version-info = Version 0.1.0 - BSD-0 License
+data-errors = Problems in the game data files:
+
+# Scenarios screen
+scenarios-title = Scenarios
+scenario-errors = Problems in scenario files:
+no-scenarios = No scenarios found in { $dir }.
+scenario-map = Map: { $shape }, { $width } × { $height }
+scenario-turn-limit = Turn limit: { $turns }
+scenario-no-turn-limit = No turn limit
+scenario-victory = Victory:
+scenario-teams = Teams:
+start-scenario = Start Scenario
+victory-eliminate = Defeat all enemies
+victory-hold-objectives = Hold every objective for { $turns } turns:
+victory-survive-until = { $team } survives until turn { $turn }
+victory-kill-leader = Kill the enemy leaders:
+victory-highest-score = Highest score after turn { $turn }:
 
 # Settings screen
 settings-title = Game Settings
 map-size = Map Size:
+map-shape = Map Shape:
+shape-rectangle-odd-q = Rectangle (odd-q)
+shape-rectangle-even-q = Rectangle (even-q)
+shape-rectangle-odd-r = Rectangle (odd-r)
+shape-rectangle-even-r = Rectangle (even-r)
+shape-hexagon = Hexagon
+shape-parallelogram = Parallelogram
+shape-triangle = Triangle
+coord-labels = Coordinate Labels:
+coords-axial = Axial (q, r)
+coords-odd-q = Offset odd-q
+coords-even-q = Offset even-q
+coords-odd-r = Offset odd-r
+coords-even-r = Offset even-r
+coords-doubled-height = Doubled height
+coords-doubled-width = Doubled width
 hex-size = Hex Size:
+hex-orientation = Hex Orientation:
+orientation-flat = Flat-top
+orientation-pointy = Pointy-top
+hex-stretch = Vertical Stretch:
 show-unit-info = Show Unit Info Panel
+show-minimap = Show Minimap
 show-debug = Show Debug Info
+show-coord-labels = Show Coordinate Labels
+animation-speed = Animation Speed (0 = off):
 back = Back
 apply-and-start = Apply and Start Game
 
+# Terrain generation
+terrain-generation = Terrain Generation
+map-seed = Map Seed:
+new-seed = New Seed
+water-percent = Lakes:
+mountain-percent = Mountains:
+forest-percent = Forests:
+settlement-percent = Settlements:
+rules-title = Rules
+zone-of-control-setting = Zone of control
+zone-of-control-help = Moving next to an enemy ends a unit's move, and leaving costs an extra movement point
+supply-setting = Supply
+supply-help = Ranged units use ammo and refill it next to friendly settlements at the start of their turn
+teams-title = Teams
+team-name = Name
+team-color = Colour
+team-controller = Controller
+team-alliance = Alliance
+alliance-none = None
+alliance-name = Alliance { $number }
+add-team = Add Team
+controller-human = Human
+controller-ai = Computer
+controller-remote = Remote
+
+# Keyboard controls
+controls-title = Keyboard Controls
+press-a-key = Press a key...
+reset-bindings = Reset to Defaults
+unbound = (none)
+action-cursor = Move cursor { $direction }
+action-activate = Select, move or attack at the cursor
+action-cycle-units = Next unit that can act
+action-previous-unit = Previous unit that can act
+action-deselect = Deselect
+action-end-turn = End turn
+action-centre-on-unit = Centre on selected unit
+action-pan-left = Pan left
+action-pan-right = Pan right
+action-pan-up = Pan up
+action-pan-down = Pan down
+action-zoom-in = Zoom in
+action-zoom-out = Zoom out
+action-ability = Use ability { $slot }
+
 # Game over screen
 victory-title = Victory!
 victory-message = You've defeated all enemies!
 defeat-title = Defeat
 defeat-message = Your troops have been wiped out.
+draw-title = Draw
+teams-won = { $teams } Won!
+draw-message = The turn limit was reached without a winner.
+winners = Winners:
 return-to-menu = Return to Menu
 restart = Restart
 
@@ -29,42 +120,143 @@ help-controls-header = Game Controls:
 help-click-unit = • Click a unit to select it
 help-green-tiles = • Green tiles indicate movement range
 help-red-tiles = • Red tiles indicate attack range
+help-zoc-tiles = • Orange outlines mark enemy zones of control
+help-objectives = • Gold hexes are objectives, gold rings mark leaders
+help-resting = • Units that neither move nor fight heal a little at the start of your next turn, more in friendly settlements
+help-abilities = • Abilities of the selected unit are on the action bar. Aimed ones light their targets in purple: click one to use it, anywhere else to put it away. Using one ends the unit's turn
+help-statuses = • Icons next to a unit are status effects, such as entrenched after holding ground, suppressed by ranged fire or inspired beside a leader. Hover a unit to see them and the turns left
+help-morale = • The blue bar is morale: heavy hits, flanking and fallen neighbours lower it, friends nearby restore it. Shaken units hit softer, and at zero they flee
+help-supply = • Shots use ammo; units next to a friendly settlement refill at the start of your turn
+help-veterancy = • Units gain experience from damage and kills; each rank adds attack and health and lets you pick a promotion
+help-recruit = • Click one of your cities to recruit units next to it; every unit costs upkeep each turn
+help-settlements = • End a turn on an enemy or neutral settlement to take it; units in your settlements heal
 help-end-turn = • Click "End Turn" button to end your turn
+help-camera-pan = • Drag the board to pan
+help-camera-zoom = • Mouse wheel to zoom
+help-keyboard-header = Keyboard:
 help-unit-types-header = Unit Types:
-help-infantry = • Infantry - Basic unit with balanced attack and defense
-help-archer = • Archer - Ranged unit that can attack from distance
-help-cavalry = • Cavalry - Mobile unit with greater movement range
+help-unit-line = • { $name } ({ $class }) - Health { $health }, attack { $attack }, defense { $defense }, movement { $movement }, range { $range }
 help-terrain-header = Terrain Types:
-help-plains = • Plains - Normal movement
-help-forest = • Forest - Slows movement
-help-mountain = • Mountain - Difficult terrain
-help-water = • Water - Impassable
+help-terrain-line = • { $name } - Movement cost { $cost }
 
 # Unit info
 unit-info-title = Unit Info
 unit-type-infantry = Infantry
 unit-type-archer = Archer
 unit-type-cavalry = Cavalry
-team-player = Player
-team-enemy = Enemy
+terrain-plain = Plains
+terrain-forest = Forest
+terrain-mountain = Mountain
+terrain-water = Water
+terrain-hills = Hills
+terrain-road = Road
+terrain-desert = Desert
+terrain-swamp = Swamp
+unit-class-foot = Foot
+unit-class-mounted = Mounted
+team-default-name = Team { $number }
 health = Health:
 attack = Attack:
 defense = Defense:
 attack-range = Attack Range:
+sight = Sight:
 movement-left = Movement Left:
 unit-acted = Has Acted
 unit-can-act = Can Act
 terrain = Terrain:
 movement-cost = Movement Cost:
+defense-bonus = Defense Bonus:
+impassable = Impassable
 
 # Game interface
+minimap-title = Minimap
 control-panel = Control Panel
 end-turn = End Turn
+next-unit = Next Unit
+skip-animations = Skip Animations
+previous-unit = Previous Unit
+confirm-end-turn-title = End Turn?
+confirm-end-turn-message = { $count } units can still act. End the turn anyway?
+end-turn-anyway = End Turn
+cancel = Cancel
+confirm-end-turn-setting = Confirm ending the turn with idle units
+centre-on-unit = Centre on Unit
 click-to-select = Click a unit to select it
-player-units = Player:
-enemy-units = Enemy:
 menu = Menu
+save-game = Save
+game-saved = Game saved
 help = Help
-player-turn = Player's Turn
-enemy-turn = Enemy's Turn
+team-turn = { $team }'s Turn
+morale = Morale:
+unit-routing = Routing!
+ammo = Ammo:
+unit-resting = Resting, will heal
+rank = Rank { $rank }
+xp-next = XP { $xp }/{ $needed }
+xp-max = XP { $xp } (top rank)
+promotions = Promotions:
+promotion-title = Promotion
+promotion-message = Your { $unit } reached rank { $rank }. Choose a promotion:
+promotion-show = Show unit
+promotion-attack = +1 Attack
+promotion-defense = +1 Defense
+promotion-health = +3 Health
+promotion-movement = +1 Movement
+promotion-range = +1 Range
+promotion-ignore-zoc = Ignore zone of control
+treasury = Gold: { $gold } ({ $net })
+treasury-tooltip = Income { $income }, upkeep { $upkeep }
+gold = Gold: { $gold }
+recruit-title = Recruit
+recruit = Recruit
+unit-cost = { $cost } gold
+unit-upkeep = { $upkeep } upkeep
+recruit-no-city = Only at one of your cities
+recruit-not-allowed = Your side can't recruit this unit
+recruit-no-gold = Not enough gold
+recruit-no-room = No free hex next to the city
+abilities-title = Abilities
+abilities = Abilities:
+ability-passive = { $name } (passive)
+ability-cooldown-left = { $name } ({ $turns })
+ability-details = Range { $range }, area { $area }, cooldown { $cooldown }
+ability-acted = This unit has already acted
+ability-cooldown = Not ready yet
+ability-no-ammo = Not enough ammo
+ability-no-target = Nothing in range to use it on
+guard-up = Guarding: +{ $defense } defense, can't move
+statuses = Effects:
+status-line = { $symbol } { $name } ({ $turns } turns left)
+status-entrenched = Entrenched
+status-entrenched-description = Dug in after holding its ground for a turn: more defense until it moves
+status-slowed = Slowed
+status-slowed-description = Pinned down: less movement
+status-burning = Burning
+status-burning-description = Loses health at the end of each of its turns, but never the last point
+status-inspired = Inspired
+status-inspired-description = Fights harder for ending its turn next to its leader
+status-suppressed = Suppressed
+status-suppressed-description = Keeping its head down under ranged fire: less attack and range
+ability-fire-arrows = Fire Arrows
+ability-fire-arrows-description = A flaming shot at an enemy that sets it burning
+ability-charge = Charge
+ability-charge-description = Extra attack when attacking after moving this turn
+ability-volley = Volley
+ability-volley-description = A hail of arrows on a hex, hitting every enemy on it and around it
+ability-shield-wall = Shield Wall
+ability-shield-wall-description = Raise shields for extra defense, holding the line without moving until after your next turn
+ability-first-aid = First Aid
+ability-first-aid-description = Tend to a friendly unit next to this one, or to this one, restoring some health
+settlement-info-title = Settlement
+settlement-village = Village
+settlement-city = City
+settlement-fort = Fort
+settlement-owner = Owner:
+settlement-neutral = Neutral
+settlement-income = Income:
+settlement-healing = Healing:
+settlements-owned = ({ $count } settlements)
+turns-left = ({ $count } turns left)
+leader = Leader
+turn-number-limit = Turn { $number } of { $limit }
 turn-number = Turn { $number } 
\ No newline at end of file
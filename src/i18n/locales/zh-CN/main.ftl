@@ -2,24 +2,123 @@
 app-title = OpenVictoria
 app-subtitle = 六边形回合制策略游戏
 start-game = 开始游戏
+scenarios = 剧本
+load-game = 读取存档
 game-settings = 游戏设置
 exit = 退出
 version-info = 版本 0.1.0 - BSD-0 许可证
+data-errors = 游戏数据文件有错误:
+
+# 剧本屏幕
+scenarios-title = 剧本
+scenario-errors = 剧本文件有错误:
+no-scenarios = { $dir } 中没有找到剧本。
+scenario-map = 地图：{ $shape }，{ $width } × { $height }
+scenario-turn-limit = 回合限制：{ $turns }
+scenario-no-turn-limit = 无回合限制
+scenario-victory = 胜利条件：
+scenario-teams = 队伍：
+start-scenario = 开始剧本
+victory-eliminate = 消灭所有敌人
+victory-hold-objectives = 占领所有目标 { $turns } 回合：
+victory-survive-until = { $team } 坚持到第 { $turn } 回合
+victory-kill-leader = 击杀敌方首领：
+victory-highest-score = 第 { $turn } 回合后得分最高：
 
 # 设置屏幕
 settings-title = 游戏设置
 map-size = 地图大小:
+map-shape = 地图形状:
+shape-rectangle-odd-q = 矩形 (奇列偏移)
+shape-rectangle-even-q = 矩形 (偶列偏移)
+shape-rectangle-odd-r = 矩形 (奇行偏移)
+shape-rectangle-even-r = 矩形 (偶行偏移)
+shape-hexagon = 六边形
+shape-parallelogram = 平行四边形
+shape-triangle = 三角形
+coord-labels = 坐标标签:
+coords-axial = 轴向坐标 (q, r)
+coords-odd-q = 偏移坐标 奇列
+coords-even-q = 偏移坐标 偶列
+coords-odd-r = 偏移坐标 奇行
+coords-even-r = 偏移坐标 偶行
+coords-doubled-height = 双倍高度坐标
+coords-doubled-width = 双倍宽度坐标
 hex-size = 六边形大小:
+hex-orientation = 六边形朝向:
+orientation-flat = 平顶
+orientation-pointy = 尖顶
+hex-stretch = 纵向拉伸:
 show-unit-info = 显示单位信息面板
+show-minimap = 显示小地图
 show-debug = 显示调试信息
+show-coord-labels = 显示坐标标签
+animation-speed = 动画速度 (0 = 关闭):
 back = 返回
 apply-and-start = 应用并开始游戏
 
+# 地形生成
+terrain-generation = 地形生成
+map-seed = 地图种子:
+new-seed = 新种子
+water-percent = 湖泊:
+mountain-percent = 山脉:
+forest-percent = 森林:
+settlement-percent = 定居点:
+rules-title = 规则
+zone-of-control-setting = 控制区
+zone-of-control-help = 移动到敌人相邻格会结束移动，离开需要额外消耗一点移动力
+supply-setting = 补给
+supply-help = 远程单位射击会消耗弹药，回合开始时在友方定居点旁即可补充
+teams-title = 队伍
+team-name = 名称
+team-color = 颜色
+team-controller = 控制方
+team-alliance = 同盟
+alliance-none = 无
+alliance-name = 同盟 { $number }
+add-team = 添加队伍
+controller-human = 玩家
+controller-ai = 电脑
+controller-remote = 远程
+
+# 键盘操作
+controls-title = 键盘操作
+press-a-key = 请按下按键...
+reset-bindings = 恢复默认
+unbound = (未绑定)
+action-cursor = 光标移动 { $direction }
+action-activate = 在光标处选择、移动或攻击
+action-cycle-units = 下一个可行动单位
+action-previous-unit = 上一个可行动单位
+action-deselect = 取消选择
+action-end-turn = 结束回合
+next-unit = 下一个单位
+skip-animations = 跳过动画
+previous-unit = 上一个单位
+confirm-end-turn-title = 结束回合？
+confirm-end-turn-message = 还有 { $count } 个单位可以行动。仍然结束回合吗？
+end-turn-anyway = 结束回合
+cancel = 取消
+confirm-end-turn-setting = 有单位未行动时确认结束回合
+action-centre-on-unit = 居中到选中单位
+action-pan-left = 向左平移
+action-pan-right = 向右平移
+action-pan-up = 向上平移
+action-pan-down = 向下平移
+action-zoom-in = 放大
+action-zoom-out = 缩小
+action-ability = 使用技能 { $slot }
+
 # 游戏结束屏幕
 victory-title = 胜利！
 victory-message = 你成功击败了所有敌人！
 defeat-title = 失败
 defeat-message = 你的部队被全部消灭了。
+draw-title = 平局
+teams-won = { $teams }获胜！
+draw-message = 已达到回合限制，没有获胜方。
+winners = 获胜方：
 return-to-menu = 返回主菜单
 restart = 重新开始
 
@@ -29,42 +128,135 @@ help-controls-header = 游戏操作：
 help-click-unit = • 点击单位选择它
 help-green-tiles = • 绿色格子表示移动范围
 help-red-tiles = • 红色格子表示攻击范围
+help-zoc-tiles = • 橙色边框表示敌方控制区
+help-objectives = • 金色六边形是目标，金色圆环表示首领
+help-resting = • 不移动也不战斗的单位在下回合开始时恢复少量生命，在友方定居点中恢复更多
+help-abilities = • 选中单位的技能显示在技能栏中。需要瞄准的技能会用紫色标出目标：点击目标使用，点击别处收起。使用技能会结束该单位的行动
+help-statuses = • 单位旁边的图标是状态效果，例如原地坚守获得的坚守、被远程攻击的压制或在首领身边的鼓舞。悬停在单位上可查看效果及剩余回合
+help-morale = • 蓝色条是士气：重创、被包夹和相邻友军阵亡会降低士气，身边有友军则会恢复。士气低落的单位伤害降低，归零时会溃逃
+help-supply = • 射击消耗弹药；回合开始时位于友方定居点旁的单位会补满弹药
+help-veterancy = • 单位造成伤害和击杀敌人会获得经验；每升一级都会提升攻击和生命，并可选择一项晋升
+help-recruit = • 点击己方城市即可在其旁边招募单位；每个单位每回合都要支付维持费
+help-settlements = • 在敌方或中立定居点结束回合即可占领；己方定居点中的单位会恢复生命
 help-end-turn = • 点击「结束回合」按钮结束当前回合
+help-camera-pan = • 拖动地图平移视角
+help-camera-zoom = • 鼠标滚轮缩放
+help-keyboard-header = 键盘：
 help-unit-types-header = 单位类型：
-help-infantry = • 步兵 - 基础单位，均衡的攻防能力
-help-archer = • 弓箭手 - 远程单位，可以从距离攻击
-help-cavalry = • 骑兵 - 机动单位，移动范围更大
+help-unit-line = • { $name }（{ $class }）- 生命 { $health }，攻击 { $attack }，防御 { $defense }，移动 { $movement }，射程 { $range }
 help-terrain-header = 地形类型：
-help-plains = • 平原 - 正常通行
-help-forest = • 森林 - 通行减慢
-help-mountain = • 山脉 - 通行困难
-help-water = • 水域 - 无法通行
+help-terrain-line = • { $name } - 移动消耗 { $cost }
 
 # 单位信息
 unit-info-title = 单位信息
 unit-type-infantry = 步兵
 unit-type-archer = 弓箭手
 unit-type-cavalry = 骑兵
-team-player = 玩家
-team-enemy = 敌人
+terrain-plain = 平原
+terrain-forest = 森林
+terrain-mountain = 山脉
+terrain-water = 水域
+terrain-hills = 丘陵
+terrain-road = 道路
+terrain-desert = 沙漠
+terrain-swamp = 沼泽
+unit-class-foot = 徒步
+unit-class-mounted = 骑乘
+team-default-name = 队伍 { $number }
 health = 生命值:
 attack = 攻击力:
 defense = 防御力:
 attack-range = 攻击范围:
+sight = 视野:
 movement-left = 剩余移动力:
 unit-acted = 已行动
 unit-can-act = 可行动
 terrain = 地形:
 movement-cost = 移动消耗:
+defense-bonus = 防御加成:
+impassable = 无法通行
 
 # 游戏界面
+minimap-title = 小地图
 control-panel = 控制面板
 end-turn = 结束回合
+centre-on-unit = 居中到单位
 click-to-select = 点击单位选择它
-player-units = 玩家:
-enemy-units = 敌人:
 menu = 菜单
+save-game = 保存
+game-saved = 游戏已保存
 help = 帮助
-player-turn = 玩家回合
-enemy-turn = 敌人回合
+team-turn = { $team }的回合
+morale = 士气：
+unit-routing = 溃逃中！
+ammo = 弹药：
+unit-resting = 休整中，将恢复生命
+rank = 等级 { $rank }
+xp-next = 经验 { $xp }/{ $needed }
+xp-max = 经验 { $xp }（最高等级）
+promotions = 晋升：
+promotion-title = 晋升
+promotion-message = 你的{ $unit }升到了 { $rank } 级。请选择一项晋升：
+promotion-show = 查看单位
+promotion-attack = 攻击 +1
+promotion-defense = 防御 +1
+promotion-health = 生命 +3
+promotion-movement = 移动 +1
+promotion-range = 射程 +1
+promotion-ignore-zoc = 无视控制区
+treasury = 金币：{ $gold }（{ $net }）
+treasury-tooltip = 收入 { $income }，维持费 { $upkeep }
+gold = 金币：{ $gold }
+recruit-title = 招募
+recruit = 招募
+unit-cost = { $cost } 金币
+unit-upkeep = 维持费 { $upkeep }
+recruit-no-city = 只能在己方城市招募
+recruit-not-allowed = 你的阵营不能招募这种单位
+recruit-no-gold = 金币不足
+recruit-no-room = 城市旁边没有空位
+abilities-title = 技能
+abilities = 技能：
+ability-passive = { $name }（被动）
+ability-cooldown-left = { $name }（{ $turns }）
+ability-details = 射程 { $range }，范围 { $area }，冷却 { $cooldown }
+ability-acted = 该单位已经行动过
+ability-cooldown = 尚未就绪
+ability-no-ammo = 弹药不足
+ability-no-target = 射程内没有可用目标
+guard-up = 防守中：防御 +{ $defense }，不能移动
+statuses = 状态：
+status-line = { $symbol } { $name }（剩余 { $turns } 回合）
+status-entrenched = 坚守
+status-entrenched-description = 原地坚守一回合后构筑工事：移动之前防御提高
+status-slowed = 迟缓
+status-slowed-description = 被压制在原地：移动力降低
+status-burning = 燃烧
+status-burning-description = 每个己方回合结束时损失生命值，但不会降到 1 以下
+status-inspired = 鼓舞
+status-inspired-description = 回合结束时位于首领旁边，攻击力提高
+status-suppressed = 压制
+status-suppressed-description = 在远程火力下抬不起头：攻击力和射程降低
+ability-fire-arrows = 火箭
+ability-fire-arrows-description = 向敌人射出火箭，使其燃烧
+ability-charge = 冲锋
+ability-charge-description = 本回合移动后进攻时攻击力提高
+ability-volley = 齐射
+ability-volley-description = 向一个六角格倾泻箭雨，命中该格及周围的所有敌人
+ability-shield-wall = 盾墙
+ability-shield-wall-description = 举盾提高防御，坚守阵地直到下个回合结束后，期间不能移动
+ability-first-aid = 急救
+ability-first-aid-description = 救治相邻的友军或自身，恢复一些生命值
+settlement-info-title = 定居点
+settlement-village = 村庄
+settlement-city = 城市
+settlement-fort = 要塞
+settlement-owner = 所属：
+settlement-neutral = 中立
+settlement-income = 收入：
+settlement-healing = 治疗：
+settlements-owned = （{ $count } 个定居点）
+turns-left = （剩余 { $count } 回合）
+leader = 首领
+turn-number-limit = 第 { $number } / { $limit } 回合
 turn-number = 第 { $number } 回合 
\ No newline at end of file
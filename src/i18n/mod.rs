@@ -30,7 +30,7 @@ impl Language {
         vec![Language::English, Language::Chinese]
     }
     
-    pub fn from_str(lang_str: &str) -> Option<Language> {
+    pub fn from_code(lang_str: &str) -> Option<Language> {
         match lang_str {
             "en-US" => Some(Language::English),
             "zh-CN" => Some(Language::Chinese),
@@ -83,11 +83,11 @@ impl Locale {
             let main_path = format!("src/i18n/locales/{}/main.ftl", lang_str);
             if let Ok(source) = Self::read_file(&main_path) {
                 let resource = FluentResource::try_new(source)
-                    .expect(&format!("Failed to parse Fluent resource for {}", lang_str));
+                    .unwrap_or_else(|_| panic!("Failed to parse Fluent resource for {}", lang_str));
                 
                 bundle
                     .add_resource(resource)
-                    .expect(&format!("Failed to add Fluent resource for {}", lang_str));
+                    .unwrap_or_else(|_| panic!("Failed to add Fluent resource for {}", lang_str));
                 
                 self.bundles.insert(lang_str.to_string(), bundle);
             }
@@ -99,6 +99,11 @@ impl Locale {
         fs::read_to_string(path)
     }
     
+    // 当前语言是否有这个键
+    pub fn has_message(&self, key: &str) -> bool {
+        self.bundles.get(self.current_language.as_str()).is_some_and(|bundle| bundle.has_message(key))
+    }
+    
     // 获取指定键对应的本地化文本
     pub fn get_message(&self, key: &str) -> String {
         let lang_str = self.current_language.as_str();
@@ -3,6 +3,8 @@ pub mod e;
 pub mod c;
 pub mod s;
 pub mod i18n;
+pub mod settings;
+pub mod assets;
 
 // Re-export the core types for convenience
 pub use e::entity::World;
@@ -3,15 +3,19 @@ use egui::{Pos2, Color32, RichText, Align, Layout};
 
 // 导入我们自己的库
 use openvictoria::{World, HexMapFactory, Locale};
-use openvictoria::s::{InputSystem, RenderSystem};
+use openvictoria::e::MapGenConfig;
+use openvictoria::s::{InputSystem, RenderSystem, CameraSystem, MinimapSystem, AnimationSystem, AiSystem, VictorySystem, SettlementSystem, EconomySystem, VeterancySystem, AbilitySystem, StatusSystem, InputAction, KeyBindings};
 use openvictoria::c::*;
 use openvictoria::i18n::Language;
+use openvictoria::settings::{Settings, TeamSettings};
+use openvictoria::assets::Assets;
 
 // 游戏界面状态
 #[derive(PartialEq)]
 enum GameScreen {
     MainMenu,
     Settings,
+    Scenarios,
     Playing,
     GameOver,
 }
@@ -20,44 +24,507 @@ enum GameScreen {
 struct MyApp {
     ecs_world: World,
     end_turn_clicked: bool,
+    centre_clicked: bool,
     game_screen: GameScreen,
     map_size: i32,
+    map_shape: MapShape,
+    coord_labels: CoordSystem,
     hex_size: f32,
-    player_won: bool,
+    hex_orientation: HexOrientation,
+    hex_stretch: f32,
+    map_gen: MapGenConfig,
+    winners: Vec<u8>, // 获胜的队伍
     show_help: bool,
     show_debug: bool,
+    show_coord_labels: bool,
     show_unit_info: bool,
+    show_minimap: bool,
+    confirm_end_turn: bool,
+    confirming_end_turn: bool, // 正在显示结束回合确认框
+    animation_speed: f32,
+    zone_of_control: bool,
+    supply: bool,
+    teams: Vec<TeamSettings>, // 随机地图的队伍设置
+    key_bindings: KeyBindings,
+    rebinding: Option<InputAction>, // 正在等待新按键的操作
+    assets: Assets,
+    game_data: Option<GameData>, // 单位和地形定义，加载失败时为空
+    load_errors: Vec<String>, // 数据文件中的错误，显示在主菜单
+    scenarios: Vec<Scenario>, // 剧本目录中加载成功的剧本
+    scenario_errors: Vec<String>, // 剧本文件中的错误，显示在剧本列表下方
+    selected_scenario: usize,
+    active_scenario: Option<Scenario>, // 正在进行的剧本，随机地图时为空
+    save_message: Option<String>, // 保存游戏的结果，显示在顶部状态栏
+    save_error: Option<String>, // 读取存档失败的原因，显示在主菜单
     locale: Locale,
 }
 
 impl Default for MyApp {
     fn default() -> Self {
+        // 读取保存的设置，没有则使用默认值
+        let settings = Settings::load();
+        let language = Language::from_code(&settings.language).unwrap_or(Language::Chinese); // 默认使用中文
+        
+        // 读取单位和地形定义
+        let (game_data, load_errors) = match GameData::load() {
+            Ok(data) => (Some(data), Vec::new()),
+            Err(errors) => (None, errors.iter().map(|e| e.to_string()).collect()),
+        };
+        
         Self {
             ecs_world: World::new(),
             end_turn_clicked: false,
+            centre_clicked: false,
             game_screen: GameScreen::MainMenu,
-            map_size: 8,
-            hex_size: 30.0,
-            player_won: false,
+            map_size: settings.map_size,
+            map_shape: settings.map_shape,
+            coord_labels: settings.coord_labels,
+            hex_size: settings.hex_size,
+            hex_orientation: settings.hex_orientation,
+            hex_stretch: settings.hex_stretch,
+            map_gen: MapGenConfig {
+                seed: Self::random_seed(), // 每次启动换一张新地图
+                ..settings.map_gen
+            },
+            winners: Vec::new(),
             show_help: false,
-            show_debug: false,
-            show_unit_info: true,
-            locale: Locale::new(Language::Chinese), // 默认使用中文
+            show_debug: settings.show_debug,
+            show_coord_labels: settings.show_coord_labels,
+            show_unit_info: settings.show_unit_info,
+            show_minimap: settings.show_minimap,
+            confirm_end_turn: settings.confirm_end_turn,
+            confirming_end_turn: false,
+            animation_speed: settings.animation_speed,
+            zone_of_control: settings.zone_of_control,
+            supply: settings.supply,
+            teams: settings.teams,
+            key_bindings: settings.key_bindings,
+            rebinding: None,
+            assets: Assets::default(),
+            game_data,
+            load_errors,
+            scenarios: Vec::new(),
+            scenario_errors: Vec::new(),
+            selected_scenario: 0,
+            active_scenario: None,
+            save_message: None,
+            save_error: None,
+            locale: Locale::new(language),
         }
     }
 }
 
 impl MyApp {
-    // 初始化游戏世界
+    // 启动时加载贴图
+    fn new(cc: &eframe::CreationContext) -> Self {
+        let app = Self::default();
+        let assets = Assets::load(&cc.egui_ctx, app.game_data.as_ref().map(|data| &data.terrain));
+        Self { assets, ..app }
+    }
+    
+    // 用当前时间生成地图种子
+    fn random_seed() -> u64 {
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_nanos() as u64)
+            .unwrap_or(0)
+    }
+    
+    // 保存当前设置
+    fn save_settings(&self) {
+        let settings = Settings {
+            map_size: self.map_size,
+            map_shape: self.map_shape,
+            coord_labels: self.coord_labels,
+            hex_size: self.hex_size,
+            hex_orientation: self.hex_orientation,
+            hex_stretch: self.hex_stretch,
+            map_gen: self.map_gen.clone(),
+            language: self.locale.get_language().as_str().to_string(),
+            show_unit_info: self.show_unit_info,
+            show_minimap: self.show_minimap,
+            show_debug: self.show_debug,
+            show_coord_labels: self.show_coord_labels,
+            confirm_end_turn: self.confirm_end_turn,
+            animation_speed: self.animation_speed,
+            zone_of_control: self.zone_of_control,
+            supply: self.supply,
+            teams: self.teams.clone(),
+            key_bindings: self.key_bindings.clone(),
+        };
+        
+        if let Err(e) = settings.save() {
+            eprintln!("保存设置失败: {}", e);
+        }
+    }
+    
+    // 单位类型的显示名称，没有翻译时直接显示 id
+    fn unit_name(&self, unit_type: &UnitType) -> String {
+        match self.game_data.as_ref().and_then(|data| data.units.get(unit_type)) {
+            Some(definition) if self.locale.has_message(&definition.name) => self.locale.get_message(&definition.name),
+            _ => unit_type.to_string(),
+        }
+    }
+    
+    // 地形的显示名称，没有翻译时直接显示 id
+    fn terrain_name(&self, definition: &TerrainDefinition) -> String {
+        if self.locale.has_message(&definition.name) {
+            self.locale.get_message(&definition.name)
+        } else {
+            definition.id.to_string()
+        }
+    }
+    
+    // 兵种的显示名称，没有翻译时直接显示 id
+    fn class_name(&self, class: &str) -> String {
+        let key = format!("unit-class-{}", class);
+        if self.locale.has_message(&key) {
+            self.locale.get_message(&key)
+        } else {
+            class.to_string()
+        }
+    }
+    
+    // 移动消耗的显示文字，无法进入时显示“无法通行”
+    fn movement_cost_label(&self, cost: Option<i32>) -> String {
+        cost.map_or_else(|| self.locale.get_message("impassable"), |cost| cost.to_string())
+    }
+    
+    // 受状态效果影响的属性值，提高显示为绿色，降低显示为红色
+    fn stat_text(text: String, value: i32, base: i32) -> RichText {
+        let text = RichText::new(text);
+        match value.cmp(&base) {
+            std::cmp::Ordering::Greater => text.color(Color32::GREEN),
+            std::cmp::Ordering::Less => text.color(Color32::RED),
+            std::cmp::Ordering::Equal => text,
+        }
+    }
+    
+    // 操作的显示名称，光标方向用箭头表示，技能用技能栏编号
+    fn action_label(&self, action: InputAction) -> String {
+        match (action.cursor_direction(), action.ability_slot()) {
+            (Some(direction), _) => {
+                let arrow = InputAction::direction_arrow(direction, self.hex_orientation);
+                self.locale.get_message_args(action.name_key(), &[("direction", arrow)])
+            },
+            (None, Some(slot)) => self.locale.get_message_args(action.name_key(), &[("slot", &(slot + 1).to_string())]),
+            (None, None) => self.locale.get_message(action.name_key()),
+        }
+    }
+    
+    // 操作当前绑定的按键名称
+    fn key_label(&self, action: InputAction) -> String {
+        match self.key_bindings.key(action) {
+            Some(key) => key.name().to_string(),
+            None => self.locale.get_message("unbound"),
+        }
+    }
+    
+    // 队伍的默认名称
+    fn default_team_name(&self, index: usize) -> String {
+        self.locale.get_message_args("team-default-name", &[("number", &(index + 1).to_string())])
+    }
+    
+    // 根据设置创建队伍和同盟表
+    fn build_teams(&self) -> Teams {
+        let infos = self.teams.iter().enumerate().map(|(index, settings)| {
+            let [r, g, b] = settings.color;
+            TeamInfo {
+                id: index as u8,
+                name: if settings.name.trim().is_empty() { self.default_team_name(index) } else { settings.name.clone() },
+                color: Color32::from_rgb(r, g, b),
+                controller: settings.controller,
+            }
+        });
+        let mut teams = Teams::new(infos.collect());
+        
+        // 同盟编号相同的队伍互为盟友
+        for (a, first) in self.teams.iter().enumerate() {
+            for (b, second) in self.teams.iter().enumerate().skip(a + 1) {
+                if first.alliance != 0 && first.alliance == second.alliance {
+                    teams.set_allied(a as u8, b as u8);
+                }
+            }
+        }
+        
+        teams
+    }
+    
+    // 胜利条件的说明文字，队伍名称由调用方提供
+    fn victory_text(&self, condition: &VictoryCondition, team_name: &dyn Fn(u8) -> String, turn_limit: Option<i32>) -> String {
+        let args: Vec<(&str, String)> = match *condition {
+            VictoryCondition::HoldObjectives { turns } => vec![("turns", turns.to_string())],
+            VictoryCondition::SurviveUntil { team, turn } => vec![("team", team_name(team)), ("turn", turn.to_string())],
+            VictoryCondition::HighestScore => vec![("turn", turn_limit.unwrap_or(0).to_string())],
+            VictoryCondition::Eliminate | VictoryCondition::KillLeader => Vec::new(),
+        };
+        let args: Vec<(&str, &str)> = args.iter().map(|(name, value)| (*name, value.as_str())).collect();
+        self.locale.get_message_args(condition.name_key(), &args)
+    }
+    
+    // 随机地图的队伍设置：名称、颜色、控制方和同盟
+    fn render_team_settings(&mut self, ui: &mut egui::Ui) {
+        let hints: Vec<String> = (0..self.teams.len()).map(|index| self.default_team_name(index)).collect();
+        let alliance_name = |alliance: u8| match alliance {
+            0 => self.locale.get_message("alliance-none"),
+            n => self.locale.get_message_args("alliance-name", &[("number", &n.to_string())]),
+        };
+        let alliance_names: Vec<String> = (0..=(MAX_TEAMS / 2) as u8).map(alliance_name).collect();
+        let controller_names: Vec<(Controller, String)> = Controller::all().into_iter()
+            .map(|controller| (controller, self.locale.get_message(controller.name_key())))
+            .collect();
+        let can_remove = self.teams.len() > MIN_TEAMS;
+        let mut removed = None;
+        
+        egui::Grid::new("team_settings").striped(true).show(ui, |ui| {
+            ui.label(self.locale.get_message("team-name"));
+            ui.label(self.locale.get_message("team-color"));
+            ui.label(self.locale.get_message("team-controller"));
+            ui.label(self.locale.get_message("team-alliance"));
+            ui.end_row();
+            
+            for (index, team) in self.teams.iter_mut().enumerate() {
+                ui.add(egui::TextEdit::singleline(&mut team.name).hint_text(&hints[index]).desired_width(120.0));
+                ui.color_edit_button_srgb(&mut team.color);
+                
+                let controller_name = controller_names.iter().find(|(controller, _)| *controller == team.controller).map_or("", |(_, name)| name);
+                egui::ComboBox::from_id_source(("team_controller", index))
+                    .selected_text(controller_name)
+                    .show_ui(ui, |ui| {
+                        for (controller, name) in &controller_names {
+                            ui.selectable_value(&mut team.controller, *controller, name);
+                        }
+                    });
+                
+                egui::ComboBox::from_id_source(("team_alliance", index))
+                    .selected_text(&alliance_names[(team.alliance as usize).min(alliance_names.len() - 1)])
+                    .show_ui(ui, |ui| {
+                        for (alliance, name) in alliance_names.iter().enumerate() {
+                            ui.selectable_value(&mut team.alliance, alliance as u8, name);
+                        }
+                    });
+                
+                if ui.add_enabled(can_remove, egui::Button::new("✖")).clicked() {
+                    removed = Some(index);
+                }
+                ui.end_row();
+            }
+        });
+        
+        if let Some(index) = removed {
+            self.teams.remove(index);
+        }
+        
+        if self.teams.len() < MAX_TEAMS && ui.button(self.locale.get_message("add-team")).clicked() {
+            self.teams.push(TeamSettings::numbered(self.teams.len()));
+        }
+    }
+    
+    // 地图布局，原点由摄像机负责居中
+    fn hex_layout(&self) -> HexLayout {
+        let size = egui::vec2(self.hex_size, self.hex_size * self.hex_stretch);
+        HexLayout::new(self.hex_orientation, size, Pos2::ZERO)
+    }
+    
+    // 初始化游戏世界（随机地图）
     fn initialize_game(&mut self) {
         self.ecs_world = World::new();
+        self.active_scenario = None;
+        let data = match &self.game_data {
+            Some(data) => data,
+            None => return,
+        };
+        
+        let layout = self.hex_layout();
+        let teams = self.build_teams();
+        HexMapFactory::create_map(&mut self.ecs_world, self.map_shape, (self.map_size, self.map_size), layout, &self.map_gen, data, teams);
+        
+        // 随机地图使用设置中的规则
+        if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(&self.ecs_world) {
+            self.ecs_world.add_component(game_state_entity, GameRules { zone_of_control: self.zone_of_control, supply: self.supply, ..GameRules::default() });
+        }
+        
+        self.start_playing();
+    }
+    
+    // 开始一个剧本，地图、队伍和规则都来自剧本文件
+    fn start_scenario(&mut self, scenario: Scenario) {
+        self.ecs_world = World::new();
+        let data = match &self.game_data {
+            Some(data) => data,
+            None => return,
+        };
         
-        // 创建地图，设置大小和原点
-        let origin = Pos2::new(300.0, 300.0);
-        HexMapFactory::create_map(&mut self.ecs_world, self.map_size, self.map_size, self.hex_size, origin);
+        let layout = self.hex_layout();
+        let teams = scenario.build_teams(self.locale.get_language().as_str());
+        HexMapFactory::create_scenario(&mut self.ecs_world, &scenario, layout, data, teams);
+        self.active_scenario = Some(scenario);
+        
+        self.start_playing();
+    }
+    
+    // 读取存档，继续之前的游戏
+    fn load_game(&mut self) {
+        let data = match &self.game_data {
+            Some(data) => data,
+            None => return,
+        };
+        
+        let save = match SaveGame::load(data) {
+            Ok(save) => save,
+            Err(e) => {
+                self.save_error = Some(e);
+                return;
+            },
+        };
+        
+        self.ecs_world = World::new();
+        let layout = self.hex_layout();
+        HexMapFactory::create_saved_game(&mut self.ecs_world, &save, layout, data);
+        // 剧本的存档仍然可以重新开始该剧本
+        self.active_scenario = save.scenario.as_ref().and_then(|path| Scenario::load(std::path::Path::new(path), data).ok());
+        self.save_error = None;
+        
+        self.start_playing();
+    }
+    
+    // 保存当前游戏，覆盖之前的存档
+    fn save_game(&mut self) {
+        let scenario = self.active_scenario.as_ref().map(|scenario| scenario.path.clone());
+        let result = match HexMapFactory::save_game(&self.ecs_world, scenario) {
+            Some(save) => save.save(),
+            None => return,
+        };
+        
+        self.save_message = Some(match result {
+            Ok(()) => self.locale.get_message("game-saved"),
+            Err(e) => e,
+        });
+    }
+    
+    // 重新开始当前的剧本或随机地图
+    fn restart_game(&mut self) {
+        match self.active_scenario.take() {
+            Some(scenario) => self.start_scenario(scenario),
+            None => self.initialize_game(),
+        }
+    }
+    
+    // 地图创建完成后进入游戏
+    fn start_playing(&mut self) {
+        // 坐标标签使用玩家选择的坐标系，并使用已加载的贴图
+        if let Some(map_settings_entity) = HexMapFactory::get_map_settings_entity(&self.ecs_world) {
+            if let Some(settings) = self.ecs_world.get_component_mut::<MapSettings>(map_settings_entity) {
+                settings.coord_labels = self.coord_labels;
+            }
+            self.ecs_world.add_component(map_settings_entity, self.assets.sprites());
+        }
         
         self.game_screen = GameScreen::Playing;
         self.end_turn_clicked = false;
+        self.save_message = None;
+        self.confirming_end_turn = false;
+    }
+    
+    // 重新读取剧本目录并打开剧本列表
+    fn open_scenarios(&mut self) {
+        let (scenarios, errors) = match &self.game_data {
+            Some(data) => Scenario::load_all(SCENARIOS_DIR, data),
+            None => (Vec::new(), Vec::new()),
+        };
+        
+        self.scenarios = scenarios;
+        self.scenario_errors = errors.iter().map(|e| e.to_string()).collect();
+        self.selected_scenario = self.selected_scenario.min(self.scenarios.len().saturating_sub(1));
+        self.game_screen = GameScreen::Scenarios;
+    }
+    
+    // 渲染剧本列表：左边选择剧本，右边显示简报和详情
+    fn render_scenarios(&mut self, ctx: &egui::Context) {
+        let language = self.locale.get_language().as_str();
+        let mut start = None;
+        
+        egui::CentralPanel::default().show(ctx, |ui| {
+            ui.vertical_centered(|ui| {
+                ui.add_space(30.0);
+                ui.heading(self.locale.get_message("scenarios-title"));
+            });
+            ui.add_space(20.0);
+            
+            if self.scenarios.is_empty() {
+                ui.label(self.locale.get_message_args("no-scenarios", &[("dir", SCENARIOS_DIR)]));
+            } else {
+                ui.columns(2, |columns| {
+                    egui::ScrollArea::vertical().id_source("scenario_list").show(&mut columns[0], |ui| {
+                        for (index, scenario) in self.scenarios.iter().enumerate() {
+                            if ui.selectable_label(self.selected_scenario == index, scenario.name.get(language)).clicked() {
+                                self.selected_scenario = index;
+                            }
+                        }
+                    });
+                    
+                    if let Some(scenario) = self.scenarios.get(self.selected_scenario) {
+                        let ui = &mut columns[1];
+                        ui.heading(scenario.name.get(language));
+                        ui.label(scenario.briefing.get(language));
+                        ui.separator();
+                        
+                        let map = &scenario.map;
+                        ui.label(self.locale.get_message_args("scenario-map", &[
+                            ("shape", &self.locale.get_message(map.shape.name_key())),
+                            ("width", &map.width.to_string()),
+                            ("height", &map.height.to_string()),
+                        ]));
+                        match scenario.turn_limit {
+                            Some(turns) => ui.label(self.locale.get_message_args("scenario-turn-limit", &[("turns", &turns.to_string())])),
+                            None => ui.label(self.locale.get_message("scenario-no-turn-limit")),
+                        };
+                        if scenario.rules.zone_of_control {
+                            ui.label(self.locale.get_message("zone-of-control-setting"));
+                        }
+                        if scenario.rules.supply {
+                            ui.label(self.locale.get_message("supply-setting"));
+                        }
+                        
+                        let teams = scenario.build_teams(language);
+                        ui.label(self.locale.get_message("scenario-victory"));
+                        for condition in &scenario.victory {
+                            ui.label(format!("• {}", self.victory_text(condition, &|team_id| teams.name(team_id), scenario.turn_limit)));
+                        }
+                        
+                        ui.label(self.locale.get_message("scenario-teams"));
+                        for team in &teams.teams {
+                            ui.horizontal(|ui| {
+                                ui.label(RichText::new(&team.name).color(team.color).strong());
+                                ui.label(self.locale.get_message(team.controller.name_key()));
+                            });
+                        }
+                        
+                        ui.add_space(10.0);
+                        if ui.button(RichText::new(self.locale.get_message("start-scenario")).size(20.0)).clicked() {
+                            start = Some(scenario.clone());
+                        }
+                    }
+                });
+            }
+            
+            if !self.scenario_errors.is_empty() {
+                ui.add_space(20.0);
+                ui.label(RichText::new(self.locale.get_message("scenario-errors")).color(Color32::RED));
+                for error in &self.scenario_errors {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+            }
+            
+            ui.add_space(20.0);
+            if ui.button(self.locale.get_message("back")).clicked() {
+                self.game_screen = GameScreen::MainMenu;
+            }
+        });
+        
+        if let Some(scenario) = start {
+            self.start_scenario(scenario);
+        }
     }
     
     // 渲染主菜单
@@ -71,10 +538,21 @@ impl MyApp {
                 
                 ui.add_space(50.0);
                 
-                if ui.button(RichText::new(self.locale.get_message("start-game")).size(24.0)).clicked() {
+                // 数据文件有错误时无法开始游戏
+                let can_start = self.game_data.is_some();
+                if ui.add_enabled(can_start, egui::Button::new(RichText::new(self.locale.get_message("start-game")).size(24.0))).clicked() {
                     self.initialize_game();
                 }
                 
+                if ui.add_enabled(can_start, egui::Button::new(RichText::new(self.locale.get_message("scenarios")).size(24.0))).clicked() {
+                    self.open_scenarios();
+                }
+                
+                let has_save = std::path::Path::new(SAVE_PATH).exists();
+                if ui.add_enabled(can_start && has_save, egui::Button::new(RichText::new(self.locale.get_message("load-game")).size(24.0))).clicked() {
+                    self.load_game();
+                }
+                
                 if ui.button(RichText::new(self.locale.get_message("game-settings")).size(24.0)).clicked() {
                     self.game_screen = GameScreen::Settings;
                 }
@@ -83,8 +561,20 @@ impl MyApp {
                     ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                 }
                 
+                if let Some(error) = &self.save_error {
+                    ui.label(RichText::new(error).color(Color32::RED));
+                }
+                
                 ui.add_space(30.0);
                 ui.label(self.locale.get_message("version-info"));
+                
+                if !self.load_errors.is_empty() {
+                    ui.add_space(20.0);
+                    ui.label(RichText::new(self.locale.get_message("data-errors")).color(Color32::RED));
+                    for error in &self.load_errors {
+                        ui.label(RichText::new(error).color(Color32::RED));
+                    }
+                }
             });
         });
     }
@@ -99,7 +589,29 @@ impl MyApp {
                 
                 ui.horizontal(|ui| {
                     ui.label(self.locale.get_message("map-size"));
-                    ui.add(egui::Slider::new(&mut self.map_size, 4..=12).text(""));
+                    ui.add(egui::Slider::new(&mut self.map_size, 4..=200).logarithmic(true).text(""));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("map-shape"));
+                    egui::ComboBox::from_id_source("map_shape")
+                        .selected_text(self.locale.get_message(self.map_shape.name_key()))
+                        .show_ui(ui, |ui| {
+                            for shape in MapShape::all() {
+                                ui.selectable_value(&mut self.map_shape, shape, self.locale.get_message(shape.name_key()));
+                            }
+                        });
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("coord-labels"));
+                    egui::ComboBox::from_id_source("coord_labels")
+                        .selected_text(self.locale.get_message(self.coord_labels.name_key()))
+                        .show_ui(ui, |ui| {
+                            for system in CoordSystem::all() {
+                                ui.selectable_value(&mut self.coord_labels, system, self.locale.get_message(system.name_key()));
+                            }
+                        });
                 });
                 
                 ui.horizontal(|ui| {
@@ -107,8 +619,84 @@ impl MyApp {
                     ui.add(egui::Slider::new(&mut self.hex_size, 20.0..=50.0).text(""));
                 });
                 
-                ui.checkbox(&mut self.show_unit_info, &self.locale.get_message("show-unit-info"));
-                ui.checkbox(&mut self.show_debug, &self.locale.get_message("show-debug"));
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("hex-orientation"));
+                    for orientation in HexOrientation::all() {
+                        if ui.selectable_label(self.hex_orientation == orientation, self.locale.get_message(orientation.name_key())).clicked() {
+                            // 矩形地图跟随朝向换成对应的偏移方式
+                            self.hex_orientation = orientation;
+                            self.map_shape = self.map_shape.for_orientation(orientation);
+                        }
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("hex-stretch"));
+                    ui.add(egui::Slider::new(&mut self.hex_stretch, 0.5..=1.5).text(""));
+                });
+                
+                // 地形生成参数
+                ui.separator();
+                ui.heading(self.locale.get_message("terrain-generation"));
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("map-seed"));
+                    ui.add(egui::DragValue::new(&mut self.map_gen.seed));
+                    if ui.button(self.locale.get_message("new-seed")).clicked() {
+                        self.map_gen.seed = Self::random_seed();
+                    }
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("water-percent"));
+                    ui.add(egui::Slider::new(&mut self.map_gen.water_percent, 0.0..=40.0).suffix("%"));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("mountain-percent"));
+                    ui.add(egui::Slider::new(&mut self.map_gen.mountain_percent, 0.0..=40.0).suffix("%"));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("forest-percent"));
+                    ui.add(egui::Slider::new(&mut self.map_gen.forest_percent, 0.0..=60.0).suffix("%"));
+                });
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("settlement-percent"));
+                    ui.add(egui::Slider::new(&mut self.map_gen.settlement_percent, 0.0..=10.0).suffix("%"));
+                });
+                
+                // 规则选项
+                ui.separator();
+                ui.heading(self.locale.get_message("rules-title"));
+                ui.checkbox(&mut self.zone_of_control, self.locale.get_message("zone-of-control-setting"))
+                    .on_hover_text(self.locale.get_message("zone-of-control-help"));
+                ui.checkbox(&mut self.supply, self.locale.get_message("supply-setting"))
+                    .on_hover_text(self.locale.get_message("supply-help"));
+                
+                // 队伍设置
+                ui.separator();
+                ui.heading(self.locale.get_message("teams-title"));
+                self.render_team_settings(ui);
+                
+                ui.separator();
+                
+                ui.checkbox(&mut self.show_unit_info, self.locale.get_message("show-unit-info"));
+                ui.checkbox(&mut self.show_minimap, self.locale.get_message("show-minimap"));
+                ui.checkbox(&mut self.show_debug, self.locale.get_message("show-debug"));
+                ui.checkbox(&mut self.show_coord_labels, self.locale.get_message("show-coord-labels"));
+                ui.checkbox(&mut self.confirm_end_turn, self.locale.get_message("confirm-end-turn-setting"));
+                
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("animation-speed"));
+                    ui.add(egui::Slider::new(&mut self.animation_speed, 0.0..=3.0).text(""));
+                });
+                
+                // 键盘绑定
+                ui.separator();
+                ui.heading(self.locale.get_message("controls-title"));
+                self.render_key_bindings(ui);
                 
                 // 语言选择
                 ui.separator();
@@ -126,10 +714,15 @@ impl MyApp {
                 
                 ui.horizontal(|ui| {
                     if ui.button(self.locale.get_message("back")).clicked() {
+                        self.rebinding = None;
+                        self.save_settings();
                         self.game_screen = GameScreen::MainMenu;
                     }
                     
-                    if ui.button(self.locale.get_message("apply-and-start")).clicked() {
+                    let can_start = self.game_data.is_some();
+                    if ui.add_enabled(can_start, egui::Button::new(self.locale.get_message("apply-and-start"))).clicked() {
+                        self.rebinding = None;
+                        self.save_settings();
                         self.initialize_game();
                     }
                 });
@@ -137,13 +730,64 @@ impl MyApp {
         });
     }
     
+    // 渲染按键绑定列表，点击按钮后按下新按键即可重新绑定
+    fn render_key_bindings(&mut self, ui: &mut egui::Ui) {
+        // 等待新按键
+        if let Some(action) = self.rebinding {
+            let pressed = ui.input(|i| {
+                i.events.iter().find_map(|event| match event {
+                    egui::Event::Key { key, pressed: true, .. } => Some(*key),
+                    _ => None,
+                })
+            });
+            
+            if let Some(key) = pressed {
+                self.key_bindings.bind(action, key);
+                self.rebinding = None;
+                self.save_settings();
+            }
+        }
+        
+        egui::Grid::new("key_bindings").striped(true).show(ui, |ui| {
+            for action in InputAction::all() {
+                ui.label(self.action_label(action));
+                
+                let text = if self.rebinding == Some(action) {
+                    self.locale.get_message("press-a-key")
+                } else {
+                    self.key_label(action)
+                };
+                
+                if ui.button(text).clicked() {
+                    self.rebinding = Some(action);
+                }
+                ui.end_row();
+            }
+        });
+        
+        if ui.button(self.locale.get_message("reset-bindings")).clicked() {
+            self.key_bindings = KeyBindings::default();
+            self.rebinding = None;
+            self.save_settings();
+        }
+    }
+    
     // 渲染游戏结束画面
     fn render_game_over(&mut self, ctx: &egui::Context) {
         egui::CentralPanel::default().show(ctx, |ui| {
             ui.vertical_centered(|ui| {
                 ui.add_space(100.0);
                 
-                if self.player_won {
+                // 有人类玩家的队伍获胜就算胜利
+                let teams = HexMapFactory::get_teams(&self.ecs_world);
+                let players_won = self.winners.iter()
+                    .any(|&team_id| teams.and_then(|teams| teams.get(team_id)).is_some_and(|team| team.controller != Controller::Ai));
+                
+                if self.winners.is_empty() {
+                    ui.heading(RichText::new(self.locale.get_message("draw-title")).size(50.0).color(Color32::LIGHT_GRAY));
+                    ui.add_space(20.0);
+                    ui.label(self.locale.get_message("draw-message"));
+                } else if players_won {
                     ui.heading(RichText::new(self.locale.get_message("victory-title")).size(50.0).color(Color32::GOLD));
                     ui.add_space(20.0);
                     ui.label(self.locale.get_message("victory-message"));
@@ -153,6 +797,16 @@ impl MyApp {
                     ui.label(self.locale.get_message("defeat-message"));
                 }
                 
+                // 获胜队伍
+                if let Some(teams) = teams {
+                    ui.horizontal_wrapped(|ui| {
+                        ui.label(self.locale.get_message("winners"));
+                        for &team_id in &self.winners {
+                            ui.label(RichText::new(teams.name(team_id)).color(teams.color(team_id)).strong());
+                        }
+                    });
+                }
+                
                 ui.add_space(30.0);
                 
                 if ui.button(self.locale.get_message("return-to-menu")).clicked() {
@@ -160,7 +814,7 @@ impl MyApp {
                 }
                 
                 if ui.button(self.locale.get_message("restart")).clicked() {
-                    self.initialize_game();
+                    self.restart_game();
                 }
             });
         });
@@ -168,6 +822,41 @@ impl MyApp {
     
     // 显示帮助面板
     fn render_help_window(&mut self, ctx: &egui::Context) {
+        let key_lines: Vec<String> = InputAction::all()
+            .into_iter()
+            .map(|action| format!("• {} - {}", self.key_label(action), self.action_label(action)))
+            .collect();
+        
+        // 兵种说明来自单位定义文件，按文件中的顺序列出基础属性
+        let unit_lines: Vec<String> = self.game_data.iter()
+            .flat_map(|data| data.units.units.iter())
+            .map(|unit| {
+                self.locale.get_message_args("help-unit-line", &[
+                    ("name", &self.unit_name(&unit.id)),
+                    ("class", &self.class_name(&unit.class)),
+                    ("health", &unit.max_health.to_string()),
+                    ("attack", &unit.attack.to_string()),
+                    ("defense", &unit.defense.to_string()),
+                    ("movement", &unit.movement.to_string()),
+                    ("range", &unit.range.to_string()),
+                ])
+            })
+            .collect();
+        
+        // 地形说明来自地形定义文件，列出与默认消耗不同的兵种
+        let terrain_lines: Vec<String> = self.game_data.iter()
+            .flat_map(|data| data.terrain.terrain.iter())
+            .map(|terrain| {
+                let mut classes: Vec<&String> = terrain.movement_costs.keys().collect();
+                classes.sort();
+                let mut cost = self.movement_cost_label(terrain.movement_cost.points());
+                for class in classes {
+                    cost += &format!(", {}: {}", self.class_name(class), self.movement_cost_label(terrain.movement_cost_for(class)));
+                }
+                self.locale.get_message_args("help-terrain-line", &[("name", &self.terrain_name(terrain)), ("cost", &cost)])
+            })
+            .collect();
+        
         egui::Window::new(self.locale.get_message("help-title"))
             .open(&mut self.show_help)
             .show(ctx, |ui| {
@@ -175,18 +864,36 @@ impl MyApp {
                 ui.label(self.locale.get_message("help-click-unit"));
                 ui.label(self.locale.get_message("help-green-tiles"));
                 ui.label(self.locale.get_message("help-red-tiles"));
+                ui.label(self.locale.get_message("help-zoc-tiles"));
+                ui.label(self.locale.get_message("help-objectives"));
+                ui.label(self.locale.get_message("help-settlements"));
+                ui.label(self.locale.get_message("help-recruit"));
+                ui.label(self.locale.get_message("help-veterancy"));
+                ui.label(self.locale.get_message("help-resting"));
+                ui.label(self.locale.get_message("help-morale"));
+                ui.label(self.locale.get_message("help-abilities"));
+                ui.label(self.locale.get_message("help-statuses"));
+                if HexMapFactory::get_game_rules(&self.ecs_world).supply {
+                    ui.label(self.locale.get_message("help-supply"));
+                }
                 ui.label(self.locale.get_message("help-end-turn"));
+                ui.label(self.locale.get_message("help-camera-pan"));
+                ui.label(self.locale.get_message("help-camera-zoom"));
+                ui.add_space(10.0);
+                ui.label(self.locale.get_message("help-keyboard-header"));
+                for line in &key_lines {
+                    ui.label(line);
+                }
                 ui.add_space(10.0);
                 ui.label(self.locale.get_message("help-unit-types-header"));
-                ui.label(self.locale.get_message("help-infantry"));
-                ui.label(self.locale.get_message("help-archer"));
-                ui.label(self.locale.get_message("help-cavalry"));
+                for line in &unit_lines {
+                    ui.label(line);
+                }
                 ui.add_space(10.0);
                 ui.label(self.locale.get_message("help-terrain-header"));
-                ui.label(self.locale.get_message("help-plains"));
-                ui.label(self.locale.get_message("help-forest"));
-                ui.label(self.locale.get_message("help-mountain"));
-                ui.label(self.locale.get_message("help-water"));
+                for line in &terrain_lines {
+                    ui.label(line);
+                }
             });
     }
     
@@ -202,27 +909,41 @@ impl MyApp {
                                     .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
                                     .show(ctx, |ui| {
                                         // 单位类型和所属方
-                                        let unit_type_name = match unit_stats.unit_type {
-                                            UnitType::Infantry => self.locale.get_message("unit-type-infantry"),
-                                            UnitType::Archer => self.locale.get_message("unit-type-archer"),
-                                            UnitType::Cavalry => self.locale.get_message("unit-type-cavalry"),
-                                        };
+                                        let unit_type_name = self.unit_name(&unit_stats.unit_type);
                                         
-                                        let team_name = if team.team_id == 0 { 
-                                            self.locale.get_message("team-player")
-                                        } else { 
-                                            self.locale.get_message("team-enemy")
+                                        let (team_name, team_color) = match HexMapFactory::get_teams(&self.ecs_world) {
+                                            Some(teams) => (teams.name(team.team_id), teams.color(team.team_id)),
+                                            None => (self.default_team_name(team.team_id as usize), Color32::GRAY),
                                         };
-                                        let team_color = if team.team_id == 0 { Color32::BLUE } else { Color32::RED };
                                         
                                         ui.horizontal(|ui| {
                                             ui.heading(unit_type_name);
                                             ui.label(RichText::new(team_name).color(team_color));
                                         });
+                                        if self.ecs_world.get_component::<Leader>(hover_entity).is_some() {
+                                            ui.label(RichText::new(self.locale.get_message("leader")).color(Color32::GOLD));
+                                        }
+                                        
+                                        // 军衔、经验和已选的晋升
+                                        if let Some(veterancy) = self.ecs_world.get_component::<Veterancy>(hover_entity) {
+                                            ui.horizontal(|ui| {
+                                                let chevrons = "^".repeat(veterancy.rank as usize);
+                                                ui.label(RichText::new(format!("{} {}", self.locale.get_message_args("rank", &[("rank", &veterancy.rank.to_string())]), chevrons)).color(Color32::GOLD));
+                                                match veterancy.next_rank_xp() {
+                                                    Some(needed) => ui.label(self.locale.get_message_args("xp-next", &[("xp", &veterancy.xp.to_string()), ("needed", &needed.to_string())])),
+                                                    None => ui.label(self.locale.get_message_args("xp-max", &[("xp", &veterancy.xp.to_string())])),
+                                                };
+                                            });
+                                            if !veterancy.promotions.is_empty() {
+                                                let names: Vec<String> = veterancy.promotions.iter().map(|promotion| self.locale.get_message(promotion.name_key())).collect();
+                                                ui.label(format!("{} {}", self.locale.get_message("promotions"), names.join(", ")));
+                                            }
+                                        }
                                         
                                         ui.separator();
                                         
-                                        // 单位属性
+                                        // 单位属性，已计入状态效果
+                                        let effective = StatusSystem::effective_stats(&self.ecs_world, hover_entity).unwrap_or_else(|| unit_stats.clone());
                                         ui.horizontal(|ui| {
                                             ui.label(self.locale.get_message("health"));
                                             let health_percent = unit_state.health as f32 / unit_stats.max_health as f32;
@@ -239,24 +960,87 @@ impl MyApp {
                                         
                                         ui.horizontal(|ui| {
                                             ui.label(self.locale.get_message("attack"));
-                                            ui.label(format!("{}", unit_stats.attack));
+                                            ui.label(Self::stat_text(effective.attack.to_string(), effective.attack, unit_stats.attack));
                                         });
                                         
                                         ui.horizontal(|ui| {
                                             ui.label(self.locale.get_message("defense"));
-                                            ui.label(format!("{}", unit_stats.defense));
+                                            ui.label(Self::stat_text(effective.defense.to_string(), effective.defense, unit_stats.defense));
                                         });
                                         
                                         ui.horizontal(|ui| {
                                             ui.label(self.locale.get_message("attack-range"));
-                                            ui.label(format!("{}", unit_stats.range));
+                                            ui.label(Self::stat_text(effective.range.to_string(), effective.range, unit_stats.range));
+                                        });
+                                        
+                                        ui.horizontal(|ui| {
+                                            ui.label(self.locale.get_message("sight"));
+                                            ui.label(format!("{}", unit_stats.sight));
                                         });
                                         
                                         ui.horizontal(|ui| {
                                             ui.label(self.locale.get_message("movement-left"));
-                                            ui.label(format!("{}/{}", unit_state.movement_left, unit_stats.movement));
+                                            let movement = format!("{}/{}", unit_state.movement_left.min(effective.movement), effective.movement);
+                                            ui.label(Self::stat_text(movement, effective.movement, unit_stats.movement));
+                                        });
+                                        
+                                        // 士气，归零时单位溃逃
+                                        ui.horizontal(|ui| {
+                                            ui.label(self.locale.get_message("morale"));
+                                            ui.label(format!("{}/{}", unit_state.morale, MAX_MORALE));
+                                            if unit_state.routing() {
+                                                ui.label(RichText::new(self.locale.get_message("unit-routing")).color(Color32::RED));
+                                            }
                                         });
                                         
+                                        // 补给规则下远程单位的弹药
+                                        if HexMapFactory::get_game_rules(&self.ecs_world).supply && unit_stats.max_ammo > 0 {
+                                            ui.horizontal(|ui| {
+                                                ui.label(self.locale.get_message("ammo"));
+                                                let ammo_color = if unit_state.ammo > 0 { Color32::WHITE } else { Color32::RED };
+                                                ui.label(RichText::new(format!("{}/{}", unit_state.ammo, unit_stats.max_ammo)).color(ammo_color));
+                                            });
+                                        }
+                                        
+                                        // 技能及冷却，防守状态
+                                        let abilities = AbilitySystem::abilities(&self.ecs_world, hover_entity);
+                                        if !abilities.is_empty() {
+                                            let names: Vec<String> = abilities.iter().map(|ability| {
+                                                let name = self.locale.get_message(&ability.name);
+                                                let cooldown = AbilitySystem::cooldown(&self.ecs_world, hover_entity, &ability.id);
+                                                if ability.effect.is_passive() {
+                                                    self.locale.get_message_args("ability-passive", &[("name", &name)])
+                                                } else if cooldown > 0 {
+                                                    self.locale.get_message_args("ability-cooldown-left", &[("name", &name), ("turns", &cooldown.to_string())])
+                                                } else {
+                                                    name
+                                                }
+                                            }).collect();
+                                            ui.label(format!("{} {}", self.locale.get_message("abilities"), names.join(", ")));
+                                        }
+                                        let guard = AbilitySystem::defense_bonus(&self.ecs_world, hover_entity);
+                                        if guard > 0 {
+                                            ui.label(RichText::new(self.locale.get_message_args("guard-up", &[("defense", &guard.to_string())])).color(Color32::LIGHT_BLUE));
+                                        }
+                                        
+                                        // 状态效果及剩余回合，悬停显示说明
+                                        let effects = StatusSystem::effects(&self.ecs_world, hover_entity);
+                                        if !effects.is_empty() {
+                                            ui.label(self.locale.get_message("statuses"));
+                                            for (definition, effect) in effects {
+                                                let mut text = self.locale.get_message_args("status-line", &[
+                                                    ("symbol", &definition.symbol),
+                                                    ("name", &self.locale.get_message(&definition.name)),
+                                                    ("turns", &effect.turns_left.to_string()),
+                                                ]);
+                                                if effect.stacks > 1 {
+                                                    text = format!("{} ×{}", text, effect.stacks);
+                                                }
+                                                ui.label(RichText::new(text).color(definition.color()))
+                                                    .on_hover_text(self.locale.get_message(&definition.description));
+                                            }
+                                        }
+                                        
                                         // 单位状态
                                         ui.separator();
                                         if unit_state.has_acted {
@@ -264,21 +1048,20 @@ impl MyApp {
                                         } else {
                                             ui.label(RichText::new(self.locale.get_message("unit-can-act")).color(Color32::GREEN));
                                         }
+                                        if unit_state.rested && unit_state.health < unit_stats.max_health {
+                                            ui.label(RichText::new(self.locale.get_message("unit-resting")).color(Color32::LIGHT_GREEN));
+                                        }
                                         
                                         // 获取地形信息
-                                        if let Some(terrain) = self.ecs_world.get_component::<Terrain>(hover_entity) {
-                                            let terrain_type_key = match terrain.terrain_type {
-                                                TerrainType::Plain => "help-plains",
-                                                TerrainType::Forest => "help-forest",
-                                                TerrainType::Mountain => "help-mountain",
-                                                TerrainType::Water => "help-water",
-                                            };
-                                            
+                                        let hover_coord = self.ecs_world.get_component::<Position>(hover_entity).map(|position| position.coord);
+                                        if let Some(terrain) = hover_coord.and_then(|coord| HexMapFactory::terrain_at(&self.ecs_world, coord)) {
                                             ui.separator();
-                                            ui.label(format!("{} {}", self.locale.get_message("terrain"), 
-                                                       terrain_type_key.trim_start_matches("help-").trim_start_matches("• ")));
-                                            ui.label(format!("{} {}", self.locale.get_message("movement-cost"), 
-                                                       terrain.terrain_type.movement_cost()));
+                                            ui.label(format!("{} {}", self.locale.get_message("terrain"), self.terrain_name(terrain)));
+                                            let cost = self.movement_cost_label(terrain.movement_cost_for(&unit_stats.class));
+                                            ui.label(format!("{} {}", self.locale.get_message("movement-cost"), cost));
+                                            if terrain.defense_bonus != 0 {
+                                                ui.label(format!("{} {:+}", self.locale.get_message("defense-bonus"), terrain.defense_bonus));
+                                            }
                                         }
                                     });
                             }
@@ -289,20 +1072,285 @@ impl MyApp {
         }
     }
     
+    // 显示鼠标所指定居点的信息
+    fn render_settlement_info(&self, ctx: &egui::Context) {
+        let hover_entity = HexMapFactory::get_game_state_entity(&self.ecs_world)
+            .and_then(|entity| self.ecs_world.get_component::<GameState>(entity))
+            .and_then(|game_state| game_state.hover_entity);
+        let settlement = match hover_entity.and_then(|entity| self.ecs_world.get_component::<Settlement>(entity)) {
+            Some(settlement) => settlement,
+            None => return,
+        };
+        
+        egui::Window::new(self.locale.get_message("settlement-info-title"))
+            .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+            .show(ctx, |ui| {
+                ui.heading(self.locale.get_message(settlement.kind.name_key()));
+                
+                // 所属方，无人占领时显示“中立”
+                ui.horizontal(|ui| {
+                    ui.label(self.locale.get_message("settlement-owner"));
+                    match (settlement.owner, HexMapFactory::get_teams(&self.ecs_world)) {
+                        (Some(owner), Some(teams)) => ui.label(RichText::new(teams.name(owner)).color(teams.color(owner))),
+                        (Some(owner), None) => ui.label(self.default_team_name(owner as usize)),
+                        (None, _) => ui.label(self.locale.get_message("settlement-neutral")),
+                    };
+                });
+                
+                ui.label(format!("{} {}", self.locale.get_message("settlement-income"), settlement.income));
+                ui.label(format!("{} {}", self.locale.get_message("settlement-healing"), settlement.healing));
+            });
+    }
+    
+    // 当前队伍有单位升级时让玩家选择晋升
+    fn render_promotion_panel(&mut self, ctx: &egui::Context) {
+        let current_turn = match HexMapFactory::get_game_state_entity(&self.ecs_world).and_then(|entity| self.ecs_world.get_component::<GameState>(entity)) {
+            Some(game_state) if !game_state.game_over => game_state.current_turn,
+            _ => return,
+        };
+        let entity = match VeterancySystem::awaiting_promotion(&self.ecs_world, current_turn).first() {
+            Some(&entity) => entity,
+            None => return,
+        };
+        let (unit_type, rank) = match (self.ecs_world.get_component::<UnitStats>(entity), self.ecs_world.get_component::<Veterancy>(entity)) {
+            (Some(stats), Some(veterancy)) => (stats.unit_type.clone(), veterancy.rank),
+            _ => return,
+        };
+        let coord = self.ecs_world.get_component::<Position>(entity).map(|position| position.coord);
+        
+        let mut choice = None;
+        let mut show = false;
+        egui::Window::new(self.locale.get_message("promotion-title"))
+            .anchor(egui::Align2::CENTER_TOP, [0.0, 60.0])
+            .resizable(false)
+            .collapsible(false)
+            .show(ctx, |ui| {
+                ui.label(self.locale.get_message_args("promotion-message", &[
+                    ("unit", &self.unit_name(&unit_type)),
+                    ("rank", &rank.to_string()),
+                ]));
+                ui.horizontal(|ui| {
+                    for promotion in VeterancySystem::options(&self.ecs_world, entity) {
+                        if ui.button(self.locale.get_message(promotion.name_key())).clicked() {
+                            choice = Some(promotion);
+                        }
+                    }
+                });
+                show = ui.button(self.locale.get_message("promotion-show")).clicked();
+            });
+        
+        if let Some(promotion) = choice {
+            VeterancySystem::promote(&mut self.ecs_world, entity, promotion);
+        }
+        if let Some(coord) = coord.filter(|_| show) {
+            CameraSystem::centre_on(&mut self.ecs_world, coord);
+        }
+    }
+    
+    // 选中单位的技能栏，返回是否显示了
+    fn render_ability_bar(&mut self, ctx: &egui::Context) -> bool {
+        let (selected, armed) = match HexMapFactory::get_game_state_entity(&self.ecs_world).and_then(|entity| self.ecs_world.get_component::<GameState>(entity)) {
+            Some(game_state) if !game_state.game_over => (game_state.selected_entity, game_state.armed_ability.clone()),
+            _ => return false,
+        };
+        let selected = match selected {
+            Some(selected) => selected,
+            None => return false,
+        };
+        let abilities: Vec<AbilityDefinition> = AbilitySystem::active(&self.ecs_world, selected).into_iter().cloned().collect();
+        if abilities.is_empty() {
+            return false;
+        }
+        
+        let slots = [InputAction::Ability1, InputAction::Ability2, InputAction::Ability3, InputAction::Ability4];
+        let mut choice = None;
+        egui::Window::new(self.locale.get_message("abilities-title"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, -10.0])
+            .resizable(false)
+            .collapsible(false)
+            .title_bar(false)
+            .show(ctx, |ui| {
+                ui.horizontal(|ui| {
+                    for (index, ability) in abilities.iter().enumerate() {
+                        // 按钮上显示快捷键，冷却中显示剩余回合
+                        let name = self.locale.get_message(&ability.name);
+                        let cooldown = AbilitySystem::cooldown(&self.ecs_world, selected, &ability.id);
+                        let mut text = if cooldown > 0 {
+                            self.locale.get_message_args("ability-cooldown-left", &[("name", &name), ("turns", &cooldown.to_string())])
+                        } else {
+                            name
+                        };
+                        if let Some(&action) = slots.get(index) {
+                            text = format!("[{}] {}", self.key_label(action), text);
+                        }
+                        
+                        let details = format!("{}\n{}", self.locale.get_message(&ability.description), self.locale.get_message_args("ability-details", &[
+                            ("range", &ability.range.to_string()),
+                            ("area", &ability.area.to_string()),
+                            ("cooldown", &ability.cooldown.to_string()),
+                        ]));
+                        let blocker = AbilitySystem::blocker(&self.ecs_world, selected, ability);
+                        let armed = armed.as_deref() == Some(ability.id.as_str());
+                        let button = ui.add_enabled(blocker.is_none(), egui::SelectableLabel::new(armed, text));
+                        if let Some(blocker) = blocker {
+                            button.on_disabled_hover_text(format!("{}\n{}", details, self.locale.get_message(blocker)));
+                        } else if button.on_hover_text(details).clicked() {
+                            choice = Some(ability.id.clone());
+                        }
+                    }
+                });
+            });
+        
+        if let Some(id) = choice {
+            InputSystem::arm_ability(&mut self.ecs_world, &id);
+        }
+        true
+    }
+    
+    // 光标停在己方城市上时显示招募面板，技能栏显示时放在它上面
+    fn render_recruit_panel(&mut self, ctx: &egui::Context, above_action_bar: bool) {
+        let (current_turn, cursor) = match HexMapFactory::get_game_state_entity(&self.ecs_world).and_then(|entity| self.ecs_world.get_component::<GameState>(entity)) {
+            Some(game_state) if !game_state.game_over => (game_state.current_turn, game_state.cursor),
+            _ => return,
+        };
+        let city = match cursor.filter(|coord| EconomySystem::cities(&self.ecs_world, current_turn).contains(coord)) {
+            Some(city) => city,
+            None => return,
+        };
+        
+        let mut order = None;
+        egui::Window::new(self.locale.get_message("recruit-title"))
+            .anchor(egui::Align2::CENTER_BOTTOM, [0.0, if above_action_bar { -60.0 } else { -10.0 }])
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(RichText::new(self.locale.get_message_args("gold", &[("gold", &EconomySystem::gold(&self.ecs_world, current_turn).to_string())])).color(Color32::GOLD));
+                ui.separator();
+                
+                egui::Grid::new("recruit_grid").striped(true).show(ui, |ui| {
+                    for definition in EconomySystem::recruitable(&self.ecs_world, current_turn) {
+                        ui.label(self.unit_name(&definition.id));
+                        ui.label(self.locale.get_message_args("unit-cost", &[("cost", &definition.cost.to_string())]));
+                        ui.label(self.locale.get_message_args("unit-upkeep", &[("upkeep", &definition.upkeep.to_string())]));
+                        
+                        // 不能招募时在提示里说明原因
+                        let blocker = EconomySystem::recruit_blocker(&self.ecs_world, current_turn, city, definition);
+                        let button = ui.add_enabled(blocker.is_none(), egui::Button::new(self.locale.get_message("recruit")));
+                        if let Some(blocker) = blocker {
+                            button.on_disabled_hover_text(self.locale.get_message(blocker));
+                        } else if button.clicked() {
+                            order = Some(definition.id.clone());
+                        }
+                        ui.end_row();
+                    }
+                });
+            });
+        
+        if let Some(unit_type) = order {
+            EconomySystem::recruit(&mut self.ecs_world, current_turn, city, &unit_type);
+        }
+    }
+    
+    // 棋盘上的文字：当前回合的队伍，游戏结束时的结果
+    fn render_board_overlay(&self, ui: &egui::Ui) {
+        let game_state = match HexMapFactory::get_game_state_entity(&self.ecs_world).and_then(|entity| self.ecs_world.get_component::<GameState>(entity)) {
+            Some(game_state) => game_state,
+            None => return,
+        };
+        let team_name = |team_id: u8| HexMapFactory::get_teams(&self.ecs_world).map_or_else(|| team_id.to_string(), |teams| teams.name(team_id));
+        let rect = ui.max_rect();
+        
+        // 游戏结束：变暗的棋盘上写出获胜方，没有获胜方时是平局
+        if game_state.game_over {
+            let text = if game_state.winners.is_empty() {
+                self.locale.get_message("draw-title")
+            } else {
+                let names: Vec<String> = game_state.winners.iter().map(|&team_id| team_name(team_id)).collect();
+                self.locale.get_message_args("teams-won", &[("teams", &names.join(", "))])
+            };
+            
+            ui.painter().rect_filled(rect, 0.0, Color32::from_rgba_premultiplied(0, 0, 0, 150));
+            ui.painter().text(
+                rect.center(),
+                egui::Align2::CENTER_CENTER,
+                text,
+                egui::FontId::proportional(32.0),
+                game_state.winners.first().map_or(Color32::WHITE, |&team_id| HexMapFactory::team_color(&self.ecs_world, team_id)),
+            );
+        }
+        
+        // 当前回合
+        let current_turn = game_state.current_turn;
+        ui.painter().text(
+            Pos2::new(rect.right() - 100.0, rect.top() + 20.0),
+            egui::Align2::RIGHT_TOP,
+            self.locale.get_message_args("team-turn", &[("team", &team_name(current_turn))]),
+            egui::FontId::proportional(16.0),
+            HexMapFactory::team_color(&self.ecs_world, current_turn),
+        );
+    }
+    
     // 渲染游戏状态栏
     fn render_game_status(&self, ui: &mut egui::Ui) {
         if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(&self.ecs_world) {
             if let Some(game_state) = self.ecs_world.get_component::<GameState>(game_state_entity) {
                 // 当前回合信息
                 ui.horizontal(|ui| {
-                    let current_turn_text = if game_state.current_turn == 0 { 
-                        RichText::new(self.locale.get_message("player-turn")).color(Color32::BLUE)
-                    } else { 
-                        RichText::new(self.locale.get_message("enemy-turn")).color(Color32::RED)
+                    let (team_name, team_color, controller) = match HexMapFactory::get_teams(&self.ecs_world).and_then(|teams| teams.get(game_state.current_turn)) {
+                        Some(team) => (team.name.clone(), team.color, team.controller),
+                        None => (self.default_team_name(game_state.current_turn as usize), Color32::GRAY, Controller::Human),
                     };
+                    let current_turn_text = RichText::new(self.locale.get_message_args("team-turn", &[("team", &team_name)])).color(team_color);
                     
                     ui.label(current_turn_text);
-                    ui.label(self.locale.get_message_args("turn-number", &[("number", &game_state.turn_number.to_string())]));
+                    if controller != Controller::Human {
+                        ui.label(RichText::new(self.locale.get_message(controller.name_key())).italics());
+                    }
+                    let rules = HexMapFactory::get_game_rules(&self.ecs_world);
+                    match rules.turn_limit {
+                        Some(limit) => ui.label(self.locale.get_message_args("turn-number-limit", &[
+                            ("number", &game_state.turn_number.to_string()),
+                            ("limit", &limit.to_string()),
+                        ])),
+                        None => ui.label(self.locale.get_message_args("turn-number", &[("number", &game_state.turn_number.to_string())])),
+                    };
+                    
+                    // 当前队伍的金币和每回合净收入
+                    let income = EconomySystem::income(&self.ecs_world, game_state.current_turn);
+                    let upkeep = EconomySystem::upkeep(&self.ecs_world, game_state.current_turn);
+                    ui.label(RichText::new(self.locale.get_message_args("treasury", &[
+                        ("gold", &EconomySystem::gold(&self.ecs_world, game_state.current_turn).to_string()),
+                        ("net", &format!("{:+}", income - upkeep)),
+                    ])).color(Color32::GOLD))
+                        .on_hover_text(self.locale.get_message_args("treasury-tooltip", &[
+                            ("income", &income.to_string()),
+                            ("upkeep", &upkeep.to_string()),
+                        ]));
+                });
+                
+                // 胜利条件和各队伍的进度
+                let rules = HexMapFactory::get_game_rules(&self.ecs_world);
+                let teams = HexMapFactory::get_teams(&self.ecs_world);
+                let team_name = |team_id: u8| teams.map_or_else(|| self.default_team_name(team_id as usize), |teams| teams.name(team_id));
+                let team_color = |team_id: u8| teams.map_or(Color32::GRAY, |teams| teams.color(team_id));
+                ui.horizontal_wrapped(|ui| {
+                    for condition in &rules.victory {
+                        ui.label(RichText::new(self.victory_text(condition, &team_name, rules.turn_limit)).strong());
+                        
+                        if let VictoryCondition::SurviveUntil { turn, .. } = *condition {
+                            let left = (turn - game_state.turn_number + 1).max(0);
+                            ui.label(self.locale.get_message_args("turns-left", &[("count", &left.to_string())]));
+                        }
+                        
+                        for (team_id, value) in VictorySystem::progress(&self.ecs_world, condition) {
+                            let text = match *condition {
+                                VictoryCondition::HoldObjectives { turns } if value > 0 => format!("{} {}/{}", team_name(team_id), value, turns),
+                                VictoryCondition::HoldObjectives { .. } => continue,
+                                VictoryCondition::HighestScore => format!("{} {}", team_name(team_id), value),
+                                _ => team_name(team_id),
+                            };
+                            ui.label(RichText::new(text).color(team_color(team_id)));
+                        }
+                    }
                 });
                 
                 ui.separator();
@@ -310,6 +1358,16 @@ impl MyApp {
         }
     }
     
+    // 渲染小地图
+    fn render_minimap(&mut self, ctx: &egui::Context) {
+        egui::Window::new(self.locale.get_message("minimap-title"))
+            .anchor(egui::Align2::LEFT_TOP, [10.0, 60.0])
+            .resizable(false)
+            .show(ctx, |ui| {
+                MinimapSystem::show(&mut self.ecs_world, ui, egui::vec2(200.0, 150.0));
+            });
+    }
+    
     // 渲染调试信息
     fn render_debug_info(&self, ctx: &egui::Context) {
         if self.show_debug {
@@ -338,16 +1396,83 @@ impl MyApp {
                         }
                     }
                     
-                    // 实体计数
-                    let player_count = self.count_units(0);
-                    let enemy_count = self.count_units(1);
+                    // 各队伍单位数
                     ui.separator();
-                    ui.label(format!("玩家单位数: {}", player_count));
-                    ui.label(format!("敌人单位数: {}", enemy_count));
+                    if let Some(teams) = HexMapFactory::get_teams(&self.ecs_world) {
+                        for team in &teams.teams {
+                            ui.label(format!("队伍 {} 单位数: {}", team.id, self.count_units(team.id)));
+                        }
+                    }
                 });
         }
     }
     
+    // 当前回合方还能行动的单位数量
+    fn count_idle_units(&self) -> usize {
+        HexMapFactory::get_game_state_entity(&self.ecs_world)
+            .and_then(|entity| self.ecs_world.get_component::<GameState>(entity))
+            .map(|game_state| HexMapFactory::idle_units(&self.ecs_world, game_state.current_turn).len())
+            .unwrap_or(0)
+    }
+    
+    // 处理结束回合请求，还有单位未行动时先确认
+    fn handle_end_turn_request(&mut self) {
+        let requested = HexMapFactory::get_game_state_entity(&self.ecs_world)
+            .and_then(|entity| self.ecs_world.get_component_mut::<GameState>(entity))
+            .map(|game_state| std::mem::take(&mut game_state.end_turn_requested))
+            .unwrap_or(false);
+        
+        if !requested {
+            return;
+        }
+        
+        // 确认框打开时再次请求即视为确认
+        if self.confirming_end_turn || !self.confirm_end_turn || self.count_idle_units() == 0 {
+            self.confirming_end_turn = false;
+            InputSystem::end_turn(&mut self.ecs_world);
+        } else {
+            self.confirming_end_turn = true;
+        }
+    }
+    
+    // 显示结束回合确认框
+    fn render_end_turn_confirmation(&mut self, ctx: &egui::Context) {
+        // 取消键关闭确认框
+        if ctx.input(|i| self.key_bindings.pressed(i, InputAction::Deselect)) {
+            self.confirming_end_turn = false;
+            return;
+        }
+        
+        let idle = self.count_idle_units().to_string();
+        let mut end_turn = false;
+        let mut cancel = false;
+        
+        egui::Window::new(self.locale.get_message("confirm-end-turn-title"))
+            .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+            .collapsible(false)
+            .resizable(false)
+            .show(ctx, |ui| {
+                ui.label(self.locale.get_message_args("confirm-end-turn-message", &[("count", &idle)]));
+                ui.add_space(10.0);
+                
+                ui.horizontal(|ui| {
+                    end_turn = ui.button(self.locale.get_message("end-turn-anyway")).clicked();
+                    cancel = ui.button(self.locale.get_message("cancel")).clicked();
+                });
+                
+                if ui.checkbox(&mut self.confirm_end_turn, self.locale.get_message("confirm-end-turn-setting")).changed() {
+                    self.save_settings();
+                }
+            });
+        
+        if end_turn {
+            self.confirming_end_turn = false;
+            InputSystem::end_turn(&mut self.ecs_world);
+        } else if cancel {
+            self.confirming_end_turn = false;
+        }
+    }
+    
     // 计算特定队伍单位数量
     fn count_units(&self, team_id: u8) -> usize {
         let mut count = 0;
@@ -370,7 +1495,7 @@ impl MyApp {
         if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(&self.ecs_world) {
             if let Some(game_state) = self.ecs_world.get_component::<GameState>(game_state_entity) {
                 if game_state.game_over {
-                    self.player_won = game_state.player_won;
+                    self.winners = game_state.winners.clone();
                     self.game_screen = GameScreen::GameOver;
                 }
             }
@@ -388,7 +1513,17 @@ impl App for MyApp {
             GameScreen::Settings => {
                 self.render_settings(ctx);
             },
+            GameScreen::Scenarios => {
+                self.render_scenarios(ctx);
+            },
             GameScreen::Playing => {
+                // 键盘操作归棋盘所有：不让按钮保留焦点，以免空格/回车/Tab 同时触发按钮
+                ctx.memory_mut(|memory| {
+                    if let Some(id) = memory.focus() {
+                        memory.surrender_focus(id);
+                    }
+                });
+                
                 // Set up the central panel for the game
                 egui::CentralPanel::default().show(ctx, |ui| {
                     // 顶部状态栏
@@ -405,6 +1540,14 @@ impl App for MyApp {
                             if ui.button(self.locale.get_message("menu")).clicked() {
                                 self.game_screen = GameScreen::MainMenu;
                             }
+                            
+                            if ui.button(self.locale.get_message("save-game")).clicked() {
+                                self.save_game();
+                            }
+                            
+                            if let Some(message) = &self.save_message {
+                                ui.label(message);
+                            }
                         });
                     });
                     
@@ -413,18 +1556,59 @@ impl App for MyApp {
                     // Game area
                     let available_size = ui.available_size();
                     let (response, _painter) = ui.allocate_painter(available_size, egui::Sense::click_and_drag());
-
-                    // Process input first
-                    InputSystem::update(&mut self.ecs_world, ui, &response);
+                    
+                    // Pan and zoom before hit-testing so the hovered hex matches what's drawn
+                    CameraSystem::update(&mut self.ecs_world, ui, &response, &self.key_bindings);
+                    
+                    if self.centre_clicked {
+                        self.centre_clicked = false;
+                        CameraSystem::centre_on_selected(&mut self.ecs_world);
+                    }
+                    
+                    // Process input first, unless the computer is playing this turn
+                    let ai_turn = AiSystem::is_ai_turn(&self.ecs_world);
+                    if !ai_turn {
+                        InputSystem::update(&mut self.ecs_world, ui, &response, &self.key_bindings);
+                    }
                     
                     // Handle end turn button
                     if self.end_turn_clicked {
                         self.end_turn_clicked = false;
-                        InputSystem::end_turn(&mut self.ecs_world);
+                        InputSystem::request_end_turn(&mut self.ecs_world);
+                    }
+                    
+                    self.handle_end_turn_request();
+                    
+                    // 调试用坐标标签
+                    if let Some(map_settings_entity) = HexMapFactory::get_map_settings_entity(&self.ecs_world) {
+                        if let Some(settings) = self.ecs_world.get_component_mut::<MapSettings>(map_settings_entity) {
+                            settings.show_coord_labels = self.show_coord_labels;
+                        }
+                    }
+                    
+                    // 推进动画，动画只影响显示，不阻塞游戏逻辑
+                    if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(&self.ecs_world) {
+                        if let Some(animations) = self.ecs_world.get_component_mut::<Animations>(game_state_entity) {
+                            animations.speed = self.animation_speed;
+                        }
+                    }
+                    if AnimationSystem::update(&mut self.ecs_world, ctx.input(|i| i.stable_dt)) {
+                        ctx.request_repaint();
+                    }
+                    
+                    // 电脑的回合：等动画播完再让下一个单位行动，全部行动完就结束回合
+                    if ai_turn && !AnimationSystem::is_playing(&self.ecs_world) {
+                        if !AiSystem::step(&mut self.ecs_world) {
+                            InputSystem::end_turn(&mut self.ecs_world);
+                        }
+                        ctx.request_repaint();
                     }
                     
-                    // Render the game
-                    RenderSystem::render(&self.ecs_world, ui);
+                    // Render the game, clipped to the board area
+                    let mut board_ui = ui.child_ui(response.rect, *ui.layout());
+                    board_ui.set_clip_rect(response.rect);
+                    RenderSystem::render(&mut self.ecs_world, &mut board_ui);
+                    self.render_board_overlay(&board_ui);
                     
                     // 检查游戏是否结束
                     self.check_game_over();
@@ -438,32 +1622,63 @@ impl App for MyApp {
                             // 回合信息
                             self.render_game_status(ui);
                             
-                            // 结束回合按钮
-                            if ui.add_sized([120.0, 30.0], egui::Button::new(self.locale.get_message("end-turn"))).clicked() {
+                            // 结束回合按钮，角标显示还未行动的单位数
+                            let end_turn_response = ui.add_enabled_ui(!ai_turn, |ui| {
+                                ui.add_sized([120.0, 30.0], egui::Button::new(self.locale.get_message("end-turn")))
+                            }).inner;
+                            if end_turn_response.clicked() {
                                 self.end_turn_clicked = true;
                             }
                             
+                            let idle = self.count_idle_units();
+                            if idle > 0 && !ai_turn {
+                                let badge = end_turn_response.rect.right_top();
+                                ui.painter().circle_filled(badge, 9.0, Color32::from_rgb(230, 120, 0));
+                                ui.painter().text(badge, egui::Align2::CENTER_CENTER, idle.to_string(), egui::FontId::proportional(11.0), Color32::WHITE);
+                            }
+                            
+                            // 跳过正在播放的动画
+                            if AnimationSystem::is_playing(&self.ecs_world) && ui.button(self.locale.get_message("skip-animations")).clicked() {
+                                AnimationSystem::skip(&mut self.ecs_world);
+                            }
+                            
+                            // 在可行动单位之间切换，AI回合时不可用
+                            ui.add_enabled_ui(!ai_turn, |ui| ui.horizontal(|ui| {
+                                if ui.button(format!("{} ({})", self.locale.get_message("previous-unit"), self.key_label(InputAction::PreviousUnit))).clicked() {
+                                    InputSystem::cycle_units(&mut self.ecs_world, false);
+                                }
+                                if ui.button(format!("{} ({})", self.locale.get_message("next-unit"), self.key_label(InputAction::CycleUnits))).clicked() {
+                                    InputSystem::cycle_units(&mut self.ecs_world, true);
+                                }
+                            }));
+                            
+                            let centre_text = format!("{} ({})", self.locale.get_message("centre-on-unit"), self.key_label(InputAction::CentreOnUnit));
+                            if ui.button(centre_text).clicked() {
+                                self.centre_clicked = true;
+                            }
+                            
                             ui.add_space(20.0);
                             ui.label(self.locale.get_message("click-to-select"));
                             ui.label(self.locale.get_message("help-green-tiles"));
                             ui.label(self.locale.get_message("help-red-tiles"));
+                            if HexMapFactory::get_game_rules(&self.ecs_world).zone_of_control {
+                                ui.label(self.locale.get_message("help-zoc-tiles"));
+                            }
                             
                             ui.separator();
                             
-                            // 玩家和敌人单位数量
-                            let player_count = self.count_units(0);
-                            let enemy_count = self.count_units(1);
-                            
+                            // 各队伍剩余单位数量
                             ui.heading(self.locale.get_message("unit-info-title"));
-                            ui.horizontal(|ui| {
-                                ui.label(self.locale.get_message("player-units"));
-                                ui.label(RichText::new(format!("{}", player_count)).color(Color32::BLUE));
-                            });
-                            
-                            ui.horizontal(|ui| {
-                                ui.label(self.locale.get_message("enemy-units"));
-                                ui.label(RichText::new(format!("{}", enemy_count)).color(Color32::RED));
-                            });
+                            if let Some(teams) = HexMapFactory::get_teams(&self.ecs_world) {
+                                for team in &teams.teams {
+                                    ui.horizontal(|ui| {
+                                        ui.label(RichText::new(&team.name).color(team.color));
+                                        ui.label(RichText::new(format!("{}", self.count_units(team.id))).color(team.color));
+                                        let settlements = SettlementSystem::owned_by(&self.ecs_world, team.id).len();
+                                        ui.label(RichText::new(self.locale.get_message_args("settlements-owned", &[("count", &settlements.to_string())])).color(team.color));
+                                    });
+                                }
+                            }
                             
                             ui.separator();
                             
@@ -476,23 +1691,43 @@ impl App for MyApp {
                             }
                             
                             // 调试开关
-                            ui.checkbox(&mut self.show_debug, &self.locale.get_message("show-debug"));
-                            ui.checkbox(&mut self.show_unit_info, &self.locale.get_message("show-unit-info"));
+                            ui.checkbox(&mut self.show_debug, self.locale.get_message("show-debug"));
+                            ui.checkbox(&mut self.show_coord_labels, self.locale.get_message("show-coord-labels"));
+                            ui.checkbox(&mut self.show_unit_info, self.locale.get_message("show-unit-info"));
+                            ui.checkbox(&mut self.show_minimap, self.locale.get_message("show-minimap"));
                         });
                     });
                 });
                 
+                // 结束回合确认框
+                if self.confirming_end_turn {
+                    self.render_end_turn_confirmation(ctx);
+                }
+                
                 // 渲染帮助窗口
                 if self.show_help {
                     self.render_help_window(ctx);
                 }
                 
+                // 渲染小地图
+                if self.show_minimap {
+                    self.render_minimap(ctx);
+                }
+                
                 // 渲染调试信息
                 self.render_debug_info(ctx);
                 
-                // 渲染单位信息面板
+                // 技能栏、招募和晋升面板，电脑的回合不显示
+                if !AiSystem::is_ai_turn(&self.ecs_world) {
+                    let action_bar = self.render_ability_bar(ctx);
+                    self.render_recruit_panel(ctx, action_bar);
+                    self.render_promotion_panel(ctx);
+                }
+                
+                // 渲染单位和定居点信息面板
                 if self.show_unit_info {
                     self.render_unit_info(ctx);
+                    self.render_settlement_info(ctx);
                 }
             },
             GameScreen::GameOver => {
@@ -512,6 +1747,6 @@ fn main() -> eframe::Result<()> {
     eframe::run_native(
         "OpenVictoria",
         options,
-        Box::new(|_cc| Box::new(MyApp::default()))
+        Box::new(|cc| Box::new(MyApp::new(cc)))
     )
 }
@@ -0,0 +1,356 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::c::*;
+
+// System for unit abilities: what a unit can use, where it can aim them, cooldowns and lasting effects.
+// Abilities are used through InputSystem, the same way moves and attacks are.
+pub struct AbilitySystem;
+
+impl AbilitySystem {
+    // Abilities a unit's type has, in the order its definition lists them
+    pub fn abilities(world: &World, entity: Entity) -> Vec<&AbilityDefinition> {
+        let definition = match world.get_component::<UnitStats>(entity)
+            .and_then(|stats| HexMapFactory::get_unit_catalog(world)?.get(&stats.unit_type))
+        {
+            Some(definition) => definition,
+            None => return Vec::new(),
+        };
+        let catalog = match HexMapFactory::get_ability_catalog(world) {
+            Some(catalog) => catalog,
+            None => return Vec::new(),
+        };
+        
+        definition.abilities.iter().filter_map(|id| catalog.get(id)).collect()
+    }
+    
+    // Abilities a unit uses on command, for the action bar
+    pub fn active(world: &World, entity: Entity) -> Vec<&AbilityDefinition> {
+        Self::abilities(world, entity)
+            .into_iter()
+            .filter(|ability| !ability.effect.is_passive())
+            .collect()
+    }
+    
+    // Own turns before a unit can use an ability again
+    pub fn cooldown(world: &World, entity: Entity, id: &str) -> i32 {
+        world.get_component::<Abilities>(entity).map_or(0, |abilities| abilities.cooldown(id))
+    }
+    
+    // Hexes a unit could aim an ability at from where it stands
+    pub fn targets(world: &World, entity: Entity, ability: &AbilityDefinition) -> Vec<HexCoord> {
+        let (coord, team_id) = match (world.get_component::<Position>(entity), world.get_component::<Team>(entity)) {
+            (Some(position), Some(team)) => (position.coord, team.team_id),
+            _ => return Vec::new(),
+        };
+        
+        if ability.target == AbilityTarget::User {
+            return vec![coord];
+        }
+        
+        let mut targets: Vec<HexCoord> = coord.within(ability.range)
+            .into_iter()
+            .filter_map(|target| HexMapFactory::get_tile_entity(world, &target).map(|tile| (target, tile)))
+            .filter(|&(target, _)| HexMapFactory::line_of_sight(world, coord, target))
+            .filter(|&(target, tile)| match ability.target {
+                AbilityTarget::Ally => Self::unit_team(world, tile).is_some_and(|team| !HexMapFactory::are_enemies(world, team, team_id)),
+                AbilityTarget::Enemy => Self::unit_team(world, tile).is_some_and(|team| HexMapFactory::are_enemies(world, team, team_id)),
+                AbilityTarget::Hex => target != coord,
+                AbilityTarget::User => false,
+            })
+            .map(|(target, _)| target)
+            .collect();
+        targets.sort_by_key(|target| (target.q, target.r));
+        targets
+    }
+    
+    // Units an ability used by a team at a hex takes effect on, in map order
+    pub fn affected(world: &World, team_id: u8, ability: &AbilityDefinition, target: HexCoord) -> Vec<Entity> {
+        let mut affected: Vec<(HexCoord, Entity)> = target.within(ability.area)
+            .into_iter()
+            .filter_map(|coord| HexMapFactory::get_tile_entity(world, &coord).map(|tile| (coord, tile)))
+            .filter(|&(_, tile)| match (ability.effect, Self::unit_team(world, tile)) {
+                (AbilityEffect::Damage { .. }, Some(team)) => HexMapFactory::are_enemies(world, team, team_id),
+                (AbilityEffect::Heal { .. }, Some(team)) => !HexMapFactory::are_enemies(world, team, team_id),
+                (AbilityEffect::Guard { .. }, Some(team)) => team == team_id,
+                _ => false,
+            })
+            .collect();
+        affected.sort_by_key(|(coord, _)| (coord.q, coord.r));
+        affected.into_iter().map(|(_, tile)| tile).collect()
+    }
+    
+    // Why a unit can't use an ability right now, as a localization key, or None if it can
+    pub fn blocker(world: &World, entity: Entity, ability: &AbilityDefinition) -> Option<&'static str> {
+        let (stats, state) = match (world.get_component::<UnitStats>(entity), world.get_component::<UnitState>(entity)) {
+            (Some(stats), Some(state)) => (stats, state),
+            _ => return Some("ability-acted"),
+        };
+        let supply = HexMapFactory::get_game_rules(world).supply;
+        
+        if state.has_acted || state.routing() {
+            Some("ability-acted")
+        } else if Self::cooldown(world, entity, &ability.id) > 0 {
+            Some("ability-cooldown")
+        } else if supply && stats.max_ammo > 0 && state.ammo < ability.ammo {
+            Some("ability-no-ammo")
+        } else if Self::targets(world, entity, ability).is_empty() {
+            Some("ability-no-target")
+        } else {
+            None
+        }
+    }
+    
+    // Start an ability's cooldown and pay for it; a guard goes up straight away
+    pub fn used(world: &mut World, entity: Entity, ability: &AbilityDefinition) {
+        let supply = HexMapFactory::get_game_rules(world).supply;
+        if let Some(abilities) = world.get_component_mut::<Abilities>(entity) {
+            if ability.cooldown > 0 {
+                abilities.cooldowns.insert(ability.id.clone(), ability.cooldown);
+            }
+            if let AbilityEffect::Guard { defense, turns } = ability.effect {
+                abilities.guard = Some(Guard { defense, turns_left: turns });
+            }
+        }
+        if let Some(state) = world.get_component_mut::<UnitState>(entity).filter(|_| supply) {
+            state.ammo = (state.ammo - ability.ammo).max(0);
+        }
+    }
+    
+    // At the start of a team's turn its cooldowns count down and guards wear off
+    pub fn start_of_turn(world: &mut World, team_id: u8) {
+        let units: Vec<Entity> = world.query::<Abilities>()
+            .into_iter()
+            .filter(|(entity, _)| world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id))
+            .map(|(entity, _)| entity)
+            .collect();
+        
+        for entity in units {
+            if let Some(abilities) = world.get_component_mut::<Abilities>(entity) {
+                abilities.cooldowns.retain(|_, turns| {
+                    *turns -= 1;
+                    *turns > 0
+                });
+                match abilities.guard.as_mut() {
+                    Some(guard) if guard.turns_left > 0 => guard.turns_left -= 1,
+                    _ => abilities.guard = None,
+                }
+            }
+        }
+    }
+    
+    // Extra attack from passive abilities when a unit attacks after moving this turn
+    pub fn charge_bonus(world: &World, entity: Entity) -> i32 {
+        let moved = match (world.get_component::<UnitStats>(entity), world.get_component::<UnitState>(entity)) {
+            (Some(stats), Some(state)) => state.movement_left < stats.movement,
+            _ => false,
+        };
+        if !moved {
+            return 0;
+        }
+        
+        Self::abilities(world, entity)
+            .iter()
+            .map(|ability| match ability.effect {
+                AbilityEffect::Charge { attack } => attack,
+                _ => 0,
+            })
+            .sum()
+    }
+    
+    // Extra defense from a guard that is up
+    pub fn defense_bonus(world: &World, entity: Entity) -> i32 {
+        world.get_component::<Abilities>(entity)
+            .and_then(|abilities| abilities.guard.as_ref())
+            .map_or(0, |guard| guard.defense)
+    }
+    
+    // Whether a unit holds its ground behind a guard and can't move
+    pub fn rooted(world: &World, entity: Entity) -> bool {
+        world.get_component::<Abilities>(entity).is_some_and(|abilities| abilities.guard.is_some())
+    }
+    
+    // Team of the living unit on a tile
+    fn unit_team(world: &World, entity: Entity) -> Option<u8> {
+        world.get_component::<UnitState>(entity).filter(|state| state.health > 0)?;
+        world.get_component::<Team>(entity).map(|team| team.team_id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Blue's archer at column 3 with infantry beside it at 4, Red's infantry at 1 and 5,
+    // on a row of plains painted over by `row`
+    fn world(row: &str) -> World {
+        HexMapFactory::test_world(&format!(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 7
+height = 1
+terrain = "plain"
+legend = {{ "f" = "forest" }}
+rows = ["{}"]
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "archer"
+at = [3, 0]
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [1, 0]
+[[team.unit]]
+type = "infantry"
+at = [5, 0]
+"#, row))
+    }
+    
+    fn ability(world: &World, id: &str) -> AbilityDefinition {
+        HexMapFactory::get_ability_catalog(world).unwrap().get(id).unwrap().clone()
+    }
+    
+    // Columns an ability of the unit at a column can target
+    fn targets(world: &World, q: i32, id: &str) -> Vec<i32> {
+        AbilitySystem::targets(world, HexMapFactory::test_tile(world, q), &ability(world, id)).iter().map(|coord| coord.q).collect()
+    }
+    
+    #[test]
+    fn terrain_that_blocks_sight_hides_targets_behind_it() {
+        let open = world("");
+        assert_eq!(targets(&open, 3, "fire_arrows"), [1, 5]);
+        assert_eq!(targets(&open, 3, "volley"), [1, 2, 4, 5]);
+        
+        // The forest itself can still be targeted
+        let wooded = world("  f");
+        assert_eq!(targets(&wooded, 3, "fire_arrows"), [5]);
+        assert_eq!(targets(&wooded, 3, "volley"), [2, 4, 5]);
+    }
+    
+    fn state(world: &mut World, q: i32) -> &mut UnitState {
+        let tile = HexMapFactory::test_tile(world, q);
+        world.get_component_mut::<UnitState>(tile).unwrap()
+    }
+    
+    #[test]
+    fn targets_follow_what_an_ability_aims_at() {
+        let mut world = world("");
+        
+        // The infantry at 4 can patch up itself or the archer beside it, and raise a shield wall where it stands
+        assert_eq!(targets(&world, 4, "first_aid"), [3, 4]);
+        assert_eq!(targets(&world, 4, "shield_wall"), [4]);
+        
+        // Enemies out of range or already dead aren't targets
+        assert_eq!(targets(&world, 4, "fire_arrows"), [5]);
+        state(&mut world, 5).health = 0;
+        assert_eq!(targets(&world, 3, "fire_arrows"), [1]);
+    }
+    
+    #[test]
+    fn cooldowns_count_down_on_their_own_turns() {
+        let mut world = world("");
+        let archer = HexMapFactory::test_tile(&world, 3);
+        let infantry = HexMapFactory::test_tile(&world, 4);
+        let (volley, shield_wall) = (ability(&world, "volley"), ability(&world, "shield_wall"));
+        AbilitySystem::used(&mut world, archer, &volley);
+        AbilitySystem::used(&mut world, infantry, &shield_wall);
+        assert_eq!(AbilitySystem::cooldown(&world, archer, "volley"), 3);
+        assert_eq!(AbilitySystem::defense_bonus(&world, infantry), 3);
+        assert!(AbilitySystem::rooted(&world, infantry));
+        
+        // Red's turns don't count
+        AbilitySystem::start_of_turn(&mut world, 1);
+        assert_eq!(AbilitySystem::cooldown(&world, archer, "volley"), 3);
+        
+        // The guard holds through one more own turn, and the cooldown is gone after three
+        AbilitySystem::start_of_turn(&mut world, 0);
+        assert_eq!(AbilitySystem::cooldown(&world, archer, "volley"), 2);
+        assert!(AbilitySystem::rooted(&world, infantry));
+        AbilitySystem::start_of_turn(&mut world, 0);
+        assert_eq!(AbilitySystem::cooldown(&world, archer, "volley"), 1);
+        assert!(!AbilitySystem::rooted(&world, infantry));
+        assert_eq!(AbilitySystem::defense_bonus(&world, infantry), 0);
+        AbilitySystem::start_of_turn(&mut world, 0);
+        assert_eq!(AbilitySystem::cooldown(&world, archer, "volley"), 0);
+        assert!(world.get_component::<Abilities>(archer).unwrap().cooldowns.is_empty());
+    }
+    
+    #[test]
+    fn blocker_explains_why_an_ability_cant_be_used() {
+        let mut world = world("");
+        let archer = HexMapFactory::test_tile(&world, 3);
+        let volley = ability(&world, "volley");
+        let fire_arrows = ability(&world, "fire_arrows");
+        assert_eq!(AbilitySystem::blocker(&world, archer, &volley), None);
+        
+        state(&mut world, 3).has_acted = true;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &volley), Some("ability-acted"));
+        state(&mut world, 3).has_acted = false;
+        state(&mut world, 3).morale = 0;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &volley), Some("ability-acted"));
+        state(&mut world, 3).morale = MAX_MORALE;
+        
+        AbilitySystem::used(&mut world, archer, &volley);
+        assert_eq!(AbilitySystem::blocker(&world, archer, &volley), Some("ability-cooldown"));
+        
+        // Ammo only runs out under the supply rule
+        state(&mut world, 3).ammo = 0;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &fire_arrows), None);
+        let game_state = HexMapFactory::get_game_state_entity(&world).unwrap();
+        world.get_component_mut::<GameRules>(game_state).unwrap().supply = true;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &fire_arrows), Some("ability-no-ammo"));
+        state(&mut world, 3).ammo = 1;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &fire_arrows), None);
+        
+        state(&mut world, 1).health = 0;
+        state(&mut world, 5).health = 0;
+        assert_eq!(AbilitySystem::blocker(&world, archer, &fire_arrows), Some("ability-no-target"));
+    }
+    
+    #[test]
+    fn volley_hits_the_enemies_in_the_ring_around_its_target() {
+        // Red all around (2, 1) and on it, bar Blue's archer at (1, 1), and Red further out
+        let mut text = String::from(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 5
+height = 3
+terrain = "plain"
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "archer"
+at = [1, 1]
+[[team]]
+name = "Red"
+"#);
+        for [q, r] in [[2, 1], [3, 1], [2, 0], [3, 0], [2, 2], [1, 2], [4, 1], [0, 0]] {
+            text += &format!("[[team.unit]]\ntype = \"infantry\"\nat = [{}, {}]\n", q, r);
+        }
+        let world = HexMapFactory::test_world(&text);
+        let volley = ability(&world, "volley");
+        
+        let affected: Vec<HexCoord> = AbilitySystem::affected(&world, 0, &volley, HexCoord::new(2, 1))
+            .into_iter()
+            .map(|tile| world.get_component::<Position>(tile).unwrap().coord)
+            .collect();
+        assert_eq!(affected, [
+            HexCoord::new(1, 2),
+            HexCoord::new(2, 0),
+            HexCoord::new(2, 1),
+            HexCoord::new(2, 2),
+            HexCoord::new(3, 0),
+            HexCoord::new(3, 1),
+        ]);
+        
+        // Aimed at Red, the same ring only catches Blue
+        let archer = HexMapFactory::get_tile_entity(&world, &HexCoord::new(1, 1)).unwrap();
+        assert_eq!(AbilitySystem::affected(&world, 1, &volley, HexCoord::new(2, 1)), [archer]);
+    }
+}
@@ -0,0 +1,222 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::s::ability::AbilitySystem;
+use crate::s::camera::CameraSystem;
+use crate::s::economy::EconomySystem;
+use crate::s::input::InputSystem;
+use crate::s::recovery::{RecoverySystem, SUPPLY_RANGE};
+use crate::s::settlement::SettlementSystem;
+use crate::s::veterancy::VeterancySystem;
+use crate::c::*;
+
+// System that plays teams with an AI controller.
+// It gives orders through the same tile activation a player uses, one unit per step,
+// so the app can let animations finish between steps.
+pub struct AiSystem;
+
+impl AiSystem {
+    // Whether the team whose turn it is is played by the computer
+    pub fn is_ai_turn(world: &World) -> bool {
+        let current_turn = match HexMapFactory::get_game_state_entity(world).and_then(|entity| world.get_component::<GameState>(entity)) {
+            Some(game_state) if !game_state.game_over => game_state.current_turn,
+            _ => return false,
+        };
+        
+        HexMapFactory::get_teams(world)
+            .and_then(|teams| teams.get(current_turn))
+            .is_some_and(|team| team.controller == Controller::Ai)
+    }
+    
+    // Buy a unit or give orders to the next idle unit. Returns false once every unit has acted.
+    pub fn step(world: &mut World) -> bool {
+        let current_turn = match HexMapFactory::get_game_state_entity(world).and_then(|entity| world.get_component::<GameState>(entity)) {
+            Some(game_state) => game_state.current_turn,
+            None => return false,
+        };
+        
+        // Veterans take the first promotion their type offers
+        for entity in VeterancySystem::awaiting_promotion(world, current_turn) {
+            if let Some(&promotion) = VeterancySystem::options(world, entity).first() {
+                VeterancySystem::promote(world, entity, promotion);
+            }
+        }
+        
+        // Spend the gold first; new units wait for the next turn anyway
+        if Self::recruit(world, current_turn) {
+            return true;
+        }
+        
+        let unit = match HexMapFactory::idle_units(world, current_turn).first() {
+            Some(&unit) => unit,
+            None => return false,
+        };
+        let unit_coord = match world.get_component::<Position>(unit) {
+            Some(position) => position.coord,
+            None => return false,
+        };
+        
+        // Selecting the unit marks its movement and attack ranges
+        InputSystem::deselect_current(world);
+        InputSystem::activate_tile(world, unit, current_turn);
+        CameraSystem::ensure_visible(world, unit_coord);
+        
+        // Volley into a crowd or patch up a badly hurt friend when the chance is there
+        if Self::use_ability(world, unit, current_turn) {
+            return true;
+        }
+        
+        // Attack the weakest enemy in range
+        if let Some(target) = Self::weakest_target(world) {
+            InputSystem::activate_tile(world, target, current_turn);
+            return true;
+        }
+        
+        // Otherwise fall back to refill if out of ammo, take a settlement in reach, staying put if
+        // already on one, or close in on the nearest enemy, if that gets us any closer
+        let enemies = Self::enemy_positions(world, current_turn);
+        let distance_to_enemy = |coord: &HexCoord| enemies.iter().map(|enemy| coord.distance(enemy)).min().unwrap_or(0);
+        let supplies = Self::supply_positions(world, current_turn);
+        let distance_to_supply = |coord: &HexCoord| supplies.iter().map(|supply| coord.distance(supply)).min().unwrap_or(0);
+        let reachable: Vec<(Entity, HexCoord)> = world.query::<InMovementRange>()
+            .into_iter()
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| (entity, position.coord)))
+            .collect();
+        let settlement = reachable.iter()
+            .filter(|(entity, _)| Self::can_capture(world, *entity, current_turn))
+            .min_by_key(|(_, coord)| (unit_coord.distance(coord), coord.q, coord.r))
+            .copied();
+        let destination = if RecoverySystem::out_of_ammo(world, unit) && !supplies.is_empty() {
+            reachable.iter()
+                .min_by_key(|(_, coord)| (distance_to_supply(coord), coord.q, coord.r))
+                .filter(|(_, coord)| distance_to_supply(&unit_coord) > SUPPLY_RANGE && distance_to_supply(coord) < distance_to_supply(&unit_coord))
+                .copied()
+        } else if Self::can_capture(world, unit, current_turn) {
+            None
+        } else {
+            settlement.or_else(|| {
+                reachable.iter()
+                    .min_by_key(|(_, coord)| (distance_to_enemy(coord), coord.q, coord.r))
+                    .filter(|(_, coord)| distance_to_enemy(coord) < distance_to_enemy(&unit_coord))
+                    .copied()
+            })
+        };
+        
+        match destination {
+            Some((tile, _)) => InputSystem::activate_tile(world, tile, current_turn),
+            None => {
+                // Nothing useful to do, hold position for the rest of the turn
+                InputSystem::deselect_current(world);
+                if let Some(state) = world.get_component_mut::<UnitState>(unit) {
+                    state.has_acted = true;
+                }
+            },
+        }
+        
+        true
+    }
+    
+    // Buy one unit at the first city that has room, of the affordable type the team has fewest of
+    fn recruit(world: &mut World, team_id: u8) -> bool {
+        let fielded = |unit_type: &UnitType| {
+            world.query::<UnitStats>()
+                .into_iter()
+                .filter(|(entity, stats)| stats.unit_type == *unit_type && world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id))
+                .count()
+        };
+        
+        let order = EconomySystem::cities(world, team_id).into_iter().find_map(|city| {
+            EconomySystem::recruitable(world, team_id)
+                .into_iter()
+                .filter(|definition| EconomySystem::recruit_blocker(world, team_id, city, definition).is_none())
+                .min_by_key(|definition| fielded(&definition.id))
+                .map(|definition| (city, definition.id.clone()))
+        });
+        
+        match order {
+            Some((city, unit_type)) => {
+                CameraSystem::ensure_visible(world, city);
+                EconomySystem::recruit(world, team_id, city, &unit_type)
+            },
+            None => false,
+        }
+    }
+    
+    // Use the selected unit's first ability that is worth it, aimed where it does the most
+    fn use_ability(world: &mut World, unit: Entity, team_id: u8) -> bool {
+        let abilities: Vec<AbilityDefinition> = AbilitySystem::active(world, unit).into_iter().cloned().collect();
+        for ability in abilities {
+            if AbilitySystem::blocker(world, unit, &ability).is_some() {
+                continue;
+            }
+            
+            let best = AbilitySystem::targets(world, unit, &ability)
+                .into_iter()
+                .map(|target| (Self::ability_value(world, team_id, &ability, target), target))
+                .filter(|&(value, _)| value > 0)
+                .max_by_key(|&(value, target)| (value, -target.q, -target.r));
+            if let Some((_, target)) = best {
+                InputSystem::arm_ability(world, &ability.id);
+                if let Some(tile) = HexMapFactory::get_tile_entity(world, &target).filter(|_| ability.target != AbilityTarget::User) {
+                    InputSystem::activate_tile(world, tile, team_id);
+                }
+                return true;
+            }
+        }
+        false
+    }
+    
+    // How much good an ability does at a hex: the enemies caught when at least two are, or the
+    // health restored when it is all put to use. Guards are left to players.
+    fn ability_value(world: &World, team_id: u8, ability: &AbilityDefinition, target: HexCoord) -> i32 {
+        let affected = AbilitySystem::affected(world, team_id, ability, target);
+        match ability.effect {
+            AbilityEffect::Damage { .. } if affected.len() >= 2 => affected.len() as i32,
+            AbilityEffect::Heal { amount } => {
+                let healed: i32 = affected.iter()
+                    .filter_map(|&entity| match (world.get_component::<UnitStats>(entity), world.get_component::<UnitState>(entity)) {
+                        (Some(stats), Some(state)) => Some((stats.max_health - state.health).min(amount)),
+                        _ => None,
+                    })
+                    .sum();
+                if healed >= amount { healed } else { 0 }
+            },
+            _ => 0,
+        }
+    }
+    
+    // Whether ending the turn on a tile would take a settlement for the team
+    fn can_capture(world: &World, entity: Entity, team_id: u8) -> bool {
+        world.get_component::<Settlement>(entity)
+            .is_some_and(|settlement| settlement.owner.is_none_or(|owner| HexMapFactory::are_enemies(world, owner, team_id)))
+    }
+    
+    // Enemy unit in attack range with the least health left
+    fn weakest_target(world: &World) -> Option<Entity> {
+        world.query::<InAttackRange>()
+            .into_iter()
+            .filter_map(|(entity, _)| world.get_component::<UnitState>(entity).map(|state| (entity, state.health)))
+            .min_by_key(|&(entity, health)| (health, entity))
+            .map(|(entity, _)| entity)
+    }
+    
+    // Where the settlements friendly to a team are
+    fn supply_positions(world: &World, team_id: u8) -> Vec<HexCoord> {
+        world.query::<Settlement>()
+            .into_iter()
+            .filter(|(_, settlement)| SettlementSystem::is_friendly(world, settlement, team_id))
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| position.coord))
+            .collect()
+    }
+    
+    // Where the living enemies of a team are
+    fn enemy_positions(world: &World, team_id: u8) -> Vec<HexCoord> {
+        world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| {
+                HexMapFactory::are_enemies(world, team.team_id, team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+            })
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| position.coord))
+            .collect()
+    }
+}
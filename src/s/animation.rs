@@ -0,0 +1,154 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::c::*;
+use eframe::egui::{self, Color32, Pos2, Stroke};
+
+// Seconds per hex when sliding along a path
+pub const MOVE_STEP_TIME: f32 = 0.15;
+// Seconds for an attack lunge or projectile flight
+pub const ATTACK_TIME: f32 = 0.3;
+// Seconds a damage number floats for
+pub const DAMAGE_TIME: f32 = 0.9;
+// Seconds a destroyed unit takes to fade out
+pub const DEATH_TIME: f32 = 0.6;
+
+// System that plays visual animations on top of the game state.
+// Game logic never waits for it; it only changes how units are drawn for a moment.
+pub struct AnimationSystem;
+
+impl AnimationSystem {
+    // Start an animation. Anything already animating the same unit is dropped.
+    pub fn play(world: &mut World, animation: Animation) {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        if let Some(animations) = world.get_component_mut::<Animations>(game_state_entity) {
+            if animations.speed <= 0.0 {
+                return;
+            }
+            
+            if animation.entity.is_some() {
+                animations.active.retain(|active| active.entity != animation.entity);
+            }
+            animations.active.push(animation);
+        }
+    }
+    
+    // Advance all animations by `dt` seconds. Returns whether any are still running.
+    pub fn update(world: &mut World, dt: f32) -> bool {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return false,
+        };
+        
+        match world.get_component_mut::<Animations>(game_state_entity) {
+            Some(animations) => {
+                let step = dt * animations.speed;
+                for animation in animations.active.iter_mut() {
+                    animation.elapsed += step;
+                }
+                animations.active.retain(|animation| !animation.finished());
+                !animations.active.is_empty()
+            },
+            None => false,
+        }
+    }
+    
+    // Jump to the end of everything that's playing
+    pub fn skip(world: &mut World) {
+        if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(world) {
+            if let Some(animations) = world.get_component_mut::<Animations>(game_state_entity) {
+                animations.active.clear();
+            }
+        }
+    }
+    
+    // Whether anything is playing
+    pub fn is_playing(world: &World) -> bool {
+        HexMapFactory::get_game_state_entity(world)
+            .and_then(|entity| world.get_component::<Animations>(entity))
+            .is_some_and(|animations| !animations.active.is_empty())
+    }
+    
+    // Where to draw a unit right now, if an animation has moved it off its tile
+    pub fn unit_position(world: &World, entity: Entity, layout: &HexLayout) -> Option<Pos2> {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world)?;
+        let animations = world.get_component::<Animations>(game_state_entity)?;
+        let animation = animations.active.iter().find(|animation| animation.entity == Some(entity))?;
+        let t = animation.progress();
+        
+        match &animation.kind {
+            AnimationKind::Move { path } if path.len() > 1 => {
+                // Constant speed from hex to hex
+                let segments = (path.len() - 1) as f32;
+                let along = t * segments;
+                let index = (along.floor() as usize).min(path.len() - 2);
+                let from = path[index].to_pixel(layout);
+                let to = path[index + 1].to_pixel(layout);
+                Some(from + (to - from) * (along - index as f32))
+            },
+            AnimationKind::Attack { from, to, ranged: false } => {
+                // Lunge a third of the way towards the target and back
+                let start = from.to_pixel(layout);
+                let lunge = (to.to_pixel(layout) - start) * 0.35 * (t * std::f32::consts::PI).sin();
+                Some(start + lunge)
+            },
+            _ => None,
+        }
+    }
+    
+    // Draw projectiles, damage numbers and fading units
+    pub fn paint_effects(world: &World, ui: &mut egui::Ui, layout: &HexLayout) {
+        let animations = match HexMapFactory::get_game_state_entity(world).and_then(|entity| world.get_component::<Animations>(entity)) {
+            Some(animations) => animations,
+            None => return,
+        };
+        
+        let size = layout.min_size();
+        let painter = ui.painter();
+        
+        for animation in animations.active.iter().filter(|animation| animation.started()) {
+            let t = animation.progress();
+            
+            match &animation.kind {
+                AnimationKind::Attack { from, to, ranged: true } => {
+                    let start = from.to_pixel(layout);
+                    let end = to.to_pixel(layout);
+                    let pos = start + (end - start) * t;
+                    
+                    // Short streak behind the projectile
+                    let tail = start + (end - start) * (t - 0.1).max(0.0);
+                    painter.line_segment([tail, pos], Stroke::new(2.0, Color32::from_rgb(90, 60, 30)));
+                    painter.circle_filled(pos, (size * 0.1).max(2.0), Color32::from_rgb(60, 40, 20));
+                },
+                AnimationKind::Damage { at, amount } => {
+                    Self::paint_number(painter, at.to_pixel(layout), size, t, format!("-{}", amount), (255, 60, 40));
+                },
+                AnimationKind::Heal { at, amount } => {
+                    Self::paint_number(painter, at.to_pixel(layout), size, t, format!("+{}", amount), (60, 220, 60));
+                },
+                AnimationKind::Death { at, color } => {
+                    let center = at.to_pixel(layout);
+                    let radius = size * 0.6 * (1.0 - 0.4 * t);
+                    painter.circle_filled(center, radius, color.gamma_multiply(1.0 - t));
+                    painter.circle_stroke(center, radius, Stroke::new(1.0, Color32::BLACK.gamma_multiply(1.0 - t)));
+                },
+                _ => {},
+            }
+        }
+    }
+    
+    // Number floating up from a hex and fading out
+    fn paint_number(painter: &egui::Painter, center: Pos2, size: f32, t: f32, text: String, (r, g, b): (u8, u8, u8)) {
+        let alpha = ((1.0 - t) * 255.0) as u8;
+        painter.text(
+            center - egui::vec2(0.0, size * (0.3 + 0.7 * t)),
+            egui::Align2::CENTER_CENTER,
+            text,
+            egui::FontId::proportional((size * 0.5).max(12.0)),
+            Color32::from_rgba_unmultiplied(r, g, b, alpha),
+        );
+    }
+}
@@ -0,0 +1,152 @@
+use crate::e::entity::World;
+use crate::e::factory::HexMapFactory;
+use crate::s::keybindings::{InputAction, KeyBindings};
+use crate::c::*;
+use eframe::egui::{self, Rect, Vec2};
+
+// Keyboard pan speed in screen points per second
+const PAN_SPEED: f32 = 500.0;
+
+// System for panning and zooming the board
+pub struct CameraSystem;
+
+impl CameraSystem {
+    pub fn update(world: &mut World, ui: &egui::Ui, response: &egui::Response, bindings: &KeyBindings) {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        // Only measure the map when we still need to frame it
+        let framed = world.get_component::<Camera>(map_settings_entity).is_none_or(|camera| camera.framed);
+        let map_bounds = if framed { None } else { Self::map_bounds(world) };
+        
+        let (scroll, pinch, dt, pan, zoom_keys, centre_key) = ui.input(|i| {
+            let mut pan = Vec2::ZERO;
+            if bindings.held(i, InputAction::PanLeft) { pan.x += 1.0; }
+            if bindings.held(i, InputAction::PanRight) { pan.x -= 1.0; }
+            if bindings.held(i, InputAction::PanUp) { pan.y += 1.0; }
+            if bindings.held(i, InputAction::PanDown) { pan.y -= 1.0; }
+            
+            let mut zoom_keys = 1.0;
+            if bindings.pressed(i, InputAction::ZoomIn) { zoom_keys *= 1.25; }
+            if bindings.pressed(i, InputAction::ZoomOut) { zoom_keys /= 1.25; }
+            
+            (i.scroll_delta.y, i.zoom_delta(), i.stable_dt, pan, zoom_keys, bindings.pressed(i, InputAction::CentreOnUnit))
+        });
+        
+        // Don't steal keys from text fields
+        let keyboard_free = !ui.ctx().wants_keyboard_input();
+        
+        if let Some(camera) = world.get_component_mut::<Camera>(map_settings_entity) {
+            camera.viewport = response.rect;
+            
+            // Fit the whole map in view the first time we see it
+            if !camera.framed {
+                if let Some(bounds) = map_bounds {
+                    let fit = (response.rect.width() / bounds.width()).min(response.rect.height() / bounds.height());
+                    camera.zoom = fit.clamp(Camera::MIN_ZOOM, 1.0);
+                    camera.look_at(bounds.center(), response.rect.center());
+                }
+                camera.framed = true;
+            }
+            
+            // Drag to pan
+            if response.dragged() {
+                camera.offset += response.drag_delta();
+            }
+            
+            // Mouse wheel (or pinch) zooms around the cursor
+            if let Some(hover_pos) = response.hover_pos() {
+                let factor = pinch * (scroll * 0.002).exp();
+                if factor != 1.0 {
+                    camera.zoom_around(factor, hover_pos);
+                }
+            }
+            
+            if keyboard_free {
+                // Pan keys
+                if pan != Vec2::ZERO {
+                    camera.offset += pan * PAN_SPEED * dt;
+                }
+                
+                // Zoom keys zoom around the middle of the board
+                if zoom_keys != 1.0 {
+                    camera.zoom_around(zoom_keys, response.rect.center());
+                }
+            }
+        }
+        
+        if keyboard_free && centre_key {
+            Self::centre_on_selected(world);
+        }
+        
+        if response.dragged() || pan != Vec2::ZERO {
+            ui.ctx().request_repaint();
+        }
+    }
+    
+    // Centre the board on the selected unit, if any
+    pub fn centre_on_selected(world: &mut World) {
+        let selected = HexMapFactory::get_game_state_entity(world)
+            .and_then(|entity| world.get_component::<GameState>(entity))
+            .and_then(|game_state| game_state.selected_entity);
+        
+        if let Some(coord) = selected.and_then(|entity| world.get_component::<Position>(entity)).map(|pos| pos.coord) {
+            Self::centre_on(world, coord);
+        }
+    }
+    
+    // Centre the board on a hex
+    pub fn centre_on(world: &mut World, coord: HexCoord) {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let layout = match world.get_component::<MapSettings>(map_settings_entity) {
+            Some(settings) => settings.layout,
+            None => return,
+        };
+        
+        if let Some(camera) = world.get_component_mut::<Camera>(map_settings_entity) {
+            let centre = camera.viewport.center();
+            camera.look_at(coord.to_pixel(&layout), centre);
+        }
+    }
+    
+    // Recentre on a hex only if it has scrolled out of view
+    pub fn ensure_visible(world: &mut World, coord: HexCoord) {
+        let visible = HexMapFactory::get_map_settings_entity(world)
+            .and_then(|entity| world.get_component::<Camera>(entity))
+            .zip(HexMapFactory::get_view_layout(world))
+            .is_none_or(|(camera, layout)| {
+                !camera.viewport.is_positive() || camera.viewport.shrink(layout.min_size()).contains(coord.to_pixel(&layout))
+            });
+        
+        if !visible {
+            Self::centre_on(world, coord);
+        }
+    }
+    
+    // Bounding box of the whole map in map layout space (before camera).
+    // Kept with the terrain mesh; only measured here until the mesh is first built.
+    pub fn map_bounds(world: &World) -> Option<Rect> {
+        let map_settings_entity = HexMapFactory::get_map_settings_entity(world)?;
+        if let Some(mesh) = world.get_component::<TerrainMesh>(map_settings_entity).filter(|mesh| !mesh.dirty) {
+            return mesh.bounds;
+        }
+        
+        let layout = world.get_component::<MapSettings>(map_settings_entity)?.layout;
+        let hex_entities = world.get_component::<HexEntityMap>(map_settings_entity)?;
+        
+        let mut bounds = Rect::NOTHING;
+        for coord in hex_entities.map.keys() {
+            for corner in layout.corners(coord) {
+                bounds.extend_with(corner);
+            }
+        }
+        
+        if bounds.is_positive() { Some(bounds) } else { None }
+    }
+}
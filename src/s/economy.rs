@@ -0,0 +1,254 @@
+use crate::e::entity::World;
+use crate::e::factory::HexMapFactory;
+use crate::s::settlement::SettlementSystem;
+use crate::c::*;
+
+// System for team gold: income from settlements, upkeep of units and buying new ones
+pub struct EconomySystem;
+
+impl EconomySystem {
+    // Gold a team has to spend
+    pub fn gold(world: &World, team_id: u8) -> i32 {
+        Self::get_treasury(world).map_or(0, |treasury| treasury.gold(team_id))
+    }
+    
+    // Gold a team earns each turn before upkeep
+    pub fn income(world: &World, team_id: u8) -> i32 {
+        BASE_INCOME + SettlementSystem::owned_by(world, team_id).iter().map(|settlement| settlement.income).sum::<i32>()
+    }
+    
+    // Gold a team pays each turn for its living units; leaders serve for free
+    pub fn upkeep(world: &World, team_id: u8) -> i32 {
+        let catalog = match HexMapFactory::get_unit_catalog(world) {
+            Some(catalog) => catalog,
+            None => return 0,
+        };
+        
+        world.query::<UnitStats>()
+            .into_iter()
+            .filter(|(entity, _)| {
+                world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+                    && world.get_component::<Leader>(*entity).is_none()
+            })
+            .filter_map(|(_, stats)| catalog.get(&stats.unit_type))
+            .map(|definition| definition.upkeep)
+            .sum()
+    }
+    
+    // Income less upkeep
+    pub fn net_income(world: &World, team_id: u8) -> i32 {
+        Self::income(world, team_id) - Self::upkeep(world, team_id)
+    }
+    
+    // At the end of a team's turn it collects its income and pays upkeep; the treasury never goes below zero
+    pub fn collect(world: &mut World, team_id: u8) {
+        let net = Self::net_income(world, team_id);
+        if let Some(treasury) = Self::get_treasury_mut(world) {
+            let gold = treasury.gold.entry(team_id).or_insert(0);
+            *gold = (*gold + net).max(0);
+        }
+    }
+    
+    // Cities a team can recruit at, in map order
+    pub fn cities(world: &World, team_id: u8) -> Vec<HexCoord> {
+        let mut cities: Vec<HexCoord> = world.query::<Settlement>()
+            .into_iter()
+            .filter(|(_, settlement)| settlement.kind == SettlementKind::City && settlement.owner == Some(team_id))
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| position.coord))
+            .collect();
+        cities.sort_by_key(|coord| (coord.q, coord.r));
+        cities
+    }
+    
+    // Unit types a team may buy, in catalog order
+    pub fn recruitable(world: &World, team_id: u8) -> Vec<&UnitDefinition> {
+        match (HexMapFactory::get_unit_catalog(world), Self::get_treasury(world)) {
+            (Some(catalog), Some(treasury)) => catalog.units.iter().filter(|definition| treasury.can_recruit(team_id, &definition.id)).collect(),
+            _ => Vec::new(),
+        }
+    }
+    
+    // First free hex next to a city that a unit of this type can stand on
+    pub fn recruit_hex(world: &World, city: HexCoord, definition: &UnitDefinition) -> Option<HexCoord> {
+        city.neighbors().into_iter().find(|coord| {
+            HexMapFactory::get_tile_entity(world, coord).is_some_and(|entity| world.get_component::<UnitState>(entity).is_none())
+                && HexMapFactory::terrain_at(world, *coord).is_some_and(|terrain| terrain.movement_cost_for(&definition.class).is_some())
+        })
+    }
+    
+    // Why a team can't buy a unit at a city right now, as a localization key, or None if it can
+    pub fn recruit_blocker(world: &World, team_id: u8, city: HexCoord, definition: &UnitDefinition) -> Option<&'static str> {
+        if !Self::cities(world, team_id).contains(&city) {
+            Some("recruit-no-city")
+        } else if Self::get_treasury(world).is_none_or(|treasury| !treasury.can_recruit(team_id, &definition.id)) {
+            Some("recruit-not-allowed")
+        } else if Self::gold(world, team_id) < definition.cost {
+            Some("recruit-no-gold")
+        } else if Self::recruit_hex(world, city, definition).is_none() {
+            Some("recruit-no-room")
+        } else {
+            None
+        }
+    }
+    
+    // Buy a unit at a city. It arrives next to the city and can't move until the team's next turn.
+    pub fn recruit(world: &mut World, team_id: u8, city: HexCoord, unit_type: &UnitType) -> bool {
+        let definition = match HexMapFactory::get_unit_catalog(world).and_then(|catalog| catalog.get(unit_type)) {
+            Some(definition) => definition.clone(),
+            None => return false,
+        };
+        if Self::recruit_blocker(world, team_id, city, &definition).is_some() {
+            return false;
+        }
+        let coord = match Self::recruit_hex(world, city, &definition) {
+            Some(coord) => coord,
+            None => return false,
+        };
+        
+        if let Some(treasury) = Self::get_treasury_mut(world) {
+            *treasury.gold.entry(team_id).or_insert(0) -= definition.cost;
+        }
+        HexMapFactory::add_unit(world, coord, &definition, team_id);
+        if let Some(state) = HexMapFactory::get_tile_entity(world, &coord).and_then(|entity| world.get_component_mut::<UnitState>(entity)) {
+            state.movement_left = 0;
+            state.has_acted = true;
+            state.rested = false;
+        }
+        true
+    }
+    
+    fn get_treasury(world: &World) -> Option<&Treasury> {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world)?;
+        world.get_component::<Treasury>(game_state_entity)
+    }
+    
+    fn get_treasury_mut(world: &mut World) -> Option<&mut Treasury> {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world)?;
+        world.get_component_mut::<Treasury>(game_state_entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Blue: leader cavalry at column 0, its city at 1 and infantry at 3, with 12 gold.
+    // Red: a city at 4 hemmed in by its cavalry at 5 and Blue's infantry, infantry at 6, no gold
+    // and only infantry for hire.
+    fn world() -> World {
+        HexMapFactory::test_world(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 7
+height = 1
+terrain = "plain"
+[[map.settlement]]
+kind = "city"
+at = [1, 0]
+owner = 0
+income = 3
+[[map.settlement]]
+kind = "city"
+at = [4, 0]
+owner = 1
+income = 0
+[[team]]
+name = "Blue"
+gold = 12
+[[team.unit]]
+type = "cavalry"
+at = [0, 0]
+leader = true
+[[team.unit]]
+type = "infantry"
+at = [3, 0]
+[[team]]
+name = "Red"
+gold = 0
+recruit = ["infantry"]
+[[team.unit]]
+type = "cavalry"
+at = [5, 0]
+[[team.unit]]
+type = "infantry"
+at = [6, 0]
+"#)
+    }
+    
+    fn definition(world: &World, id: &str) -> UnitDefinition {
+        HexMapFactory::get_unit_catalog(world).unwrap().get(&UnitType::new(id)).unwrap().clone()
+    }
+    
+    fn set_gold(world: &mut World, team_id: u8, gold: i32) {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
+        world.get_component_mut::<Treasury>(game_state_entity).unwrap().gold.insert(team_id, gold);
+    }
+    
+    #[test]
+    fn collecting_adds_income_less_upkeep() {
+        let mut world = world();
+        assert_eq!(EconomySystem::income(&world, 0), BASE_INCOME + 3);
+        assert_eq!(EconomySystem::upkeep(&world, 0), 1);
+        
+        EconomySystem::collect(&mut world, 0);
+        assert_eq!(EconomySystem::gold(&world, 0), 12 + BASE_INCOME + 3 - 1);
+    }
+    
+    #[test]
+    fn gold_never_goes_below_zero() {
+        let mut world = world();
+        assert_eq!(EconomySystem::net_income(&world, 1), BASE_INCOME - 3);
+        
+        EconomySystem::collect(&mut world, 1);
+        assert_eq!(EconomySystem::gold(&world, 1), 0);
+    }
+    
+    #[test]
+    fn leaders_and_the_dead_cost_nothing() {
+        let mut world = world();
+        let leader = HexMapFactory::test_tile(&world, 0);
+        world.remove_component::<Leader>(leader);
+        assert_eq!(EconomySystem::upkeep(&world, 0), 3);
+        
+        world.get_component_mut::<UnitState>(leader).unwrap().health = 0;
+        assert_eq!(EconomySystem::upkeep(&world, 0), 1);
+    }
+    
+    #[test]
+    fn recruiting_needs_a_city_permission_gold_and_room() {
+        let mut world = world();
+        let (blue_city, red_city) = (HexCoord::new(1, 0), HexCoord::new(4, 0));
+        let blocker = |world: &World, team_id, city, id| EconomySystem::recruit_blocker(world, team_id, city, &definition(world, id));
+        
+        assert_eq!(blocker(&world, 1, blue_city, "infantry"), Some("recruit-no-city"));
+        assert_eq!(blocker(&world, 1, red_city, "archer"), Some("recruit-not-allowed"));
+        assert_eq!(blocker(&world, 1, red_city, "infantry"), Some("recruit-no-gold"));
+        set_gold(&mut world, 1, 20);
+        assert_eq!(blocker(&world, 1, red_city, "infantry"), Some("recruit-no-room"));
+        
+        assert_eq!(blocker(&world, 0, blue_city, "cavalry"), Some("recruit-no-gold"));
+        assert_eq!(blocker(&world, 0, blue_city, "infantry"), None);
+        assert_eq!(EconomySystem::recruitable(&world, 1).len(), 1);
+    }
+    
+    #[test]
+    fn recruits_arrive_next_to_the_city_ready_for_next_turn() {
+        let mut world = world();
+        assert!(!EconomySystem::recruit(&mut world, 0, HexCoord::new(1, 0), &UnitType::new("cavalry")));
+        assert!(EconomySystem::recruit(&mut world, 0, HexCoord::new(1, 0), &UnitType::new("infantry")));
+        assert_eq!(EconomySystem::gold(&world, 0), 2);
+        
+        let recruit = HexMapFactory::test_tile(&world, 2);
+        assert_eq!(world.get_component::<Team>(recruit).unwrap().team_id, 0);
+        assert_eq!(world.get_component::<UnitStats>(recruit).unwrap().unit_type, UnitType::new("infantry"));
+        let state = world.get_component::<UnitState>(recruit).unwrap();
+        assert_eq!((state.health, state.movement_left, state.has_acted, state.rested), (10, 0, true, false));
+        
+        // The only free hex by the city is taken now
+        set_gold(&mut world, 0, 20);
+        assert_eq!(EconomySystem::recruit_blocker(&world, 0, HexCoord::new(1, 0), &definition(&world, "infantry")), Some("recruit-no-room"));
+        assert!(!EconomySystem::recruit(&mut world, 0, HexCoord::new(1, 0), &UnitType::new("infantry")));
+    }
+}
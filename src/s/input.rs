@@ -1,5 +1,16 @@
 use crate::e::entity::{Entity, World};
 use crate::e::factory::HexMapFactory;
+use crate::s::ability::AbilitySystem;
+use crate::s::animation::{AnimationSystem, ATTACK_TIME, DAMAGE_TIME, DEATH_TIME, MOVE_STEP_TIME};
+use crate::s::camera::CameraSystem;
+use crate::s::keybindings::{InputAction, KeyBindings};
+use crate::s::economy::EconomySystem;
+use crate::s::morale::MoraleSystem;
+use crate::s::recovery::RecoverySystem;
+use crate::s::settlement::SettlementSystem;
+use crate::s::status::{StatusSystem, RULE_STATUS_TURNS, SUPPRESSED};
+use crate::s::veterancy::VeterancySystem;
+use crate::s::victory::VictorySystem;
 use crate::c::*;
 use eframe::egui;
 use std::collections::{HashMap, HashSet};
@@ -8,26 +19,18 @@ use std::collections::{HashMap, HashSet};
 pub struct InputSystem;
 
 impl InputSystem {
-    pub fn update(world: &mut World, _ui: &egui::Ui, response: &egui::Response) {
+    pub fn update(world: &mut World, ui: &egui::Ui, response: &egui::Response, bindings: &KeyBindings) {
         // Get the game state and map settings
         let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
             Some(entity) => entity,
             None => return,
         };
         
-        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
-            Some(entity) => entity,
+        let layout = match HexMapFactory::get_view_layout(world) {
+            Some(layout) => layout,
             None => return,
         };
         
-        let (hex_size, origin) = {
-            if let Some(settings) = world.get_component::<MapSettings>(map_settings_entity) {
-                (settings.hex_size, settings.origin)
-            } else {
-                return;
-            }
-        };
-        
         let current_turn = if let Some(game_state) = world.get_component::<GameState>(game_state_entity) {
             game_state.current_turn
         } else {
@@ -47,10 +50,9 @@ impl InputSystem {
         
         // Check for hovering
         if let Some(mouse_pos) = response.hover_pos() {
-            let hex_coord = HexCoord::from_pixel(mouse_pos, hex_size, origin);
-            let hex_entities = HexMapFactory::get_hex_entity_map(world);
+            let hex_coord = HexCoord::from_pixel(mouse_pos, &layout);
             
-            if let Some(&entity) = hex_entities.get(&hex_coord) {
+            if let Some(entity) = HexMapFactory::get_tile_entity(world, &hex_coord) {
                 // Add hovering component
                 world.add_component(entity, Hovering);
                 
@@ -61,43 +63,192 @@ impl InputSystem {
                 
                 // Handle click
                 if response.clicked() {
-                    let selected_entity = if let Some(game_state) = world.get_component::<GameState>(game_state_entity) {
-                        game_state.selected_entity
-                    } else {
-                        None
-                    };
-                    
-                    if let Some(selected) = selected_entity {
-                        if selected == entity {
-                            // Deselect if clicking on already selected tile
-                            Self::deselect_current(world);
-                        } else {
-                            // Check if in movement or attack range
-                            let in_movement_range = world.get_component::<InMovementRange>(entity).is_some();
-                            let in_attack_range = world.get_component::<InAttackRange>(entity).is_some();
-                            
-                            if in_movement_range {
-                                // Move unit
-                                Self::move_unit(world, selected, entity);
-                            } else if in_attack_range {
-                                // Attack
-                                Self::attack_unit(world, selected, entity);
-                            } else {
-                                // Try to select new tile
-                                Self::select_tile(world, entity, current_turn);
-                            }
-                        }
-                    } else {
-                        // Try to select new tile
-                        Self::select_tile(world, entity, current_turn);
-                    }
+                    Self::activate_tile(world, entity, current_turn);
+                }
+            }
+        }
+        
+        // Keyboard controls, unless a text field has focus
+        if !ui.ctx().wants_keyboard_input() {
+            let pressed: Vec<InputAction> = ui.input(|input| {
+                InputAction::all()
+                    .into_iter()
+                    .filter(|&action| bindings.pressed(input, action))
+                    .collect()
+            });
+            
+            for action in pressed {
+                Self::handle_action(world, action);
+            }
+        }
+    }
+    
+    // Run a keyboard action
+    pub fn handle_action(world: &mut World, action: InputAction) {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let (current_turn, cursor) = match world.get_component::<GameState>(game_state_entity) {
+            Some(game_state) => (game_state.current_turn, game_state.cursor),
+            None => return,
+        };
+        
+        if let Some(direction) = action.cursor_direction() {
+            Self::move_cursor(world, direction);
+            return;
+        }
+        
+        if let Some(slot) = action.ability_slot() {
+            Self::arm_ability_slot(world, slot);
+            return;
+        }
+        
+        match action {
+            InputAction::Activate => {
+                if let Some(entity) = cursor.and_then(|coord| HexMapFactory::get_tile_entity(world, &coord)) {
+                    Self::activate_tile(world, entity, current_turn);
+                }
+            },
+            InputAction::CycleUnits => Self::cycle_units(world, true),
+            InputAction::PreviousUnit => Self::cycle_units(world, false),
+            InputAction::Deselect => Self::deselect_current(world),
+            InputAction::EndTurn => Self::request_end_turn(world),
+            _ => {},
+        }
+    }
+    
+    // Select, move, attack or aim the armed ability at a tile, the same way a click does
+    pub fn activate_tile(world: &mut World, entity: Entity, current_turn: u8) {
+        let (selected_entity, armed_ability) = if let Some(game_state) = world.get_component::<GameState>(HexMapFactory::get_game_state_entity(world).unwrap()) {
+            (game_state.selected_entity, game_state.armed_ability.clone())
+        } else {
+            (None, None)
+        };
+        
+        if let Some(selected) = selected_entity {
+            if let Some(id) = armed_ability {
+                // A marked tile is the ability's target, anything else puts the ability away
+                let ability = AbilitySystem::active(world, selected).into_iter().find(|ability| ability.id == id).cloned();
+                let target = world.get_component::<Position>(entity).map(|pos| pos.coord);
+                match (ability, target) {
+                    (Some(ability), Some(target)) if world.get_component::<InAbilityRange>(entity).is_some() => {
+                        Self::use_ability(world, selected, &ability, target);
+                    },
+                    _ => Self::disarm_ability(world, selected),
+                }
+            } else if selected == entity {
+                // Deselect if clicking on already selected tile
+                Self::deselect_current(world);
+            } else {
+                // Check if in movement or attack range
+                let in_movement_range = world.get_component::<InMovementRange>(entity).is_some();
+                let in_attack_range = world.get_component::<InAttackRange>(entity).is_some();
+                
+                if in_movement_range {
+                    // Move unit
+                    Self::move_unit(world, selected, entity);
+                } else if in_attack_range {
+                    // Attack
+                    Self::attack_unit(world, selected, entity);
+                } else {
+                    // Try to select new tile
+                    Self::deselect_current(world);
+                    Self::select_tile(world, entity, current_turn);
                 }
             }
+        } else {
+            // Try to select new tile
+            Self::select_tile(world, entity, current_turn);
+        }
+        
+        // Keep the keyboard cursor on the tile we acted on
+        if let Some(coord) = world.get_component::<Position>(entity).map(|pos| pos.coord) {
+            Self::set_cursor(world, coord);
+        }
+    }
+    
+    // Move the keyboard cursor one hex in a direction
+    fn move_cursor(world: &mut World, direction: usize) {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let (cursor, selected) = match world.get_component::<GameState>(game_state_entity) {
+            Some(game_state) => (game_state.cursor, game_state.selected_entity),
+            None => return,
+        };
+        
+        // Start from the selected unit (or the hovered tile) if there's no cursor yet
+        let start = cursor.or_else(|| {
+            let game_state = world.get_component::<GameState>(game_state_entity)?;
+            selected.or(game_state.hover_entity)
+                .and_then(|entity| world.get_component::<Position>(entity))
+                .map(|pos| pos.coord)
+        });
+        
+        let target = match start {
+            Some(start) if cursor.is_some() => start.neighbors()[direction % 6],
+            Some(start) => start,
+            None => match world.query::<Position>().first() {
+                Some((_, pos)) => pos.coord,
+                None => return,
+            },
+        };
+        
+        // Stay on the map
+        if HexMapFactory::get_tile_entity(world, &target).is_some() {
+            Self::set_cursor(world, target);
+            CameraSystem::ensure_visible(world, target);
+        }
+    }
+    
+    // Place the keyboard cursor
+    fn set_cursor(world: &mut World, coord: HexCoord) {
+        if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(world) {
+            if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+                game_state.cursor = Some(coord);
+            }
+        }
+    }
+    
+    // Select and centre on the next (or previous) unit of the current team that can still act
+    pub fn cycle_units(world: &mut World, forward: bool) {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let (current_turn, selected) = match world.get_component::<GameState>(game_state_entity) {
+            Some(game_state) => (game_state.current_turn, game_state.selected_entity),
+            None => return,
+        };
+        
+        let idle = HexMapFactory::idle_units(world, current_turn);
+        
+        // The one after (or before) the current selection, wrapping around
+        let next = match (selected, forward) {
+            (Some(selected), true) => idle.iter().find(|&&entity| entity > selected).or(idle.first()),
+            (Some(selected), false) => idle.iter().rev().find(|&&entity| entity < selected).or(idle.last()),
+            (None, true) => idle.first(),
+            (None, false) => idle.last(),
+        };
+        
+        if let Some(&next) = next {
+            Self::deselect_current(world);
+            Self::select_tile(world, next, current_turn);
+            
+            if let Some(coord) = world.get_component::<Position>(next).map(|pos| pos.coord) {
+                Self::set_cursor(world, coord);
+                CameraSystem::centre_on(world, coord);
+            }
         }
     }
     
     // Deselect the currently selected tile
-    fn deselect_current(world: &mut World) {
+    pub fn deselect_current(world: &mut World) {
         // Get game state
         let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
             Some(entity) => entity,
@@ -107,8 +258,23 @@ impl InputSystem {
         // Clear selection in game state
         if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
             game_state.selected_entity = None;
+            game_state.armed_ability = None;
         }
         
+        Self::clear_ranges(world);
+        
+        let selected_entities: Vec<Entity> = world.query::<Selected>()
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect();
+        
+        for entity in selected_entities {
+            world.remove_component::<Selected>(entity);
+        }
+    }
+    
+    // Remove the range and target markers of the selected unit
+    fn clear_ranges(world: &mut World) {
         // Collect all entities with components to remove
         let movement_range_entities: Vec<Entity> = world.query::<InMovementRange>()
             .into_iter()
@@ -120,25 +286,108 @@ impl InputSystem {
             .map(|(entity, _)| entity)
             .collect();
         
-        let selected_entities: Vec<Entity> = world.query::<Selected>()
+        let zone_of_control_entities: Vec<Entity> = world.query::<InZoneOfControl>()
+            .into_iter()
+            .map(|(entity, _)| entity)
+            .collect();
+        
+        let ability_range_entities: Vec<Entity> = world.query::<InAbilityRange>()
             .into_iter()
             .map(|(entity, _)| entity)
             .collect();
         
-        // Remove components (not implemented in our simple ECS)
-        for _entity in movement_range_entities {
-            // world.remove_component::<InMovementRange>(entity);
+        // Remove marker components
+        for entity in movement_range_entities {
+            world.remove_component::<InMovementRange>(entity);
         }
         
-        for _entity in attack_range_entities {
-            // world.remove_component::<InAttackRange>(entity);
+        for entity in attack_range_entities {
+            world.remove_component::<InAttackRange>(entity);
         }
         
-        for _entity in selected_entities {
-            // world.remove_component::<Selected>(entity);
+        for entity in zone_of_control_entities {
+            world.remove_component::<InZoneOfControl>(entity);
+        }
+        
+        for entity in ability_range_entities {
+            world.remove_component::<InAbilityRange>(entity);
+        }
+    }
+    
+    // Arm the ability in a slot of the selected unit's action bar
+    fn arm_ability_slot(world: &mut World, slot: usize) {
+        let selected = match HexMapFactory::get_game_state_entity(world)
+            .and_then(|entity| world.get_component::<GameState>(entity))
+            .and_then(|game_state| game_state.selected_entity)
+        {
+            Some(selected) => selected,
+            None => return,
+        };
+        
+        if let Some(id) = AbilitySystem::active(world, selected).get(slot).map(|ability| ability.id.clone()) {
+            Self::arm_ability(world, &id);
         }
     }
     
+    // Pick an ability for the selected unit. Abilities aimed at the unit itself go off at once, the
+    // others mark their targets and wait for one to be activated; picking it again puts it away.
+    pub fn arm_ability(world: &mut World, id: &str) {
+        let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        let (selected, armed) = match world.get_component::<GameState>(game_state_entity) {
+            Some(game_state) => (game_state.selected_entity, game_state.armed_ability.clone()),
+            None => return,
+        };
+        let selected = match selected {
+            Some(selected) => selected,
+            None => return,
+        };
+        let ability = match AbilitySystem::active(world, selected).into_iter().find(|ability| ability.id == id) {
+            Some(ability) => ability.clone(),
+            None => return,
+        };
+        
+        if armed.as_deref() == Some(id) {
+            Self::disarm_ability(world, selected);
+            return;
+        }
+        if AbilitySystem::blocker(world, selected, &ability).is_some() {
+            return;
+        }
+        
+        let targets = AbilitySystem::targets(world, selected, &ability);
+        if ability.target == AbilityTarget::User {
+            if let Some(&target) = targets.first() {
+                Self::use_ability(world, selected, &ability, target);
+            }
+            return;
+        }
+        
+        // Swap the unit's usual ranges for the ability's targets
+        Self::clear_ranges(world);
+        for coord in targets {
+            if let Some(tile_entity) = HexMapFactory::get_tile_entity(world, &coord) {
+                world.add_component(tile_entity, InAbilityRange);
+            }
+        }
+        if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+            game_state.armed_ability = Some(ability.id);
+        }
+    }
+    
+    // Put the armed ability away and show the selected unit's ranges again
+    fn disarm_ability(world: &mut World, selected: Entity) {
+        if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(world) {
+            if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+                game_state.armed_ability = None;
+            }
+        }
+        Self::clear_ranges(world);
+        Self::calculate_ranges(world, selected);
+    }
+    
     // Try to select a tile
     fn select_tile(world: &mut World, entity: Entity, current_turn: u8) {
         // Check if entity has a unit of the current team
@@ -148,42 +397,89 @@ impl InputSystem {
             false
         };
         
+        // Routing units don't take orders
         let has_movement = if let Some(unit_state) = world.get_component::<UnitState>(entity) {
-            unit_state.movement_left > 0 && !unit_state.has_acted
+            unit_state.movement_left > 0 && !unit_state.has_acted && !unit_state.routing()
         } else {
             false
         };
         
         if team_matches && has_movement {
-            // Add Selected component
-            world.add_component(entity, Selected);
+            Self::select_unit(world, entity);
+        }
+    }
+    
+    // Select a unit and mark its ranges, whether or not it takes orders
+    fn select_unit(world: &mut World, entity: Entity) {
+        // Add Selected component
+        world.add_component(entity, Selected);
+        
+        // Update game state
+        let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
+        if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+            game_state.selected_entity = Some(entity);
+        }
+        
+        // Calculate movement and attack ranges
+        Self::calculate_ranges(world, entity);
+    }
+    
+    // Routing units of a team run as far from the enemy as they can, then wait out the turn
+    fn flee_routing_units(world: &mut World, team_id: u8) {
+        let enemies: Vec<HexCoord> = world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| {
+                HexMapFactory::are_enemies(world, team.team_id, team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+            })
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| position.coord))
+            .collect();
+        let distance_to_enemy = |coord: &HexCoord| enemies.iter().map(|enemy| coord.distance(enemy)).min().unwrap_or(0);
+        
+        for entity in MoraleSystem::routing_units(world, team_id) {
+            let coord = match world.get_component::<Position>(entity) {
+                Some(position) => position.coord,
+                None => continue,
+            };
             
-            // Update game state
-            let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
-            if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
-                game_state.selected_entity = Some(entity);
-            }
+            Self::deselect_current(world);
+            Self::select_unit(world, entity);
+            let destination = world.query::<InMovementRange>()
+                .into_iter()
+                .filter_map(|(tile, _)| world.get_component::<Position>(tile).map(|position| (tile, position.coord)))
+                .max_by_key(|(_, to)| (distance_to_enemy(to), -to.q, -to.r))
+                .filter(|(_, to)| distance_to_enemy(to) > distance_to_enemy(&coord));
             
-            // Calculate movement and attack ranges
-            Self::calculate_ranges(world, entity);
+            let unit = match destination {
+                Some((tile, _)) => {
+                    Self::move_unit(world, entity, tile);
+                    tile
+                },
+                None => entity,
+            };
+            Self::deselect_current(world);
+            if let Some(state) = world.get_component_mut::<UnitState>(unit) {
+                state.movement_left = 0;
+                state.has_acted = true;
+            }
         }
     }
     
     // Calculate movement and attack ranges for the selected entity
     fn calculate_ranges(world: &mut World, entity: Entity) {
         // First get the position and movement points
-        let (coord, movement_points) = if let (Some(position), Some(unit_state)) = (
-            world.get_component::<Position>(entity),
-            world.get_component::<UnitState>(entity)
-        ) {
-            (position.coord, unit_state.movement_left)
+        let coord = if let Some(position) = world.get_component::<Position>(entity) {
+            position.coord
         } else {
             return;
         };
+        let movement_points = Self::movement_points(world, entity);
+        let team_id = world.get_component::<Team>(entity).map_or(0, |team| team.team_id);
         
-        // Calculate attack range
-        let attack_range = if let Some(unit_stats) = world.get_component::<UnitStats>(entity) {
-            let range = unit_stats.range;
+        // Calculate attack range; without ammo only neighbours can be fought
+        let out_of_ammo = RecoverySystem::out_of_ammo(world, entity);
+        let attack_range = if let Some(unit_stats) = StatusSystem::effective_stats(world, entity) {
+            let range = if out_of_ammo { 1 } else { unit_stats.range };
             
             // For simplicity, we'll just use distance for attack range
             Self::calculate_attack_range(world, &coord, range)
@@ -192,23 +488,29 @@ impl InputSystem {
         };
         
         // Calculate movement range
-        let movement_range = Self::calculate_movement_range(world, &coord, movement_points);
+        let movement_range = Self::calculate_movement_range(world, &coord, team_id, movement_points);
         
         // Add components for visualization
-        let hex_entities = HexMapFactory::get_hex_entity_map(world);
-        
         for coord in movement_range {
-            if let Some(&tile_entity) = hex_entities.get(&coord) {
+            if let Some(tile_entity) = HexMapFactory::get_tile_entity(world, &coord) {
                 world.add_component(tile_entity, InMovementRange);
             }
         }
         
+        // Show where enemies will stop this unit
+        if HexMapFactory::get_game_rules(world).zone_of_control && !VeterancySystem::ignores_zoc(world, entity) {
+            for coord in HexMapFactory::zone_of_control(world, team_id) {
+                if let Some(tile_entity) = HexMapFactory::get_tile_entity(world, &coord) {
+                    world.add_component(tile_entity, InZoneOfControl);
+                }
+            }
+        }
+        
         for coord in attack_range {
-            if let Some(&tile_entity) = hex_entities.get(&coord) {
+            if let Some(tile_entity) = HexMapFactory::get_tile_entity(world, &coord) {
                 // Only add attack range if there's an enemy unit
                 let is_enemy = if let Some(team) = world.get_component::<Team>(tile_entity) {
-                    let attacker_team = world.get_component::<Team>(entity).unwrap();
-                    team.team_id != attacker_team.team_id
+                    HexMapFactory::are_enemies(world, team.team_id, team_id)
                 } else {
                     false
                 };
@@ -220,62 +522,89 @@ impl InputSystem {
         }
     }
     
-    // Calculate all hexes within attack range
-    fn calculate_attack_range(world: &World, start: &HexCoord, range: i32) -> HashSet<HexCoord> {
-        let mut attack_hexes = HashSet::new();
-        let hex_entities = HexMapFactory::get_hex_entity_map(world);
-        
-        // For each hex on the map
-        for (coord, _) in &hex_entities {
-            // If it's within range distance
-            if coord.distance(start) <= range && coord != start {
-                attack_hexes.insert(*coord);
-            }
+    // Movement points a unit can still spend this turn, after its status effects.
+    // A unit behind a guard stays put.
+    fn movement_points(world: &World, entity: Entity) -> i32 {
+        let movement = StatusSystem::effective_stats(world, entity).map_or(0, |stats| stats.movement);
+        match world.get_component::<UnitState>(entity) {
+            Some(_) if AbilitySystem::rooted(world, entity) => 0,
+            Some(unit_state) => unit_state.movement_left.min(movement),
+            None => 0,
         }
-        
-        attack_hexes
     }
     
-    // Calculate all hexes reachable with given movement points
-    fn calculate_movement_range(world: &World, start: &HexCoord, movement_points: i32) -> HashSet<HexCoord> {
-        let mut visited = HashMap::new();
-        let mut to_visit = vec![(*start, movement_points)];
-        let mut range = HashSet::new();
-        let hex_entities = HexMapFactory::get_hex_entity_map(world);
+    // Calculate all hexes on the map within attack range
+    fn calculate_attack_range(world: &World, start: &HexCoord, range: i32) -> HashSet<HexCoord> {
+        start.within(range)
+            .into_iter()
+            .filter(|coord| coord != start && HexMapFactory::get_tile_entity(world, coord).is_some())
+            .filter(|coord| HexMapFactory::line_of_sight(world, *start, *coord))
+            .collect()
+    }
+    
+    // Calculate all empty hexes a unit of the team can reach with given movement points
+    fn calculate_movement_range(world: &World, start: &HexCoord, team_id: u8, movement_points: i32) -> HashSet<HexCoord> {
+        Self::movement_costs(world, start, team_id, movement_points)
+            .into_keys()
+            .filter(|coord| coord != start)
+            .filter(|coord| {
+                // Friendly units can be passed through but not stopped on
+                HexMapFactory::get_tile_entity(world, coord)
+                    .and_then(|entity| world.get_component::<UnitState>(entity))
+                    .is_none_or(|state| state.health <= 0)
+            })
+            .collect()
+    }
+    
+    // Best movement points left on arriving at each reachable hex, for the unit standing on start
+    fn movement_costs(world: &World, start: &HexCoord, team_id: u8, movement_points: i32) -> HashMap<HexCoord, i32> {
+        Self::movement_search(world, start, team_id, movement_points)
+            .into_iter()
+            .map(|(coord, (remaining, _))| (coord, remaining))
+            .collect()
+    }
+    
+    // Best movement points left at each reachable hex, with the hex it was entered from.
+    // Enemies and their zones of control are those of the moving unit's team, whoever's turn it is.
+    fn movement_search(world: &World, start: &HexCoord, team_id: u8, movement_points: i32) -> HashMap<HexCoord, (i32, HexCoord)> {
+        let mut visited: HashMap<HexCoord, (i32, HexCoord)> = HashMap::new();
+        let mut to_visit = vec![(*start, movement_points, *start)];
+        let class = Self::movement_class(world, start);
         
-        let current_turn = {
-            let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
-            let game_state = world.get_component::<GameState>(game_state_entity).unwrap();
-            game_state.current_turn
+        // Hexes next to enemy units, if the zone of control rule is on and the unit heeds it
+        let rules = HexMapFactory::get_game_rules(world);
+        let ignores_zoc = HexMapFactory::get_tile_entity(world, start).is_some_and(|entity| VeterancySystem::ignores_zoc(world, entity));
+        let zone_of_control = if rules.zone_of_control && !ignores_zoc {
+            HexMapFactory::zone_of_control(world, team_id)
+        } else {
+            HashSet::new()
         };
         
-        while let Some((current, remaining_movement)) = to_visit.pop() {
+        while let Some((current, remaining_movement, from)) = to_visit.pop() {
             // Skip if we've already found a better path to this hex
-            if let Some(&prev_movement) = visited.get(&current) {
+            if let Some(&(prev_movement, _)) = visited.get(&current) {
                 if prev_movement >= remaining_movement {
                     continue;
                 }
             }
             
-            visited.insert(current, remaining_movement);
+            visited.insert(current, (remaining_movement, from));
             
-            if current != *start {
-                range.insert(current);
-            }
+            // Leaving an enemy's zone of control costs extra
+            let exit_cost = if zone_of_control.contains(&current) { rules.zoc_exit_cost } else { 0 };
             
             // Check each neighbor
             for neighbor in current.neighbors().iter() {
-                if let Some(&entity) = hex_entities.get(neighbor) {
-                    // Get terrain cost
-                    let cost = if let Some(terrain) = world.get_component::<Terrain>(entity) {
-                        terrain.terrain_type.movement_cost()
-                    } else {
-                        continue;
+                if let Some(entity) = HexMapFactory::get_tile_entity(world, neighbor) {
+                    // Get terrain cost, skipping terrain that can't be entered
+                    let cost = match Self::entry_cost(world, entity, &class) {
+                        Some(cost) => cost + exit_cost,
+                        None => continue,
                     };
                     
-                    // Skip if this neighbor has a unit of opposing team
+                    // Skip if this neighbor has a unit of an enemy team; allies can be passed through
                     let has_enemy = if let Some(team) = world.get_component::<Team>(entity) {
-                        team.team_id != current_turn
+                        HexMapFactory::are_enemies(world, team.team_id, team_id)
                     } else {
                         false
                     };
@@ -285,12 +614,52 @@ impl InputSystem {
                         continue;
                     }
                     
-                    to_visit.push((*neighbor, remaining_movement - cost));
+                    // Entering an enemy's zone of control ends movement
+                    let remaining = if zone_of_control.contains(neighbor) { 0 } else { remaining_movement - cost };
+                    to_visit.push((*neighbor, remaining, current));
                 }
             }
         }
         
-        range
+        visited
+    }
+    
+    // Movement class of the unit on a hex
+    fn movement_class(world: &World, coord: &HexCoord) -> String {
+        HexMapFactory::get_tile_entity(world, coord)
+            .and_then(|entity| world.get_component::<UnitStats>(entity))
+            .map_or_else(|| DEFAULT_UNIT_CLASS.to_string(), |stats| stats.class.clone())
+    }
+    
+    // Movement points a unit of `class` spends entering a tile, None if it can't enter
+    fn entry_cost(world: &World, entity: Entity, class: &str) -> Option<i32> {
+        let terrain = world.get_component::<Terrain>(entity)?;
+        let definition = HexMapFactory::get_terrain_catalog(world)?.get(&terrain.terrain_type)?;
+        definition.movement_cost_for(class)
+    }
+    
+    // Cheapest path from start to goal, both included, walking back through the hexes each was entered from
+    fn movement_path(world: &World, start: &HexCoord, goal: &HexCoord, team_id: u8, movement_points: i32) -> Vec<HexCoord> {
+        let reached = Self::movement_search(world, start, team_id, movement_points);
+        let mut path = vec![*goal];
+        let mut current = *goal;
+        
+        while current != *start {
+            match reached.get(&current) {
+                Some(&(_, previous)) if previous != current => {
+                    path.push(previous);
+                    current = previous;
+                },
+                _ => {
+                    // Shouldn't happen for a hex in range, but never loop forever
+                    path.push(*start);
+                    break;
+                },
+            }
+        }
+        
+        path.reverse();
+        path
     }
     
     // Move a unit from one hex to another
@@ -301,7 +670,7 @@ impl InputSystem {
         }
         
         // Get current position of the unit
-        let (_from_coord, _to_coord) = if let (Some(from_pos), Some(to_pos)) = (
+        let (from_coord, to_coord) = if let (Some(from_pos), Some(to_pos)) = (
             world.get_component::<Position>(from_entity),
             world.get_component::<Position>(to_entity)
         ) {
@@ -310,39 +679,35 @@ impl InputSystem {
             return;
         };
         
-        // Calculate movement cost
-        let movement_cost = if let Some(terrain) = world.get_component::<Terrain>(to_entity) {
-            terrain.terrain_type.movement_cost()
-        } else {
-            return;
+        let movement_left = Self::movement_points(world, from_entity);
+        let team_id = world.get_component::<Team>(from_entity).map_or(0, |team| team.team_id);
+        
+        // Calculate movement cost along the cheapest path
+        let movement_cost = match Self::movement_costs(world, &from_coord, team_id, movement_left).get(&to_coord) {
+            Some(&remaining) => movement_left - remaining,
+            None => return,
         };
+        let path = Self::movement_path(world, &from_coord, &to_coord, team_id, movement_left);
         
         // Update unit's movement points
         if let Some(unit_state) = world.get_component_mut::<UnitState>(from_entity) {
-            if unit_state.movement_left < movement_cost {
+            if movement_left < movement_cost {
                 return;
             }
-            unit_state.movement_left -= movement_cost;
+            unit_state.movement_left = movement_left - movement_cost;
+            unit_state.rested = false;
         } else {
             return;
         }
         
-        // Move components from from_entity to to_entity
-        if let Some(unit_stats) = world.get_component::<UnitStats>(from_entity).cloned() {
-            world.add_component(to_entity, unit_stats);
-        }
-        
-        if let Some(unit_state) = world.get_component::<UnitState>(from_entity).cloned() {
-            world.add_component(to_entity, unit_state);
-        }
-        
-        if let Some(team) = world.get_component::<Team>(from_entity).cloned() {
-            world.add_component(to_entity, team);
-        }
+        // Move the unit's components from from_entity to to_entity
+        HexMapFactory::transfer_unit(world, from_entity, to_entity);
+        world.remove_component::<Selected>(from_entity);
+        StatusSystem::moved(world, to_entity);
         
-        // Remove components from original entity
-        // In a real ECS, we'd use world.remove_component<T>(entity)
-        // Here we'll leave them and handle it in the render system
+        // Slide the unit along its path
+        let duration = MOVE_STEP_TIME * (path.len() - 1) as f32;
+        AnimationSystem::play(world, Animation::new(AnimationKind::Move { path }, Some(to_entity), duration));
         
         // Deselect if no more movement
         let should_deselect = if let Some(unit_state) = world.get_component::<UnitState>(to_entity) {
@@ -354,32 +719,15 @@ impl InputSystem {
         if should_deselect {
             Self::deselect_current(world);
         } else {
-            // Update selection and recalculate ranges
+            // Clear the old ranges, then update selection and recalculate them
+            Self::deselect_current(world);
+            
+            world.add_component(to_entity, Selected);
             let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
             if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
                 game_state.selected_entity = Some(to_entity);
             }
             
-            // Collect all entities with components to remove
-            let movement_range_entities: Vec<Entity> = world.query::<InMovementRange>()
-                .into_iter()
-                .map(|(entity, _)| entity)
-                .collect();
-            
-            let attack_range_entities: Vec<Entity> = world.query::<InAttackRange>()
-                .into_iter()
-                .map(|(entity, _)| entity)
-                .collect();
-            
-            // Remove components
-            for _ in movement_range_entities {
-                // world.remove_component::<InMovementRange>(entity);
-            }
-            
-            for _ in attack_range_entities {
-                // world.remove_component::<InAttackRange>(entity);
-            }
-            
             // Recalculate ranges
             Self::calculate_ranges(world, to_entity);
         }
@@ -392,53 +740,217 @@ impl InputSystem {
             return;
         }
         
-        // Get unit stats
-        let (attacker_attack, defender_defense) = if let (Some(attacker_stats), Some(defender_stats)) = (
-            world.get_component::<UnitStats>(attacker_entity),
-            world.get_component::<UnitStats>(defender_entity)
+        let (attacker_coord, defender_coord) = match (
+            world.get_component::<Position>(attacker_entity),
+            world.get_component::<Position>(defender_entity)
         ) {
-            (attacker_stats.attack, defender_stats.defense)
-        } else {
-            return;
+            (Some(attacker_pos), Some(defender_pos)) => (attacker_pos.coord, defender_pos.coord),
+            _ => return,
         };
         
+        // Get unit stats; a charge after moving hits harder
+        let attacker_attack = match StatusSystem::effective_stats(world, attacker_entity) {
+            Some(attacker_stats) => attacker_stats.attack + AbilitySystem::charge_bonus(world, attacker_entity),
+            None => return,
+        };
+        let defender_defense = Self::defense_of(world, defender_entity);
+        
         // Calculate damage
         let damage = std::cmp::max(1, attacker_attack - defender_defense / 2);
         
+        // Mark attacker as has acted; shooting uses up ammo under the supply rule
+        let ranged = attacker_coord.distance(&defender_coord) > 1;
+        let uses_ammo = ranged && HexMapFactory::get_game_rules(world).supply;
+        if let Some(attacker_state) = world.get_component_mut::<UnitState>(attacker_entity) {
+            attacker_state.movement_left = 0;
+            attacker_state.has_acted = true;
+            attacker_state.rested = false;
+            if uses_ammo {
+                attacker_state.ammo = (attacker_state.ammo - 1).max(0);
+            }
+        }
+        
+        // Lunge or shoot, then show the damage
+        let hit_delay = if ranged { ATTACK_TIME } else { ATTACK_TIME * 0.5 };
+        AnimationSystem::play(world, Animation::new(
+            AnimationKind::Attack { from: attacker_coord, to: defender_coord, ranged },
+            Some(attacker_entity),
+            ATTACK_TIME,
+        ));
+        let destroyed = Self::strike(world, attacker_entity, defender_entity, damage, hit_delay);
+        
+        // Being shot at keeps heads down
+        if ranged && !destroyed {
+            StatusSystem::apply(world, defender_entity, SUPPRESSED, RULE_STATUS_TURNS);
+        }
+        
+        // Deselect current unit
+        Self::deselect_current(world);
+    }
+    
+    // Defense of the unit on a tile, with its status effects, terrain and any guard it has up
+    fn defense_of(world: &World, entity: Entity) -> i32 {
+        let defense = StatusSystem::effective_stats(world, entity).map_or(0, |stats| stats.defense);
+        let terrain_bonus = world.get_component::<Position>(entity)
+            .and_then(|position| HexMapFactory::terrain_at(world, position.coord))
+            .map_or(0, |terrain| terrain.defense_bonus);
+        (defense + terrain_bonus + AbilitySystem::defense_bonus(world, entity)).max(0)
+    }
+    
+    // Deal damage to a unit after a delay, scaled by the attacker's morale, and settle what follows:
+    // score, experience, morale and, if it falls, clearing it away and checking for the end of the game.
+    // Returns whether the unit fell.
+    fn strike(world: &mut World, attacker_entity: Entity, defender_entity: Entity, damage: i32, hit_delay: f32) -> bool {
+        let (attacker_coord, defender_coord) = match (world.get_component::<Position>(attacker_entity), world.get_component::<Position>(defender_entity)) {
+            (Some(attacker), Some(defender)) => (attacker.coord, defender.coord),
+            _ => return false,
+        };
+        
+        // Shaken units hit softer
+        let attacker_morale = world.get_component::<UnitState>(attacker_entity).map_or(MAX_MORALE, |state| state.morale);
+        let damage = std::cmp::max(1, (damage as f32 * MoraleSystem::damage_factor(attacker_morale)).round() as i32);
+        
         // Apply damage to defender
-        let defender_destroyed = if let Some(defender_state) = world.get_component_mut::<UnitState>(defender_entity) {
+        let (damage_dealt, defender_destroyed) = if let Some(defender_state) = world.get_component_mut::<UnitState>(defender_entity) {
+            let dealt = damage.min(defender_state.health);
             defender_state.health -= damage;
-            defender_state.health <= 0
+            defender_state.rested = false;
+            (dealt, defender_state.health <= 0)
         } else {
-            false
+            (0, false)
         };
         
-        // Mark attacker as has acted
-        if let Some(attacker_state) = world.get_component_mut::<UnitState>(attacker_entity) {
-            attacker_state.movement_left = 0;
-            attacker_state.has_acted = true;
+        // Only damage that lands counts towards the score
+        if let Some(team) = world.get_component::<Team>(attacker_entity).cloned() {
+            VictorySystem::record_damage(world, team.team_id, damage_dealt);
+        }
+        
+        // The attacker learns from every point of damage, and more from a kill
+        let xp = damage_dealt + if defender_destroyed { KILL_XP } else { 0 };
+        VeterancySystem::gain_xp(world, attacker_entity, xp);
+        
+        AnimationSystem::play(world, Animation::new(
+            AnimationKind::Damage { at: defender_coord, amount: damage },
+            None,
+            DAMAGE_TIME,
+        ).with_delay(hit_delay));
+        
+        // Survivors lose heart, the fallen dishearten their neighbours
+        if !defender_destroyed {
+            MoraleSystem::unit_hit(world, defender_entity, damage_dealt, attacker_coord);
         }
         
         // Check if game is over if defender was destroyed
         if defender_destroyed {
-            // In a real ECS, we would remove components
-            // For now, we'll just set health to 0 and handle in render
+            // Clear the tile and leave a fading ghost behind
+            let defender_team = world.get_component::<Team>(defender_entity).map(|team| team.team_id);
+            let color = match defender_team {
+                Some(team_id) => HexMapFactory::team_color(world, team_id),
+                None => egui::Color32::GRAY,
+            };
+            let was_leader = world.get_component::<Leader>(defender_entity).is_some();
+            HexMapFactory::remove_unit(world, defender_entity);
+            AnimationSystem::play(world, Animation::new(
+                AnimationKind::Death { at: defender_coord, color },
+                None,
+                DEATH_TIME,
+            ).with_delay(hit_delay));
             
-            let (game_over, player_won) = HexMapFactory::check_game_over(world);
+            if let Some(team_id) = defender_team {
+                MoraleSystem::unit_died(world, defender_coord, team_id);
+                VictorySystem::unit_destroyed(world, team_id, was_leader);
+            }
             
-            if game_over {
+            if let Some(winners) = HexMapFactory::check_game_over(world) {
                 let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
                 if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
                     game_state.game_over = true;
-                    game_state.player_won = player_won;
+                    game_state.winners = winners;
                 }
             }
         }
         
-        // Deselect current unit
+        defender_destroyed
+    }
+    
+    // Use an ability at a hex. Like an attack, it takes the rest of the unit's turn.
+    fn use_ability(world: &mut World, user_entity: Entity, ability: &AbilityDefinition, target: HexCoord) {
+        let (user_coord, team_id, attack) = match (
+            world.get_component::<Position>(user_entity),
+            world.get_component::<Team>(user_entity),
+            StatusSystem::effective_stats(world, user_entity)
+        ) {
+            (Some(position), Some(team), Some(stats)) => (position.coord, team.team_id, stats.attack),
+            _ => return,
+        };
+        let affected = AbilitySystem::affected(world, team_id, ability, target);
+        
+        AbilitySystem::used(world, user_entity, ability);
+        if let Some(user_state) = world.get_component_mut::<UnitState>(user_entity) {
+            user_state.movement_left = 0;
+            user_state.has_acted = true;
+            user_state.rested = false;
+        }
+        
+        match ability.effect {
+            AbilityEffect::Damage { power } => {
+                // One shot at the target hex, hitting every enemy around it
+                let ranged = user_coord.distance(&target) > 1;
+                let hit_delay = if ranged { ATTACK_TIME } else { ATTACK_TIME * 0.5 };
+                AnimationSystem::play(world, Animation::new(
+                    AnimationKind::Attack { from: user_coord, to: target, ranged },
+                    Some(user_entity),
+                    ATTACK_TIME,
+                ));
+                
+                let attack = (attack as f32 * power).round() as i32;
+                for &defender_entity in &affected {
+                    let damage = std::cmp::max(1, attack - Self::defense_of(world, defender_entity) / 2);
+                    let destroyed = Self::strike(world, user_entity, defender_entity, damage, hit_delay);
+                    if ranged && !destroyed {
+                        StatusSystem::apply(world, defender_entity, SUPPRESSED, RULE_STATUS_TURNS);
+                    }
+                }
+            },
+            AbilityEffect::Heal { amount } => {
+                for &entity in &affected {
+                    let max_health = world.get_component::<UnitStats>(entity).map_or(0, |stats| stats.max_health);
+                    let healed = match world.get_component_mut::<UnitState>(entity) {
+                        Some(state) => {
+                            let healed = amount.min(max_health - state.health).max(0);
+                            state.health += healed;
+                            healed
+                        },
+                        None => 0,
+                    };
+                    if let Some(at) = world.get_component::<Position>(entity).map(|position| position.coord).filter(|_| healed > 0) {
+                        AnimationSystem::play(world, Animation::new(AnimationKind::Heal { at, amount: healed }, None, DAMAGE_TIME));
+                    }
+                }
+            },
+            // Guards go up in AbilitySystem::used, charges only work when attacking
+            AbilityEffect::Guard { .. } | AbilityEffect::Charge { .. } => {},
+        }
+        
+        // Units still standing keep the ability's status
+        if let Some(status) = &ability.status {
+            for entity in affected {
+                StatusSystem::apply(world, entity, &status.id, status.turns);
+            }
+        }
+        
         Self::deselect_current(world);
     }
     
+    // Ask to end the turn; the app may confirm first if units are still idle
+    pub fn request_end_turn(world: &mut World) {
+        if let Some(game_state_entity) = HexMapFactory::get_game_state_entity(world) {
+            if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+                game_state.end_turn_requested = true;
+            }
+        }
+    }
+    
     // End the current turn
     pub fn end_turn(world: &mut World) {
         // Get game state
@@ -456,18 +968,176 @@ impl InputSystem {
             }
         };
         
-        let next_turn = 1 - current_turn;
+        // Next team in turn order that still has units
+        let living = HexMapFactory::living_teams(world);
+        let (next_turn, new_round) = match HexMapFactory::get_teams(world).and_then(|teams| teams.next_team(current_turn, &living)) {
+            Some(next) => next,
+            None => return,
+        };
         
-        // Update game state
+        // Burning units smoulder, statuses wear off and units that held their ground dig in
+        StatusSystem::end_of_turn(world, current_turn);
+        
+        // Units ending their turn in enemy or neutral settlements take them, then the team is paid
+        SettlementSystem::capture(world, current_turn);
+        EconomySystem::collect(world, current_turn);
+        
+        // The victory conditions may end the game before the next team gets to move
+        let winners = VictorySystem::end_of_turn(world, current_turn, new_round);
         if let Some(game_state) = world.get_component_mut::<GameState>(game_state_entity) {
+            game_state.end_turn_requested = false;
+            if let Some(winners) = winners {
+                game_state.game_over = true;
+                game_state.winners = winners;
+                Self::deselect_current(world);
+                return;
+            }
+            
             game_state.current_turn = next_turn;
-            game_state.turn_number += 1;
+            if new_round {
+                game_state.turn_number += 1;
+            }
         }
         
-        // Reset movement for new team
+        // Units that rested or are in friendly settlements heal, then get their movement back,
+        // as much as their status effects allow
+        RecoverySystem::start_of_turn(world, next_turn);
         HexMapFactory::reset_team_units_for_new_turn(world, next_turn);
+        StatusSystem::start_of_turn(world, next_turn);
+        
+        // Cooldowns count down and guards wear off
+        AbilitySystem::start_of_turn(world, next_turn);
+        
+        // Routing units run first, then everyone regains some nerve
+        Self::flee_routing_units(world, next_turn);
+        MoraleSystem::start_of_turn(world, next_turn);
         
         // Deselect current selection
         Self::deselect_current(world);
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // A single row of plains, so every path is the straight one. Teams 0 and 2 are allied,
+    // and teams 1 and 2 keep a unit at the far end so every team has one.
+    fn world(units: &[(usize, &str, i32)], zone_of_control: bool) -> World {
+        let mut text = format!(
+            "name = \"Test\"\n[rules]\nzone_of_control = {}\n[map]\nshape = \"Parallelogram\"\nwidth = 12\nheight = 1\nterrain = \"plain\"\n",
+            zone_of_control,
+        );
+        let parked = [(1, "infantry", 9), (2, "infantry", 11)];
+        for (team, alliance) in [(0, 1), (1, 0), (2, 1)] {
+            text += &format!("[[team]]\nname = \"{}\"\nalliance = {}\n", team, alliance);
+            for (_, unit_type, q) in units.iter().chain(&parked).filter(|unit| unit.0 == team) {
+                text += &format!("[[team.unit]]\ntype = \"{}\"\nat = [{}, 0]\n", unit_type, q);
+            }
+        }
+        HexMapFactory::test_world(&text)
+    }
+    
+    fn unit(world: &World, q: i32) -> Entity {
+        HexMapFactory::test_tile(world, q)
+    }
+    
+    // Movement points left on reaching each hex, by column, for the unit at a column
+    fn reach(world: &World, q: i32) -> Vec<(i32, i32)> {
+        let entity = unit(world, q);
+        let team_id = world.get_component::<Team>(entity).unwrap().team_id;
+        let costs = InputSystem::movement_costs(world, &HexCoord::new(q, 0), team_id, InputSystem::movement_points(world, entity));
+        let mut reach: Vec<(i32, i32)> = costs.into_iter().map(|(coord, remaining)| (coord.q, remaining)).collect();
+        reach.sort();
+        reach
+    }
+    
+    // Columns marked with a component
+    fn marked<T: 'static>(world: &World) -> Vec<i32> {
+        let mut columns: Vec<i32> = world.query::<T>().into_iter()
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity))
+            .map(|position| position.coord.q)
+            .collect();
+        columns.sort();
+        columns
+    }
+    
+    #[test]
+    fn entering_zone_of_control_ends_movement() {
+        let units = [(0, "cavalry", 0), (1, "infantry", 4)];
+        
+        assert_eq!(reach(&world(&units, false), 0), [(0, 4), (1, 3), (2, 2), (3, 1)]);
+        assert_eq!(reach(&world(&units, true), 0), [(0, 4), (1, 3), (2, 2), (3, 0)]);
+    }
+    
+    #[test]
+    fn leaving_zone_of_control_costs_extra() {
+        let world = world(&[(0, "cavalry", 3), (1, "infantry", 4)], true);
+        let exit_cost = HexMapFactory::get_game_rules(&world).zoc_exit_cost;
+        
+        assert_eq!(reach(&world, 3), [(0, 1 - exit_cost), (1, 2 - exit_cost), (2, 3 - exit_cost), (3, 4)]);
+    }
+    
+    #[test]
+    fn allies_can_be_passed_but_not_stopped_on() {
+        // Its own team at 1, an allied team at 2
+        let allies = world(&[(0, "cavalry", 0), (0, "infantry", 1), (2, "infantry", 2)], true);
+        
+        assert_eq!(reach(&allies, 0), [(0, 4), (1, 3), (2, 2), (3, 1), (4, 0)]);
+        assert_eq!(InputSystem::calculate_movement_range(&allies, &HexCoord::new(0, 0), 0, 4), HashSet::from([HexCoord::new(3, 0), HexCoord::new(4, 0)]));
+        
+        // Enemies block the way
+        let enemies = world(&[(0, "cavalry", 0), (1, "infantry", 1)], false);
+        assert_eq!(reach(&enemies, 0), [(0, 4)]);
+    }
+    
+    #[test]
+    fn ignoring_zone_of_control() {
+        let mut world = world(&[(0, "cavalry", 0), (1, "infantry", 4)], true);
+        world.get_component_mut::<Veterancy>(unit(&world, 0)).unwrap().promotions.push(Promotion::IgnoreZoc);
+        
+        assert_eq!(reach(&world, 0), [(0, 4), (1, 3), (2, 2), (3, 1)]);
+    }
+    
+    #[test]
+    fn ranges_use_the_moving_units_team() {
+        // Looking at team 0's cavalry while it is team 1's turn
+        let mut world = world(&[(0, "cavalry", 0), (1, "infantry", 4)], true);
+        let game_state_entity = HexMapFactory::get_game_state_entity(&world).unwrap();
+        world.get_component_mut::<GameState>(game_state_entity).unwrap().current_turn = 1;
+        
+        let cavalry = unit(&world, 0);
+        InputSystem::calculate_ranges(&mut world, cavalry);
+        assert_eq!(marked::<InMovementRange>(&world), [1, 2, 3]);
+        assert_eq!(marked::<InZoneOfControl>(&world), [3, 5, 8, 10]);
+        assert_eq!(marked::<InAttackRange>(&world), Vec::<i32>::new());
+    }
+    
+    #[test]
+    fn attack_range_needs_line_of_sight() {
+        let world = HexMapFactory::test_world(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 6
+height = 1
+terrain = "plain"
+legend = { "f" = "forest" }
+rows = ["  f"]
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "archer"
+at = [3, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [1, 0]
+"#);
+        let mut columns: Vec<i32> = InputSystem::calculate_attack_range(&world, &HexCoord::new(3, 0), 2).into_iter().map(|coord| coord.q).collect();
+        columns.sort();
+        
+        // Column 1 is behind the forest, the forest itself is in the open
+        assert_eq!(columns, [2, 4, 5]);
+    }
+}
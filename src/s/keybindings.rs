@@ -0,0 +1,211 @@
+use eframe::egui::{InputState, Key};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::c::HexOrientation;
+
+// Everything the player can do from the keyboard
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum InputAction {
+    // Move the keyboard cursor to a neighbor, numbered like HexCoord::neighbors
+    CursorDir0,
+    CursorDir1,
+    CursorDir2,
+    CursorDir3,
+    CursorDir4,
+    CursorDir5,
+    Activate,     // Select, move or attack at the cursor
+    CycleUnits,   // Select the next unit that can still act
+    PreviousUnit, // Select the previous unit that can still act
+    Deselect,
+    EndTurn,
+    CentreOnUnit,
+    PanLeft,
+    PanRight,
+    PanUp,
+    PanDown,
+    ZoomIn,
+    ZoomOut,
+    // Use the selected unit's abilities, numbered like its action bar
+    Ability1,
+    Ability2,
+    Ability3,
+    Ability4,
+}
+
+impl InputAction {
+    pub fn all() -> Vec<InputAction> {
+        vec![
+            InputAction::CursorDir0,
+            InputAction::CursorDir1,
+            InputAction::CursorDir2,
+            InputAction::CursorDir3,
+            InputAction::CursorDir4,
+            InputAction::CursorDir5,
+            InputAction::Activate,
+            InputAction::CycleUnits,
+            InputAction::PreviousUnit,
+            InputAction::Deselect,
+            InputAction::EndTurn,
+            InputAction::CentreOnUnit,
+            InputAction::PanLeft,
+            InputAction::PanRight,
+            InputAction::PanUp,
+            InputAction::PanDown,
+            InputAction::ZoomIn,
+            InputAction::ZoomOut,
+            InputAction::Ability1,
+            InputAction::Ability2,
+            InputAction::Ability3,
+            InputAction::Ability4,
+        ]
+    }
+    
+    // Neighbor index for cursor movement actions
+    pub fn cursor_direction(&self) -> Option<usize> {
+        match self {
+            InputAction::CursorDir0 => Some(0),
+            InputAction::CursorDir1 => Some(1),
+            InputAction::CursorDir2 => Some(2),
+            InputAction::CursorDir3 => Some(3),
+            InputAction::CursorDir4 => Some(4),
+            InputAction::CursorDir5 => Some(5),
+            _ => None,
+        }
+    }
+    
+    // Action bar slot for ability actions
+    pub fn ability_slot(&self) -> Option<usize> {
+        match self {
+            InputAction::Ability1 => Some(0),
+            InputAction::Ability2 => Some(1),
+            InputAction::Ability3 => Some(2),
+            InputAction::Ability4 => Some(3),
+            _ => None,
+        }
+    }
+    
+    // Arrow showing which way a cursor action moves on screen
+    pub fn direction_arrow(direction: usize, orientation: HexOrientation) -> &'static str {
+        let arrows = match orientation {
+            HexOrientation::Flat => ["↘", "↗", "↑", "↖", "↙", "↓"],
+            HexOrientation::Pointy => ["→", "↗", "↖", "←", "↙", "↘"],
+        };
+        arrows[direction % 6]
+    }
+    
+    // Localization key for this action's description
+    pub fn name_key(&self) -> &'static str {
+        match self {
+            InputAction::CursorDir0
+            | InputAction::CursorDir1
+            | InputAction::CursorDir2
+            | InputAction::CursorDir3
+            | InputAction::CursorDir4
+            | InputAction::CursorDir5 => "action-cursor",
+            InputAction::Activate => "action-activate",
+            InputAction::CycleUnits => "action-cycle-units",
+            InputAction::PreviousUnit => "action-previous-unit",
+            InputAction::Deselect => "action-deselect",
+            InputAction::EndTurn => "action-end-turn",
+            InputAction::CentreOnUnit => "action-centre-on-unit",
+            InputAction::PanLeft => "action-pan-left",
+            InputAction::PanRight => "action-pan-right",
+            InputAction::PanUp => "action-pan-up",
+            InputAction::PanDown => "action-pan-down",
+            InputAction::ZoomIn => "action-zoom-in",
+            InputAction::ZoomOut => "action-zoom-out",
+            InputAction::Ability1
+            | InputAction::Ability2
+            | InputAction::Ability3
+            | InputAction::Ability4 => "action-ability",
+        }
+    }
+}
+
+// One saved key binding
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyBinding {
+    pub action: InputAction,
+    pub key: Key,
+}
+
+// Remappable keyboard bindings, one key per action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(from = "Vec<KeyBinding>", into = "Vec<KeyBinding>")]
+pub struct KeyBindings {
+    keys: HashMap<InputAction, Key>,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        let keys = HashMap::from([
+            (InputAction::CursorDir0, Key::D),
+            (InputAction::CursorDir1, Key::E),
+            (InputAction::CursorDir2, Key::W),
+            (InputAction::CursorDir3, Key::Q),
+            (InputAction::CursorDir4, Key::A),
+            (InputAction::CursorDir5, Key::S),
+            (InputAction::Activate, Key::Enter),
+            (InputAction::CycleUnits, Key::Tab),
+            (InputAction::PreviousUnit, Key::Backspace),
+            (InputAction::Deselect, Key::Escape),
+            (InputAction::EndTurn, Key::Space),
+            (InputAction::CentreOnUnit, Key::C),
+            (InputAction::PanLeft, Key::ArrowLeft),
+            (InputAction::PanRight, Key::ArrowRight),
+            (InputAction::PanUp, Key::ArrowUp),
+            (InputAction::PanDown, Key::ArrowDown),
+            (InputAction::ZoomIn, Key::PlusEquals),
+            (InputAction::ZoomOut, Key::Minus),
+            (InputAction::Ability1, Key::Num1),
+            (InputAction::Ability2, Key::Num2),
+            (InputAction::Ability3, Key::Num3),
+            (InputAction::Ability4, Key::Num4),
+        ]);
+        
+        Self { keys }
+    }
+}
+
+impl KeyBindings {
+    // Key currently bound to an action
+    pub fn key(&self, action: InputAction) -> Option<Key> {
+        self.keys.get(&action).copied()
+    }
+    
+    // Bind a key to an action. Any other action using that key loses it.
+    pub fn bind(&mut self, action: InputAction, key: Key) {
+        self.keys.retain(|_, bound| *bound != key);
+        self.keys.insert(action, key);
+    }
+    
+    // Whether the action's key was pressed this frame
+    pub fn pressed(&self, input: &InputState, action: InputAction) -> bool {
+        self.key(action).is_some_and(|key| input.key_pressed(key))
+    }
+    
+    // Whether the action's key is being held down
+    pub fn held(&self, input: &InputState, action: InputAction) -> bool {
+        self.key(action).is_some_and(|key| input.key_down(key))
+    }
+}
+
+impl From<Vec<KeyBinding>> for KeyBindings {
+    fn from(saved: Vec<KeyBinding>) -> Self {
+        // Start from the defaults so actions added later still get a key
+        let mut bindings = KeyBindings::default();
+        for binding in saved {
+            bindings.bind(binding.action, binding.key);
+        }
+        bindings
+    }
+}
+
+impl From<KeyBindings> for Vec<KeyBinding> {
+    fn from(bindings: KeyBindings) -> Self {
+        InputAction::all()
+            .into_iter()
+            .filter_map(|action| bindings.key(action).map(|key| KeyBinding { action, key }))
+            .collect()
+    }
+}
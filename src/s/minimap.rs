@@ -0,0 +1,133 @@
+use crate::e::entity::World;
+use crate::e::factory::HexMapFactory;
+use crate::s::camera::CameraSystem;
+use crate::c::*;
+use eframe::egui::{self, Color32, ColorImage, Pos2, Rect, Stroke, TextureOptions, Vec2};
+
+// System for drawing the minimap and jumping the camera from it
+pub struct MinimapSystem;
+
+impl MinimapSystem {
+    // Draw the minimap into `ui` at the given size; clicking or dragging recentres the board
+    pub fn show(world: &mut World, ui: &mut egui::Ui, size: Vec2) {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let bounds = match CameraSystem::map_bounds(world) {
+            Some(bounds) => bounds,
+            None => return,
+        };
+        
+        let (response, painter) = ui.allocate_painter(size, egui::Sense::click_and_drag());
+        let rect = response.rect;
+        
+        // Scale the whole map to fit, keeping its aspect ratio
+        let scale = (rect.width() / bounds.width()).min(rect.height() / bounds.height());
+        let map_rect = Rect::from_center_size(rect.center(), bounds.size() * scale);
+        
+        // Map layout space <-> minimap space
+        let to_minimap = |pos: Pos2| map_rect.min + (pos - bounds.min) * scale;
+        let from_minimap = |pos: Pos2| bounds.min + (pos - map_rect.min) / scale;
+        
+        painter.rect_filled(rect, 2.0, Color32::from_gray(20));
+        
+        // Terrain from an image that is only redrawn when the terrain or the minimap size changes
+        let pixels_per_point = ui.ctx().pixels_per_point();
+        let image_size = [
+            (map_rect.width() * pixels_per_point).round().max(1.0) as usize,
+            (map_rect.height() * pixels_per_point).round().max(1.0) as usize,
+        ];
+        let revision = world.get_component::<TerrainMesh>(map_settings_entity).map_or(0, |mesh| mesh.revision);
+        let current = world.get_component::<MinimapTexture>(map_settings_entity)
+            .is_some_and(|minimap| minimap.revision == revision && minimap.size == image_size);
+        if !current {
+            let image = Self::terrain_image(world, bounds, image_size);
+            let texture = ui.ctx().load_texture("minimap-terrain", image, TextureOptions::LINEAR);
+            world.add_component(map_settings_entity, MinimapTexture { texture, revision, size: image_size });
+        }
+        if let Some(minimap) = world.get_component::<MinimapTexture>(map_settings_entity) {
+            let uv = Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+            painter.image(minimap.texture.id(), map_rect, uv, Color32::WHITE);
+        }
+        
+        if let Some(settings) = world.get_component::<MapSettings>(map_settings_entity) {
+            let layout = HexLayout::new(
+                settings.layout.orientation,
+                settings.layout.size * scale,
+                to_minimap(settings.layout.origin),
+            );
+            
+            // Units as dots in team colours
+            let dot_radius = (layout.min_size() * 0.7).max(1.5);
+            for (entity, state) in world.query::<UnitState>() {
+                if state.health <= 0 {
+                    continue;
+                }
+                if let (Some(team), Some(position)) = (world.get_component::<Team>(entity), world.get_component::<Position>(entity)) {
+                    let color = HexMapFactory::team_color(world, team.team_id);
+                    painter.circle_filled(position.coord.to_pixel(&layout), dot_radius, color);
+                }
+            }
+        }
+        
+        // Current camera viewport
+        if let Some(camera) = world.get_component::<Camera>(map_settings_entity) {
+            if camera.viewport.is_positive() {
+                let view = Rect::from_two_pos(
+                    to_minimap(camera.screen_to_world(camera.viewport.min)),
+                    to_minimap(camera.screen_to_world(camera.viewport.max)),
+                );
+                painter.rect_stroke(view.intersect(rect), 0.0, Stroke::new(1.5, Color32::WHITE));
+            }
+        }
+        
+        painter.rect_stroke(rect, 2.0, Stroke::new(1.0, Color32::DARK_GRAY));
+        
+        // Click or drag to move the camera there
+        if response.clicked() || response.dragged() {
+            if let Some(pointer) = response.interact_pointer_pos() {
+                let target = from_minimap(pointer);
+                if let Some(camera) = world.get_component_mut::<Camera>(map_settings_entity) {
+                    let centre = camera.viewport.center();
+                    camera.look_at(target, centre);
+                }
+            }
+        }
+    }
+    
+    // Rasterise the map's terrain colours over `bounds` (map layout space) into an image of `size` pixels
+    fn terrain_image(world: &World, bounds: Rect, size: [usize; 2]) -> ColorImage {
+        let mut image = ColorImage::new(size, Color32::TRANSPARENT);
+        
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return image,
+        };
+        let (settings, hex_entities) = match (world.get_component::<MapSettings>(map_settings_entity), world.get_component::<HexEntityMap>(map_settings_entity)) {
+            (Some(settings), Some(hex_entities)) => (settings, &hex_entities.map),
+            _ => return image,
+        };
+        let catalog = world.get_component::<TerrainCatalog>(map_settings_entity);
+        
+        let [width, height] = size;
+        for y in 0..height {
+            for x in 0..width {
+                // Sample the middle of each pixel
+                let pos = bounds.min + Vec2::new(
+                    (x as f32 + 0.5) / width as f32 * bounds.width(),
+                    (y as f32 + 0.5) / height as f32 * bounds.height(),
+                );
+                let color = hex_entities.get(&settings.layout.pixel_to_hex(pos))
+                    .and_then(|&entity| world.get_component::<Terrain>(entity))
+                    .map(|terrain| catalog.and_then(|catalog| catalog.get(&terrain.terrain_type)).map_or(Color32::GRAY, |definition| definition.color()));
+                if let Some(color) = color {
+                    image.pixels[y * width + x] = color;
+                }
+            }
+        }
+        
+        image
+    }
+}
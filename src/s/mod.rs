@@ -2,6 +2,32 @@
 
 mod input;
 mod render;
+mod camera;
+mod minimap;
+mod keybindings;
+mod animation;
+mod ai;
+mod victory;
+mod settlement;
+mod economy;
+mod veterancy;
+mod recovery;
+mod morale;
+mod ability;
+mod status;
 
 pub use input::*;
-pub use render::*; 
\ No newline at end of file
+pub use render::*;
+pub use camera::*;
+pub use minimap::*;
+pub use keybindings::*;
+pub use animation::*;
+pub use ai::*;
+pub use victory::*;
+pub use settlement::*;
+pub use economy::*;
+pub use veterancy::*;
+pub use recovery::*;
+pub use morale::*;
+pub use ability::*;
+pub use status::*; 
\ No newline at end of file
@@ -0,0 +1,227 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::c::*;
+
+// Morale lost on every hit, and on top of that when one hit takes this share of the unit's health or more
+pub const HIT_MORALE_LOSS: i32 = 1;
+pub const HEAVY_HIT_SHARE: f32 = 0.3;
+pub const HEAVY_HIT_MORALE_LOSS: i32 = 2;
+
+// Morale lost when attacked with this many enemies next to the unit besides the attacker
+pub const FLANKING_ENEMIES: usize = 1;
+pub const FLANKED_MORALE_LOSS: i32 = 2;
+
+// Morale lost by units next to an ally that dies
+pub const ALLY_DEATH_MORALE_LOSS: i32 = 2;
+
+// Morale regained at the start of a turn with an ally next to the unit, and with no enemy next to it
+pub const NEAR_FRIENDS_RECOVERY: i32 = 2;
+pub const UNTHREATENED_RECOVERY: i32 = 1;
+
+// Share of its damage a unit deals at zero morale; it rises evenly to all of it at full morale
+pub const BROKEN_DAMAGE_FACTOR: f32 = 0.5;
+
+// System for unit morale: losing heart in the fight, recovering among friends and routing at zero
+pub struct MoraleSystem;
+
+impl MoraleSystem {
+    // Share of its damage a unit deals at a morale
+    pub fn damage_factor(morale: i32) -> f32 {
+        let share = morale.clamp(0, MAX_MORALE) as f32 / MAX_MORALE as f32;
+        BROKEN_DAMAGE_FACTOR + (1.0 - BROKEN_DAMAGE_FACTOR) * share
+    }
+    
+    // A unit was hit by an attacker at a hex; heavy blows and being flanked shake it more
+    pub fn unit_hit(world: &mut World, entity: Entity, damage: i32, attacker: HexCoord) {
+        let max_health = match world.get_component::<UnitStats>(entity) {
+            Some(stats) => stats.max_health,
+            None => return,
+        };
+        let team_id = match world.get_component::<Team>(entity) {
+            Some(team) => team.team_id,
+            None => return,
+        };
+        let coord = match world.get_component::<Position>(entity) {
+            Some(position) => position.coord,
+            None => return,
+        };
+        
+        let mut loss = HIT_MORALE_LOSS;
+        if damage as f32 >= max_health as f32 * HEAVY_HIT_SHARE {
+            loss += HEAVY_HIT_MORALE_LOSS;
+        }
+        let others = coord.neighbors().into_iter()
+            .filter(|&neighbor| neighbor != attacker && Self::unit_team(world, neighbor).is_some_and(|other| HexMapFactory::are_enemies(world, other, team_id)))
+            .count();
+        if others >= FLANKING_ENEMIES {
+            loss += FLANKED_MORALE_LOSS;
+        }
+        Self::change(world, entity, -loss);
+    }
+    
+    // A unit of a team died at a hex, disheartening its friends next to it
+    pub fn unit_died(world: &mut World, at: HexCoord, team_id: u8) {
+        let friends: Vec<Entity> = at.neighbors()
+            .iter()
+            .filter_map(|coord| HexMapFactory::get_tile_entity(world, coord))
+            .filter(|&entity| Self::is_friend(world, entity, team_id))
+            .collect();
+        for entity in friends {
+            Self::change(world, entity, -ALLY_DEATH_MORALE_LOSS);
+        }
+    }
+    
+    // At the start of a team's turn its units regain morale near friends and away from enemies.
+    // Routing units flee before this, so they get one turn of running before they can rally.
+    pub fn start_of_turn(world: &mut World, team_id: u8) {
+        let units: Vec<(Entity, HexCoord)> = world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| team.team_id == team_id && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0))
+            .filter_map(|(entity, _)| world.get_component::<Position>(entity).map(|position| (entity, position.coord)))
+            .collect();
+        
+        for (entity, coord) in units {
+            let mut recovery = 0;
+            if Self::adjacent_units(world, coord, |other| !HexMapFactory::are_enemies(world, other, team_id)) > 0 {
+                recovery += NEAR_FRIENDS_RECOVERY;
+            }
+            if Self::adjacent_units(world, coord, |other| HexMapFactory::are_enemies(world, other, team_id)) == 0 {
+                recovery += UNTHREATENED_RECOVERY;
+            }
+            Self::change(world, entity, recovery);
+        }
+    }
+    
+    // Living units of a team that have lost their nerve, in entity order
+    pub fn routing_units(world: &World, team_id: u8) -> Vec<Entity> {
+        world.query::<UnitState>()
+            .into_iter()
+            .filter(|(entity, state)| {
+                state.health > 0
+                    && state.routing()
+                    && world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id)
+            })
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+    
+    fn change(world: &mut World, entity: Entity, amount: i32) {
+        if let Some(state) = world.get_component_mut::<UnitState>(entity) {
+            state.morale = (state.morale + amount).clamp(0, MAX_MORALE);
+        }
+    }
+    
+    // Whether a tile holds a living unit of the team or an ally
+    fn is_friend(world: &World, entity: Entity, team_id: u8) -> bool {
+        world.get_component::<UnitState>(entity).is_some_and(|state| state.health > 0)
+            && world.get_component::<Team>(entity).is_some_and(|team| !HexMapFactory::are_enemies(world, team.team_id, team_id))
+    }
+    
+    // Team of the living unit at a hex
+    fn unit_team(world: &World, at: HexCoord) -> Option<u8> {
+        let entity = HexMapFactory::get_tile_entity(world, &at)?;
+        world.get_component::<UnitState>(entity).filter(|state| state.health > 0)?;
+        world.get_component::<Team>(entity).map(|team| team.team_id)
+    }
+    
+    // Living units next to a hex whose team passes the filter
+    fn adjacent_units(world: &World, at: HexCoord, filter: impl Fn(u8) -> bool) -> usize {
+        at.neighbors()
+            .into_iter()
+            .filter_map(|coord| Self::unit_team(world, coord))
+            .filter(|&team_id| filter(team_id))
+            .count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Infantry of Blue (team 0) and Red (team 1) at columns of a row of plains
+    fn world(units: &[(u8, i32)]) -> World {
+        let mut text = "name = \"Test\"\n[map]\nshape = \"Parallelogram\"\nwidth = 8\nheight = 1\nterrain = \"plain\"\n".to_string();
+        for (team_id, name) in [(0, "Blue"), (1, "Red")] {
+            text += &format!("[[team]]\nname = \"{}\"\n", name);
+            for (_, q) in units.iter().filter(|unit| unit.0 == team_id) {
+                text += &format!("[[team.unit]]\ntype = \"infantry\"\nat = [{}, 0]\n", q);
+            }
+        }
+        HexMapFactory::test_world(&text)
+    }
+    
+    fn morale(world: &World, q: i32) -> i32 {
+        world.get_component::<UnitState>(HexMapFactory::test_tile(world, q)).unwrap().morale
+    }
+    
+    fn set_morale(world: &mut World, q: i32, morale: i32) {
+        let entity = HexMapFactory::test_tile(world, q);
+        world.get_component_mut::<UnitState>(entity).unwrap().morale = morale;
+    }
+    
+    #[test]
+    fn heavy_hits_cost_more_morale() {
+        let mut world = world(&[(0, 3), (1, 2)]);
+        let target = HexMapFactory::test_tile(&world, 3);
+        
+        // Infantry has 10 health, so 3 damage is a heavy hit
+        MoraleSystem::unit_hit(&mut world, target, 2, HexCoord::new(2, 0));
+        assert_eq!(morale(&world, 3), MAX_MORALE - HIT_MORALE_LOSS);
+        MoraleSystem::unit_hit(&mut world, target, 3, HexCoord::new(2, 0));
+        assert_eq!(morale(&world, 3), MAX_MORALE - 2 * HIT_MORALE_LOSS - HEAVY_HIT_MORALE_LOSS);
+    }
+    
+    #[test]
+    fn flanking_needs_an_enemy_besides_the_attacker() {
+        // A melee attacker alone doesn't flank
+        let mut alone = world(&[(0, 3), (1, 2)]);
+        let target = HexMapFactory::test_tile(&alone, 3);
+        MoraleSystem::unit_hit(&mut alone, target, 1, HexCoord::new(2, 0));
+        assert_eq!(morale(&alone, 3), MAX_MORALE - HIT_MORALE_LOSS);
+        
+        // One on each side does
+        let mut both_sides = world(&[(0, 3), (1, 2), (1, 4)]);
+        let target = HexMapFactory::test_tile(&both_sides, 3);
+        MoraleSystem::unit_hit(&mut both_sides, target, 1, HexCoord::new(2, 0));
+        assert_eq!(morale(&both_sides, 3), MAX_MORALE - HIT_MORALE_LOSS - FLANKED_MORALE_LOSS);
+        
+        // So does shooting at a unit already in a fight
+        let mut shot = world(&[(0, 3), (1, 1), (1, 4)]);
+        let target = HexMapFactory::test_tile(&shot, 3);
+        MoraleSystem::unit_hit(&mut shot, target, 1, HexCoord::new(1, 0));
+        assert_eq!(morale(&shot, 3), MAX_MORALE - HIT_MORALE_LOSS - FLANKED_MORALE_LOSS);
+    }
+    
+    #[test]
+    fn deaths_dishearten_the_neighbours() {
+        let mut world = world(&[(0, 3), (0, 5), (0, 7), (1, 6)]);
+        
+        MoraleSystem::unit_died(&mut world, HexCoord::new(4, 0), 0);
+        assert_eq!(morale(&world, 3), MAX_MORALE - ALLY_DEATH_MORALE_LOSS);
+        assert_eq!(morale(&world, 5), MAX_MORALE - ALLY_DEATH_MORALE_LOSS);
+        assert_eq!(morale(&world, 7), MAX_MORALE);
+        
+        // Enemies next to the fallen don't mind
+        assert_eq!(morale(&world, 6), MAX_MORALE);
+    }
+    
+    #[test]
+    fn morale_comes_back_among_friends_and_away_from_enemies() {
+        let units = [(0, 0), (0, 1), (0, 3), (1, 4), (0, 5), (0, 6)];
+        let mut world = world(&units);
+        for (_, q) in units {
+            set_morale(&mut world, q, 0);
+        }
+        set_morale(&mut world, 6, MAX_MORALE - 1);
+        
+        MoraleSystem::start_of_turn(&mut world, 0);
+        assert_eq!(morale(&world, 0), NEAR_FRIENDS_RECOVERY + UNTHREATENED_RECOVERY);
+        assert_eq!(morale(&world, 3), 0);
+        assert_eq!(morale(&world, 5), NEAR_FRIENDS_RECOVERY);
+        assert_eq!(morale(&world, 6), MAX_MORALE);
+        
+        // Only the team whose turn starts
+        assert_eq!(morale(&world, 4), 0);
+        assert_eq!(MoraleSystem::routing_units(&world, 0), [HexMapFactory::test_tile(&world, 3)]);
+    }
+}
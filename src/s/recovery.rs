@@ -0,0 +1,205 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::s::animation::{AnimationSystem, DAMAGE_TIME};
+use crate::s::settlement::SettlementSystem;
+use crate::c::*;
+
+// Health a unit regains when it neither moved nor fought on its last turn
+pub const REST_HEALING: i32 = 2;
+
+// How close to a friendly settlement a unit must be to refill its ammo under the supply rule
+pub const SUPPLY_RANGE: i32 = 1;
+
+// System for units recovering between turns: resting, healing in settlements and resupplying
+pub struct RecoverySystem;
+
+impl RecoverySystem {
+    // At the start of a team's turn, before its units are made ready again
+    pub fn start_of_turn(world: &mut World, team_id: u8) {
+        let units: Vec<Entity> = world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| team.team_id == team_id && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0))
+            .map(|(entity, _)| entity)
+            .collect();
+        let supply = HexMapFactory::get_game_rules(world).supply;
+        
+        for entity in units {
+            Self::heal(world, entity, team_id);
+            if supply && Self::in_supply(world, entity, team_id) {
+                Self::resupply(world, entity);
+            }
+        }
+    }
+    
+    // Rest and friendly settlements add up, shown as one number
+    fn heal(world: &mut World, entity: Entity, team_id: u8) {
+        let (health, rested) = match world.get_component::<UnitState>(entity) {
+            Some(state) => (state.health, state.rested),
+            None => return,
+        };
+        let max_health = match world.get_component::<UnitStats>(entity) {
+            Some(stats) => stats.max_health,
+            None => return,
+        };
+        
+        let healing = if rested { REST_HEALING } else { 0 } + SettlementSystem::healing_at(world, entity, team_id);
+        let amount = healing.min(max_health - health).max(0);
+        if amount == 0 {
+            return;
+        }
+        
+        if let Some(state) = world.get_component_mut::<UnitState>(entity) {
+            state.health += amount;
+        }
+        if let Some(at) = world.get_component::<Position>(entity).map(|position| position.coord) {
+            AnimationSystem::play(world, Animation::new(AnimationKind::Heal { at, amount }, None, DAMAGE_TIME));
+        }
+    }
+    
+    // Whether a unit is close enough to a friendly settlement to refill
+    fn in_supply(world: &World, entity: Entity, team_id: u8) -> bool {
+        let coord = match world.get_component::<Position>(entity) {
+            Some(position) => position.coord,
+            None => return false,
+        };
+        
+        world.query::<Settlement>()
+            .into_iter()
+            .filter(|(_, settlement)| SettlementSystem::is_friendly(world, settlement, team_id))
+            .filter_map(|(source, _)| world.get_component::<Position>(source))
+            .any(|position| position.coord.distance(&coord) <= SUPPLY_RANGE)
+    }
+    
+    fn resupply(world: &mut World, entity: Entity) {
+        let max_ammo = match world.get_component::<UnitStats>(entity) {
+            Some(stats) => stats.max_ammo,
+            None => return,
+        };
+        if let Some(state) = world.get_component_mut::<UnitState>(entity) {
+            state.ammo = max_ammo;
+        }
+    }
+    
+    // Whether a unit is out of shots and limited to fighting hand to hand
+    pub fn out_of_ammo(world: &World, entity: Entity) -> bool {
+        HexMapFactory::get_game_rules(world).supply
+            && world.get_component::<UnitStats>(entity).is_some_and(|stats| stats.max_ammo > 0)
+            && world.get_component::<UnitState>(entity).is_some_and(|state| state.ammo <= 0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Blue's archers at columns 1 to 3, the first on Blue's fort, and its infantry on Red's village at 4.
+    // Red's infantry waits at 6.
+    fn world(supply: bool) -> World {
+        HexMapFactory::test_world(&format!(r#"
+name = "Test"
+[rules]
+supply = {}
+[map]
+shape = "Parallelogram"
+width = 7
+height = 1
+terrain = "plain"
+[[map.settlement]]
+kind = "fort"
+at = [1, 0]
+owner = 0
+[[map.settlement]]
+kind = "village"
+at = [4, 0]
+owner = 1
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "archer"
+at = [1, 0]
+[[team.unit]]
+type = "archer"
+at = [2, 0]
+[[team.unit]]
+type = "archer"
+at = [3, 0]
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [6, 0]
+"#, supply))
+    }
+    
+    fn state(world: &mut World, q: i32) -> &mut UnitState {
+        let entity = HexMapFactory::test_tile(world, q);
+        world.get_component_mut::<UnitState>(entity).unwrap()
+    }
+    
+    #[test]
+    fn rest_and_friendly_settlements_heal_together() {
+        let mut world = world(false);
+        for q in 1..=4 {
+            state(&mut world, q).health = 1;
+        }
+        state(&mut world, 2).rested = false;
+        
+        RecoverySystem::start_of_turn(&mut world, 0);
+        assert_eq!(state(&mut world, 1).health, 1 + REST_HEALING + SettlementKind::Fort.default_healing());
+        assert_eq!(state(&mut world, 2).health, 1);
+        assert_eq!(state(&mut world, 3).health, 1 + REST_HEALING);
+        
+        // An enemy's village is no help
+        assert_eq!(state(&mut world, 4).health, 1 + REST_HEALING);
+    }
+    
+    #[test]
+    fn healing_stops_at_max_health() {
+        let mut world = world(false);
+        state(&mut world, 1).health = 6;
+        
+        RecoverySystem::start_of_turn(&mut world, 0);
+        assert_eq!(state(&mut world, 1).health, 8);
+        assert_eq!(state(&mut world, 3).health, 8);
+    }
+    
+    #[test]
+    fn ammo_is_refilled_near_friendly_settlements_under_the_supply_rule() {
+        for supply in [false, true] {
+            let mut world = world(supply);
+            for q in 1..=3 {
+                state(&mut world, q).ammo = 0;
+            }
+            
+            // Column 3 is next to a village, but Red's
+            let in_supply: Vec<bool> = (1..=3)
+                .map(|q| RecoverySystem::in_supply(&world, HexMapFactory::test_tile(&world, q), 0))
+                .collect();
+            assert_eq!(in_supply, [true, true, false]);
+            
+            RecoverySystem::start_of_turn(&mut world, 0);
+            let ammo: Vec<i32> = (1..=3).map(|q| state(&mut world, q).ammo).collect();
+            assert_eq!(ammo, if supply { [4, 4, 0] } else { [0, 0, 0] });
+        }
+    }
+    
+    #[test]
+    fn only_units_that_need_ammo_run_out() {
+        let mut unlimited = world(false);
+        let mut world = world(true);
+        let (archer, infantry) = (HexMapFactory::test_tile(&world, 3), HexMapFactory::test_tile(&world, 4));
+        assert!(!RecoverySystem::out_of_ammo(&world, archer));
+        
+        state(&mut world, 3).ammo = 0;
+        state(&mut world, 4).ammo = 0;
+        assert!(RecoverySystem::out_of_ammo(&world, archer));
+        assert!(!RecoverySystem::out_of_ammo(&world, infantry));
+        
+        // Without the rule nobody counts shots
+        state(&mut unlimited, 3).ammo = 0;
+        assert!(!RecoverySystem::out_of_ammo(&unlimited, HexMapFactory::test_tile(&unlimited, 3)));
+    }
+}
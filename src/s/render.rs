@@ -1,13 +1,26 @@
 use crate::e::entity::World;
 use crate::e::factory::HexMapFactory;
+use crate::s::animation::AnimationSystem;
+use crate::s::status::StatusSystem;
 use crate::c::*;
-use eframe::egui::{self, Color32, Pos2, Stroke};
+use eframe::egui::{self, Color32, Mesh, Pos2, Rect, Stroke, Vec2};
+use std::collections::HashMap;
 
 // System for rendering the hex map and game UI
 pub struct RenderSystem;
 
+// Tiles per chunk side when baking the terrain mesh
+const CHUNK_SIZE: i32 = 16;
+
 impl RenderSystem {
-    pub fn render(world: &World, ui: &mut egui::Ui) {
+    pub fn render(world: &mut World, ui: &mut egui::Ui) {
+        // Rebuild the cached terrain if it changed, and its screen copy if the camera moved
+        let visible_rect = ui.clip_rect();
+        Self::update_terrain_mesh(world);
+        Self::update_terrain_view(world, visible_rect);
+        
+        let world = &*world;
+        
         // Get the game state and map settings
         let game_state_entity = match HexMapFactory::get_game_state_entity(world) {
             Some(entity) => entity,
@@ -19,38 +32,52 @@ impl RenderSystem {
             None => return,
         };
         
-        // Get the hex_size and origin from map settings
-        let (hex_size, origin) = {
+        // Get the label settings from map settings
+        let (coord_labels, show_coord_labels) = {
             if let Some(settings) = world.get_component::<MapSettings>(map_settings_entity) {
-                (settings.hex_size, settings.origin)
+                (settings.coord_labels, settings.show_coord_labels)
             } else {
                 return;
             }
         };
         
+        // Layout with camera pan and zoom applied
+        let layout = match HexMapFactory::get_view_layout(world) {
+            Some(layout) => layout,
+            None => return,
+        };
+        
         // Get game state
-        let (game_over, player_won, current_turn) = {
+        let (selected_entity, cursor) = {
             if let Some(game_state) = world.get_component::<GameState>(game_state_entity) {
-                (game_state.game_over, game_state.player_won, game_state.current_turn)
+                (game_state.selected_entity, game_state.cursor)
             } else {
                 return;
             }
         };
         
-        // Get the hex grid entities
-        let hex_entities = HexMapFactory::get_hex_entity_map(world);
-        let selected_entity = {
-            if let Some(game_state) = world.get_component::<GameState>(game_state_entity) {
-                game_state.selected_entity
-            } else {
-                None
+        // Static terrain in one go from the cached screen meshes; egui takes meshes by value, so this is a plain copy
+        if let Some(view) = world.get_component::<TerrainMesh>(map_settings_entity).and_then(|mesh| mesh.view.as_ref()) {
+            for layer in &view.layers {
+                ui.painter().add(egui::Shape::mesh(layer.clone()));
             }
+        }
+        
+        let hex_entities = match world.get_component::<HexEntityMap>(map_settings_entity) {
+            Some(hex_entities) => &hex_entities.map,
+            None => return,
         };
         
-        // Draw each hex tile
-        for (hex_coord, &entity) in &hex_entities {
+        let sprites = world.get_component::<Sprites>(map_settings_entity);
+        
+        // Draw the dynamic layers for each visible tile
+        for hex_coord in Self::visible_coords(&layout, visible_rect) {
+            let entity = match hex_entities.get(&hex_coord) {
+                Some(&entity) => entity,
+                None => continue,
+            };
+            
             // Get components for this hex
-            let terrain = world.get_component::<Terrain>(entity);
             let unit_stats = world.get_component::<UnitStats>(entity);
             let unit_state = world.get_component::<UnitState>(entity);
             let team = world.get_component::<Team>(entity);
@@ -58,61 +85,126 @@ impl RenderSystem {
             let selected = world.get_component::<Selected>(entity).is_some();
             let in_movement_range = world.get_component::<InMovementRange>(entity).is_some();
             let in_attack_range = world.get_component::<InAttackRange>(entity).is_some();
+            let in_zone_of_control = world.get_component::<InZoneOfControl>(entity).is_some();
+            let in_ability_range = world.get_component::<InAbilityRange>(entity).is_some();
+            let objective = world.get_component::<Objective>(entity).is_some();
+            let settlement = world.get_component::<Settlement>(entity);
+            let leader = world.get_component::<Leader>(entity).is_some();
+            let guarding = world.get_component::<Abilities>(entity).is_some_and(|abilities| abilities.guard.is_some());
             
             // Calculate pixel position
-            let pixel_pos = hex_coord.to_pixel(hex_size, origin);
+            let pixel_pos = hex_coord.to_pixel(&layout);
             
-            // Draw hex base
-            Self::draw_hex(
-                ui,
-                pixel_pos,
-                hex_size,
-                terrain.map_or(Color32::GRAY, |t| t.terrain_type.color()),
-                Stroke::new(1.0, Color32::BLACK),
-            );
+            // Settlements get a border in the owner's colour and an icon in the corner
+            if let Some(settlement) = settlement {
+                let owner_color = settlement.owner.map_or(Color32::from_gray(200), |owner| HexMapFactory::team_color(world, owner));
+                Self::draw_hex(
+                    ui,
+                    &layout,
+                    pixel_pos,
+                    0.92,
+                    Color32::TRANSPARENT,
+                    Stroke::new(3.0, owner_color),
+                );
+                
+                let icon_size = layout.min_size() * 0.25;
+                let icon_pos = pixel_pos + Vec2::new(-0.5, -0.5) * layout.min_size();
+                Self::draw_settlement(ui, icon_pos, icon_size, settlement.kind, owner_color);
+            }
+            
+            // Mark objective hexes
+            if objective {
+                Self::draw_hex(
+                    ui,
+                    &layout,
+                    pixel_pos,
+                    0.7,
+                    Color32::from_rgba_premultiplied(120, 100, 0, 60),
+                    Stroke::new(2.0, Color32::GOLD),
+                );
+            }
             
             // Draw movement range
             if in_movement_range {
                 Self::draw_hex(
                     ui,
+                    &layout,
                     pixel_pos,
-                    hex_size * 0.9,
+                    0.9,
                     Color32::from_rgba_premultiplied(0, 255, 0, 100),
                     Stroke::NONE,
                 );
             }
             
+            // Outline hexes where enemies stop movement
+            if in_zone_of_control {
+                Self::draw_hex(
+                    ui,
+                    &layout,
+                    pixel_pos,
+                    0.8,
+                    Color32::TRANSPARENT,
+                    Stroke::new(2.0, Color32::from_rgb(255, 140, 0)),
+                );
+            }
+            
             // Draw attack range
             if in_attack_range {
                 Self::draw_hex(
                     ui,
+                    &layout,
                     pixel_pos,
-                    hex_size * 0.9,
+                    0.9,
                     Color32::from_rgba_premultiplied(255, 0, 0, 100),
                     Stroke::NONE,
                 );
             }
             
+            // Draw where the armed ability can be aimed
+            if in_ability_range {
+                Self::draw_hex(
+                    ui,
+                    &layout,
+                    pixel_pos,
+                    0.9,
+                    Color32::from_rgba_premultiplied(150, 0, 255, 100),
+                    Stroke::new(1.0, Color32::from_rgb(190, 120, 255)),
+                );
+            }
+            
             // Draw unit if present
             if let (Some(stats), Some(state), Some(team_info)) = (unit_stats, unit_state, team) {
                 // Only draw if health > 0
                 if state.health > 0 {
-                    let unit_color = if team_info.team_id == 0 {
-                        Color32::BLUE
-                    } else {
-                        Color32::RED
-                    };
+                    let unit_color = HexMapFactory::team_color(world, team_info.team_id);
+                    let rank = world.get_component::<Veterancy>(entity).map_or(0, |veterancy| veterancy.rank);
+                    let statuses: Vec<(&str, Color32)> = StatusSystem::effects(world, entity)
+                        .into_iter()
+                        .map(|(definition, _)| (definition.symbol.as_str(), definition.color()))
+                        .collect();
+                    
+                    // Animations may be drawing the unit somewhere along its way
+                    let unit_pos = AnimationSystem::unit_position(world, entity, &layout).unwrap_or(pixel_pos);
                     
                     Self::draw_unit(
                         ui,
-                        pixel_pos,
-                        hex_size * 0.6,
+                        unit_pos,
+                        layout.min_size() * 0.6,
                         unit_color,
-                        state.health,
-                        stats.max_health,
-                        state.movement_left,
-                        state.has_acted,
+                        (stats, state),
+                        (rank, &statuses),
+                        sprites.and_then(|sprites| sprites.units.get(&stats.unit_type).copied()),
                     );
+                    
+                    // Ring leaders in gold
+                    if leader {
+                        ui.painter().circle_stroke(unit_pos, layout.min_size() * 0.6 + 3.0, Stroke::new(2.0, Color32::GOLD));
+                    }
+                    
+                    // Ring units with a guard up in steel
+                    if guarding {
+                        ui.painter().circle_stroke(unit_pos, layout.min_size() * 0.6 + 6.0, Stroke::new(2.0, Color32::LIGHT_BLUE));
+                    }
                 }
             }
             
@@ -120,80 +212,259 @@ impl RenderSystem {
             if selected || Some(entity) == selected_entity {
                 Self::draw_hex(
                     ui,
+                    &layout,
                     pixel_pos,
-                    hex_size * 0.8,
+                    0.8,
                     Color32::from_rgba_premultiplied(255, 255, 0, 100),
                     Stroke::new(2.0, Color32::YELLOW),
                 );
             } else if hovering {
                 Self::draw_hex(
                     ui,
+                    &layout,
                     pixel_pos,
-                    hex_size * 0.8,
+                    0.8,
                     Color32::from_rgba_premultiplied(255, 255, 255, 100),
                     Stroke::new(2.0, Color32::WHITE),
                 );
             }
             
+            // Draw keyboard cursor
+            if cursor == Some(hex_coord) {
+                Self::draw_hex(
+                    ui,
+                    &layout,
+                    pixel_pos,
+                    0.95,
+                    Color32::TRANSPARENT,
+                    Stroke::new(3.0, Color32::LIGHT_BLUE),
+                );
+            }
+            
             // Draw coordinates for debugging
-            ui.painter().text(
-                Pos2::new(pixel_pos.x, pixel_pos.y),
-                egui::Align2::CENTER_CENTER,
-                format!("{},{}", hex_coord.q, hex_coord.r),
-                egui::FontId::proportional(10.0),
-                Color32::BLACK,
-            );
+            if show_coord_labels {
+                ui.painter().text(
+                    Pos2::new(pixel_pos.x, pixel_pos.y),
+                    egui::Align2::CENTER_CENTER,
+                    coord_labels.label(hex_coord),
+                    egui::FontId::proportional(10.0),
+                    Color32::BLACK,
+                );
+            }
         }
         
-        // Draw game over message if game is over
-        if game_over {
-            let screen_rect = ui.max_rect();
-            let text = if player_won {
-                "Player Won!"
-            } else {
-                "Enemy Won!"
+        // Projectiles, damage numbers and fading units go over everything on the board
+        AnimationSystem::paint_effects(world, ui, &layout);
+    }
+    
+    // Rebuild the terrain mesh if it has been marked dirty
+    fn update_terrain_mesh(world: &mut World) {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let dirty = world.get_component::<TerrainMesh>(map_settings_entity).is_some_and(|mesh| mesh.dirty);
+        if !dirty {
+            return;
+        }
+        
+        let chunks = Self::build_terrain_chunks(world);
+        let bounds = chunks.iter().fold(Rect::NOTHING, |bounds, chunk| bounds.union(chunk.bounds));
+        if let Some(terrain_mesh) = world.get_component_mut::<TerrainMesh>(map_settings_entity) {
+            terrain_mesh.bounds = Some(bounds).filter(|bounds| bounds.is_positive());
+            terrain_mesh.chunks = chunks;
+            terrain_mesh.dirty = false;
+            terrain_mesh.revision += 1;
+        }
+    }
+    
+    // Move the visible terrain to the screen again if the camera, the board area or the terrain changed
+    fn update_terrain_view(world: &mut World, visible_rect: Rect) {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return,
+        };
+        
+        let (scale, offset) = match world.get_component::<Camera>(map_settings_entity) {
+            Some(camera) => (camera.zoom, camera.offset),
+            None => (1.0, Vec2::ZERO),
+        };
+        
+        let terrain_mesh = match world.get_component_mut::<TerrainMesh>(map_settings_entity) {
+            Some(terrain_mesh) => terrain_mesh,
+            None => return,
+        };
+        
+        let revision = terrain_mesh.revision;
+        let current = terrain_mesh.view.as_ref().is_some_and(|view| {
+            view.revision == revision && view.scale == scale && view.offset == offset && view.visible_rect == visible_rect
+        });
+        if !current {
+            let layers = Self::terrain_layers(&terrain_mesh.chunks, scale, offset, visible_rect);
+            terrain_mesh.view = Some(TerrainView { revision, scale, offset, visible_rect, layers });
+        }
+    }
+    
+    // Bake every tile's fill and outline into chunked meshes, in map layout space
+    fn build_terrain_chunks(world: &World) -> Vec<TerrainChunk> {
+        let map_settings_entity = match HexMapFactory::get_map_settings_entity(world) {
+            Some(entity) => entity,
+            None => return Vec::new(),
+        };
+        
+        let layout = match world.get_component::<MapSettings>(map_settings_entity) {
+            Some(settings) => settings.layout,
+            None => return Vec::new(),
+        };
+        
+        let hex_entities = match world.get_component::<HexEntityMap>(map_settings_entity) {
+            Some(hex_entities) => &hex_entities.map,
+            None => return Vec::new(),
+        };
+        
+        let sprites = world.get_component::<Sprites>(map_settings_entity);
+        let catalog = world.get_component::<TerrainCatalog>(map_settings_entity);
+        
+        // One point wide outline at zoom 1
+        let inner_scale = (1.0 - 1.0 / layout.min_size()).max(0.5);
+        
+        // Texture coordinates of the centre and corners, fitting the hex inside a square image
+        let uv_center = Pos2::new(0.5, 0.5);
+        let uv_corners: [Pos2; 6] = std::array::from_fn(|i| uv_center + layout.corner_offset(i, 0.5) / layout.size);
+        
+        let mut chunks: HashMap<(i32, i32), TerrainChunk> = HashMap::new();
+        for (coord, &entity) in hex_entities {
+            let terrain_type = world.get_component::<Terrain>(entity).map(|t| &t.terrain_type);
+            let texture = terrain_type.and_then(|t| sprites.and_then(|sprites| sprites.terrain.get(t).copied()));
+            let definition = terrain_type.and_then(|t| catalog.and_then(|catalog| catalog.get(t)));
+            
+            // Textured tiles are drawn untinted, flat ones in the terrain colour
+            let (texture_id, color) = match texture {
+                Some(texture_id) => (texture_id, Color32::WHITE),
+                None => (egui::TextureId::default(), definition.map_or(Color32::GRAY, |d| d.color())),
             };
             
-            ui.painter().rect_filled(
-                screen_rect,
-                0.0,
-                Color32::from_rgba_premultiplied(0, 0, 0, 150),
-            );
+            let key = (coord.q.div_euclid(CHUNK_SIZE), coord.r.div_euclid(CHUNK_SIZE));
+            let chunk = chunks.entry(key).or_insert_with(|| TerrainChunk {
+                bounds: Rect::NOTHING,
+                fill: Vec::new(),
+                outline: Mesh::default(),
+            });
             
-            ui.painter().text(
-                screen_rect.center(),
-                egui::Align2::CENTER_CENTER,
-                text,
-                egui::FontId::proportional(32.0),
-                if player_won { Color32::GREEN } else { Color32::RED },
-            );
+            let center = coord.to_pixel(&layout);
+            let outer = layout.corners_at(center, 1.0);
+            let inner = layout.corners_at(center, inner_scale);
+            
+            // Fill as a fan around the centre, in the mesh for this tile's texture
+            let mesh = match chunk.fill.iter().position(|mesh| mesh.texture_id == texture_id) {
+                Some(index) => &mut chunk.fill[index],
+                None => {
+                    chunk.fill.push(Mesh::with_texture(texture_id));
+                    chunk.fill.last_mut().unwrap()
+                },
+            };
+            let base = mesh.vertices.len() as u32;
+            let uv = |pos: Pos2| if texture.is_some() { pos } else { egui::epaint::WHITE_UV };
+            mesh.vertices.push(egui::epaint::Vertex { pos: center, uv: uv(uv_center), color });
+            for (corner, uv_corner) in outer.into_iter().zip(uv_corners) {
+                mesh.vertices.push(egui::epaint::Vertex { pos: corner, uv: uv(uv_corner), color });
+            }
+            for i in 0..6 {
+                mesh.add_triangle(base, base + 1 + i, base + 1 + (i + 1) % 6);
+            }
+            
+            // Outline as a ring between the inner and outer corners
+            let mesh = &mut chunk.outline;
+            let base = mesh.vertices.len() as u32;
+            for i in 0..6 {
+                mesh.colored_vertex(outer[i], Color32::BLACK);
+                mesh.colored_vertex(inner[i], Color32::BLACK);
+            }
+            for i in 0..6 {
+                let (o0, i0) = (base + 2 * i, base + 2 * i + 1);
+                let (o1, i1) = (base + 2 * ((i + 1) % 6), base + 2 * ((i + 1) % 6) + 1);
+                mesh.add_triangle(o0, o1, i0);
+                mesh.add_triangle(i0, o1, i1);
+            }
+            
+            for corner in outer {
+                chunk.bounds.extend_with(corner);
+            }
         }
         
-        // Draw current turn indicator
-        let turn_text = if current_turn == 0 {
-            "Player's Turn"
-        } else {
-            "Enemy's Turn"
-        };
+        chunks.into_values().collect()
+    }
+    
+    // Merge the visible chunks into one screen-space mesh per texture, mapping layout space to the screen
+    // as `pos * scale + offset`. Outlines go last, and are left out once they'd be under half a point.
+    fn terrain_layers(chunks: &[TerrainChunk], scale: f32, offset: Vec2, visible_rect: Rect) -> Vec<Mesh> {
+        let transform = |pos: Pos2| (pos.to_vec2() * scale + offset).to_pos2();
         
-        ui.painter().text(
-            Pos2::new(ui.max_rect().right() - 100.0, ui.max_rect().top() + 20.0),
-            egui::Align2::RIGHT_TOP,
-            turn_text,
-            egui::FontId::proportional(16.0),
-            if current_turn == 0 { Color32::BLUE } else { Color32::RED },
-        );
+        let mut fills: Vec<Mesh> = Vec::new();
+        let mut outline = Mesh::default();
+        for chunk in chunks {
+            let screen_bounds = Rect::from_min_max(transform(chunk.bounds.min), transform(chunk.bounds.max));
+            if !screen_bounds.intersects(visible_rect) {
+                continue;
+            }
+            
+            for fill in &chunk.fill {
+                let index = match fills.iter().position(|mesh| mesh.texture_id == fill.texture_id) {
+                    Some(index) => index,
+                    None => {
+                        fills.push(Mesh::with_texture(fill.texture_id));
+                        fills.len() - 1
+                    },
+                };
+                Self::append_transformed(&mut fills[index], fill, transform);
+            }
+            if scale >= 0.5 {
+                Self::append_transformed(&mut outline, &chunk.outline, transform);
+            }
+        }
+        
+        fills.push(outline);
+        fills.retain(|mesh| !mesh.is_empty());
+        fills
+    }
+    
+    fn append_transformed(target: &mut Mesh, source: &Mesh, transform: impl Fn(Pos2) -> Pos2) {
+        let base = target.vertices.len() as u32;
+        target.indices.extend(source.indices.iter().map(|index| base + index));
+        target.vertices.extend(source.vertices.iter().map(|vertex| egui::epaint::Vertex { pos: transform(vertex.pos), ..*vertex }));
+    }
+    
+    // Coordinates whose tiles could overlap the visible rect
+    fn visible_coords(layout: &HexLayout, visible_rect: Rect) -> Vec<HexCoord> {
+        // Grow by a tile so partly visible hexes at the edges are kept
+        let margin = layout.size.x.max(layout.size.y);
+        let rect = visible_rect.expand(margin);
+        
+        let corners = [rect.left_top(), rect.right_top(), rect.left_bottom(), rect.right_bottom()];
+        let fractional: Vec<(f32, f32)> = corners.iter().map(|&corner| layout.pixel_to_fractional(corner)).collect();
+        
+        let q_min = fractional.iter().map(|f| f.0).fold(f32::INFINITY, f32::min).floor() as i32;
+        let q_max = fractional.iter().map(|f| f.0).fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+        let r_min = fractional.iter().map(|f| f.1).fold(f32::INFINITY, f32::min).floor() as i32;
+        let r_max = fractional.iter().map(|f| f.1).fold(f32::NEG_INFINITY, f32::max).ceil() as i32;
+        
+        let mut coords = Vec::new();
+        for q in q_min..=q_max {
+            for r in r_min..=r_max {
+                let coord = HexCoord::new(q, r);
+                if rect.contains(coord.to_pixel(layout)) {
+                    coords.push(coord);
+                }
+            }
+        }
+        
+        coords
     }
     
-    // Draw a hexagon at the given position
-    fn draw_hex(ui: &mut egui::Ui, center: Pos2, size: f32, fill_color: Color32, stroke: Stroke) {
-        let points = (0..6).map(|i| {
-            let angle = std::f32::consts::PI / 3.0 * i as f32;
-            Pos2::new(
-                center.x + size * angle.cos(),
-                center.y + size * angle.sin(),
-            )
-        }).collect::<Vec<Pos2>>();
+    // Draw a hexagon at the given position, scaled relative to a full tile
+    fn draw_hex(ui: &mut egui::Ui, layout: &HexLayout, center: Pos2, scale: f32, fill_color: Color32, stroke: Stroke) {
+        let points = layout.corners_at(center, scale).to_vec();
         
         ui.painter().add(egui::Shape::convex_polygon(
             points,
@@ -202,12 +473,59 @@ impl RenderSystem {
         ));
     }
     
+    // Draw a settlement icon: a house, a pair of towers or a walled fort
+    fn draw_settlement(ui: &mut egui::Ui, center: Pos2, size: f32, kind: SettlementKind, color: Color32) {
+        let painter = ui.painter();
+        let stroke = Stroke::new(1.0, Color32::BLACK);
+        let rect = |left: f32, top: f32, right: f32, bottom: f32| {
+            Rect::from_min_max(center + Vec2::new(left, top) * size, center + Vec2::new(right, bottom) * size)
+        };
+        
+        match kind {
+            SettlementKind::Village => {
+                painter.rect(rect(-0.5, -0.1, 0.5, 0.6), 0.0, color, stroke);
+                painter.add(egui::Shape::convex_polygon(
+                    vec![
+                        center + Vec2::new(-0.7, -0.1) * size,
+                        center + Vec2::new(0.0, -0.7) * size,
+                        center + Vec2::new(0.7, -0.1) * size,
+                    ],
+                    color,
+                    stroke,
+                ));
+            },
+            SettlementKind::City => {
+                painter.rect(rect(-0.7, -0.6, -0.05, 0.6), 0.0, color, stroke);
+                painter.rect(rect(0.05, -0.2, 0.7, 0.6), 0.0, color, stroke);
+            },
+            SettlementKind::Fort => {
+                painter.rect(rect(-0.7, -0.2, 0.7, 0.6), 0.0, color, stroke);
+                for left in [-0.7, -0.15, 0.4] {
+                    painter.rect(rect(left, -0.5, left + 0.3, -0.2), 0.0, color, stroke);
+                }
+            },
+        }
+    }
+    
     // Draw a unit with health bar
-    fn draw_unit(ui: &mut egui::Ui, center: Pos2, size: f32, color: Color32, health: i32, max_health: i32, movement_left: i32, has_acted: bool) {
+    fn draw_unit(ui: &mut egui::Ui, center: Pos2, size: f32, color: Color32, (stats, state): (&UnitStats, &UnitState), (rank, statuses): (u8, &[(&str, Color32)]), sprite: Option<egui::TextureId>) {
+        let (health, max_health) = (state.health, stats.max_health);
+        let (movement_left, has_acted) = (state.movement_left, state.has_acted);
+        
         // Draw unit circle
         ui.painter().circle_filled(center, size, color);
         ui.painter().circle_stroke(center, size, Stroke::new(1.0, Color32::BLACK));
         
+        // Unit sprite on top of the team colour
+        if let Some(texture_id) = sprite {
+            ui.painter().image(
+                texture_id,
+                Rect::from_center_size(center, Vec2::splat(size * 1.5)),
+                Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0)),
+                Color32::WHITE,
+            );
+        }
+        
         // If unit has acted, draw an X
         if has_acted {
             let size_mult = size * 0.6;
@@ -271,20 +589,171 @@ impl RenderSystem {
             Color32::WHITE,
         );
         
-        // Draw movement indicator
+        // Morale bar, thinner, just under the health bar; a routing unit gets a red empty bar
+        let morale_ratio = state.morale.clamp(0, MAX_MORALE) as f32 / MAX_MORALE as f32;
+        let morale_bar_height = health_bar_height * 0.5;
+        let morale_bar_y = health_bar_y + health_bar_height + 1.0;
+        let morale_bar_left = center.x - health_bar_width / 2.0;
+        ui.painter().rect_filled(
+            egui::Rect::from_min_max(
+                Pos2::new(morale_bar_left, morale_bar_y),
+                Pos2::new(morale_bar_left + health_bar_width, morale_bar_y + morale_bar_height),
+            ),
+            0.0,
+            if state.routing() { Color32::from_rgb(150, 0, 0) } else { Color32::DARK_GRAY },
+        );
+        ui.painter().rect_filled(
+            egui::Rect::from_min_max(
+                Pos2::new(morale_bar_left, morale_bar_y),
+                Pos2::new(morale_bar_left + health_bar_width * morale_ratio, morale_bar_y + morale_bar_height),
+            ),
+            0.0,
+            Color32::from_rgb(80, 160, 255),
+        );
+        
+        // Draw movement indicator, above the sprite if there is one
         if movement_left > 0 {
+            let text_y = if sprite.is_some() { center.y - size * 0.7 } else { center.y };
             ui.painter().text(
-                Pos2::new(center.x, center.y),
+                Pos2::new(center.x, text_y),
                 egui::Align2::CENTER_CENTER,
                 format!("{}MP", movement_left),
                 egui::FontId::proportional(10.0),
                 Color32::WHITE,
             );
         }
+        
+        // One gold chevron per rank, stacked on the unit's left
+        for index in 0..rank {
+            let tip = Pos2::new(center.x - size * 0.95, center.y - size * 0.35 + index as f32 * size * 0.3);
+            let half_width = size * 0.22;
+            ui.painter().add(egui::Shape::line(
+                vec![
+                    Pos2::new(tip.x - half_width, tip.y + half_width * 0.8),
+                    tip,
+                    Pos2::new(tip.x + half_width, tip.y + half_width * 0.8),
+                ],
+                Stroke::new(2.0, Color32::GOLD),
+            ));
+        }
+        
+        // One small icon per status effect, stacked on the unit's right
+        for (index, (symbol, icon_color)) in statuses.iter().enumerate() {
+            let icon_center = Pos2::new(center.x + size * 0.95, center.y - size * 0.5 + index as f32 * size * 0.5);
+            let icon_radius = size * 0.24;
+            ui.painter().circle_filled(icon_center, icon_radius, *icon_color);
+            ui.painter().circle_stroke(icon_center, icon_radius, Stroke::new(1.0, Color32::BLACK));
+            ui.painter().text(
+                icon_center,
+                egui::Align2::CENTER_CENTER,
+                symbol,
+                egui::FontId::proportional(icon_radius * 1.4),
+                Color32::WHITE,
+            );
+        }
     }
     
     // Draw end turn button
     pub fn draw_end_turn_button(ui: &mut egui::Ui) -> bool {
         ui.button("结束回合").clicked()
     }
-} 
\ No newline at end of file
+} 
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::e::mapgen::MapGenConfig;
+    use crate::s::{CameraSystem, InputSystem, KeyBindings, MinimapSystem};
+    use std::time::{Duration, Instant};
+    
+    // A random map of the largest size the settings allow, with two teams
+    fn large_world(size: i32) -> World {
+        let data = GameData::load().unwrap();
+        let teams = Teams::new((0..2u8)
+            .map(|id| {
+                let [r, g, b] = TEAM_COLORS[id as usize];
+                TeamInfo { id, name: id.to_string(), color: Color32::from_rgb(r, g, b), controller: Controller::Human }
+            })
+            .collect());
+        let layout = HexLayout::new(HexOrientation::Flat, egui::vec2(30.0, 30.0), Pos2::ZERO);
+        let mut world = World::new();
+        HexMapFactory::create_map(&mut world, MapShape::RectangleOddQ, (size, size), layout, &MapGenConfig::default(), &data, teams);
+        world
+    }
+    
+    // One frame of the board the way the app lays it out, tessellated like the renderer would
+    fn frame(ctx: &egui::Context, world: &mut World, bindings: &KeyBindings) -> Duration {
+        let start = Instant::now();
+        let input = egui::RawInput {
+            screen_rect: Some(Rect::from_min_size(Pos2::ZERO, egui::vec2(1600.0, 900.0))),
+            ..Default::default()
+        };
+        let output = ctx.run(input, |ctx| {
+            egui::SidePanel::right("controls").show(ctx, |ui| MinimapSystem::show(world, ui, egui::vec2(200.0, 150.0)));
+            egui::CentralPanel::default().show(ctx, |ui| {
+                let (response, _) = ui.allocate_painter(ui.available_size(), egui::Sense::click_and_drag());
+                CameraSystem::update(world, ui, &response, bindings);
+                InputSystem::update(world, ui, &response, bindings);
+                let mut board_ui = ui.child_ui(response.rect, *ui.layout());
+                board_ui.set_clip_rect(response.rect);
+                RenderSystem::render(world, &mut board_ui);
+            });
+        });
+        ctx.tessellate(output.shapes, output.pixels_per_point);
+        start.elapsed()
+    }
+    
+    // Mean and worst of a number of frames, moving the camera by `pan` before each
+    fn frames(ctx: &egui::Context, world: &mut World, count: u32, pan: Vec2) -> (Duration, Duration) {
+        let bindings = KeyBindings::default();
+        let map_settings_entity = HexMapFactory::get_map_settings_entity(world).unwrap();
+        let times: Vec<Duration> = (0..count)
+            .map(|_| {
+                if let Some(camera) = world.get_component_mut::<Camera>(map_settings_entity) {
+                    camera.offset += pan;
+                }
+                frame(ctx, world, &bindings)
+            })
+            .collect();
+        (times.iter().sum::<Duration>() / count, times.into_iter().max().unwrap_or_default())
+    }
+    
+    #[test]
+    fn cached_bounds_match_the_tiles() {
+        let mut world = large_world(12);
+        let walked = CameraSystem::map_bounds(&world).unwrap();
+        
+        RenderSystem::update_terrain_mesh(&mut world);
+        let map_settings_entity = HexMapFactory::get_map_settings_entity(&world).unwrap();
+        assert_eq!(world.get_component::<TerrainMesh>(map_settings_entity).unwrap().bounds, Some(walked));
+        assert_eq!(CameraSystem::map_bounds(&world), Some(walked));
+    }
+    
+    // Frame times on the largest map, zoomed out as far as the camera fits it and at full size.
+    // Run with `cargo test --release -- --ignored --nocapture` to see the numbers.
+    #[test]
+    #[ignore]
+    fn large_map_frame_time() {
+        let budget = Duration::from_secs_f64(1.0 / 60.0);
+        let mut world = large_world(200);
+        let ctx = egui::Context::default();
+        
+        let first = frame(&ctx, &mut world, &KeyBindings::default());
+        println!("200x200, first frame (builds the terrain mesh): {:?}", first);
+        
+        let (mean, worst) = frames(&ctx, &mut world, 120, Vec2::ZERO);
+        println!("200x200, zoomed out to fit, still camera: mean {:?}, worst {:?}", mean, worst);
+        assert!(mean < budget);
+        
+        let (mean, worst) = frames(&ctx, &mut world, 120, egui::vec2(1.0, 0.0));
+        println!("200x200, zoomed out to fit, panning: mean {:?}, worst {:?}", mean, worst);
+        assert!(mean < budget);
+        
+        let map_settings_entity = HexMapFactory::get_map_settings_entity(&world).unwrap();
+        if let Some(camera) = world.get_component_mut::<Camera>(map_settings_entity) {
+            camera.zoom = 1.0;
+        }
+        let (mean, worst) = frames(&ctx, &mut world, 120, egui::vec2(1.0, 0.0));
+        println!("200x200, zoom 1, panning: mean {:?}, worst {:?}", mean, worst);
+        assert!(mean < budget);
+    }
+}
@@ -0,0 +1,150 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::c::*;
+
+// System for settlements changing hands
+pub struct SettlementSystem;
+
+impl SettlementSystem {
+    // At the end of a team's turn it takes every settlement its units stand on from enemies and nobody
+    pub fn capture(world: &mut World, team_id: u8) {
+        let captured: Vec<Entity> = world.query::<Settlement>()
+            .into_iter()
+            .filter(|(entity, settlement)| {
+                world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+                    && settlement.owner.is_none_or(|owner| HexMapFactory::are_enemies(world, owner, team_id))
+            })
+            .map(|(entity, _)| entity)
+            .collect();
+        
+        for entity in captured {
+            if let Some(settlement) = world.get_component_mut::<Settlement>(entity) {
+                settlement.owner = Some(team_id);
+            }
+        }
+    }
+    
+    // Extra health a unit of the team regains at the start of its turn on this tile, if it's a friendly settlement
+    pub fn healing_at(world: &World, entity: Entity, team_id: u8) -> i32 {
+        world.get_component::<Settlement>(entity)
+            .filter(|settlement| Self::is_friendly(world, settlement, team_id))
+            .map_or(0, |settlement| settlement.healing)
+    }
+    
+    // Whether a settlement belongs to the team or one of its allies
+    pub fn is_friendly(world: &World, settlement: &Settlement, team_id: u8) -> bool {
+        settlement.owner.is_some_and(|owner| !HexMapFactory::are_enemies(world, owner, team_id))
+    }
+    
+    // Settlements a team owns
+    pub fn owned_by(world: &World, team_id: u8) -> Vec<&Settlement> {
+        world.query::<Settlement>()
+            .into_iter()
+            .filter(|(_, settlement)| settlement.owner == Some(team_id))
+            .map(|(_, settlement)| settlement)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Blue and Green allied against Red. Along the row: a village of Blue's with Red standing in it,
+    // a neutral village with Blue in it, Blue's fort with Green in it, Red's city, and a neutral
+    // village with Red in it
+    fn world() -> World {
+        HexMapFactory::test_world(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 6
+height = 1
+terrain = "plain"
+[[map.settlement]]
+kind = "village"
+at = [0, 0]
+owner = 0
+[[map.settlement]]
+kind = "village"
+at = [1, 0]
+[[map.settlement]]
+kind = "fort"
+at = [2, 0]
+owner = 0
+[[map.settlement]]
+kind = "city"
+at = [3, 0]
+owner = 2
+[[map.settlement]]
+kind = "village"
+at = [4, 0]
+[[team]]
+name = "Blue"
+alliance = 1
+[[team.unit]]
+type = "infantry"
+at = [1, 0]
+[[team]]
+name = "Green"
+alliance = 1
+[[team.unit]]
+type = "infantry"
+at = [2, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [0, 0]
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+"#)
+    }
+    
+    // Owner of each settlement along the row
+    fn owners(world: &World) -> Vec<Option<u8>> {
+        (0..5).map(|q| world.get_component::<Settlement>(HexMapFactory::test_tile(world, q)).unwrap().owner).collect()
+    }
+    
+    #[test]
+    fn settlements_change_hands_when_an_enemy_ends_its_turn_there() {
+        let mut world = world();
+        
+        // Blue takes the neutral village it stands in, but Red's units wait for Red's own turn
+        SettlementSystem::capture(&mut world, 0);
+        assert_eq!(owners(&world), [Some(0), Some(0), Some(0), Some(2), None]);
+        
+        // Green leaves its ally's fort alone
+        SettlementSystem::capture(&mut world, 1);
+        assert_eq!(owners(&world), [Some(0), Some(0), Some(0), Some(2), None]);
+        
+        SettlementSystem::capture(&mut world, 2);
+        assert_eq!(owners(&world), [Some(2), Some(0), Some(0), Some(2), Some(2)]);
+        assert_eq!(SettlementSystem::owned_by(&world, 2).len(), 3);
+    }
+    
+    #[test]
+    fn dead_units_capture_nothing() {
+        let mut world = world();
+        let tile = HexMapFactory::test_tile(&world, 1);
+        world.get_component_mut::<UnitState>(tile).unwrap().health = 0;
+        
+        SettlementSystem::capture(&mut world, 0);
+        assert_eq!(owners(&world)[1], None);
+    }
+    
+    #[test]
+    fn units_heal_in_their_own_and_allied_settlements() {
+        let world = world();
+        let healing = |q: i32, team_id: u8| SettlementSystem::healing_at(&world, HexMapFactory::test_tile(&world, q), team_id);
+        
+        assert_eq!((healing(0, 0), healing(0, 1), healing(0, 2)), (2, 2, 0));
+        assert_eq!((healing(2, 0), healing(2, 1), healing(2, 2)), (4, 4, 0));
+        assert_eq!((healing(3, 0), healing(3, 2)), (0, 2));
+        
+        // Neutral villages and open ground don't heal anyone
+        assert_eq!((healing(1, 0), healing(4, 2), healing(5, 0)), (0, 0, 0));
+    }
+}
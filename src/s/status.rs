@@ -0,0 +1,259 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::s::animation::{AnimationSystem, DAMAGE_TIME};
+use crate::c::*;
+
+// Status effects the rules hand out themselves, by id; any that data/statuses.toml leaves out are skipped
+pub const ENTRENCHED: &str = "entrenched"; // Units that held their ground for a whole turn
+pub const SUPPRESSED: &str = "suppressed"; // Units that survive ranged fire
+pub const INSPIRED: &str = "inspired";     // Units that end their turn next to their leader
+
+// Own turns the statuses above last
+pub const RULE_STATUS_TURNS: i32 = 1;
+
+// System for timed status effects: gaining them, what they do to a unit's stats and wearing off
+pub struct StatusSystem;
+
+impl StatusSystem {
+    // Give a unit a status for a number of its own turns
+    pub fn apply(world: &mut World, entity: Entity, id: &str, turns: i32) {
+        let definition = match HexMapFactory::get_status_catalog(world).and_then(|catalog| catalog.get(id)) {
+            Some(definition) => definition.clone(),
+            None => return,
+        };
+        if world.get_component::<UnitState>(entity).is_none_or(|state| state.health <= 0) {
+            return;
+        }
+        
+        if let Some(statuses) = world.get_component_mut::<StatusEffects>(entity) {
+            statuses.apply(&definition, turns);
+        }
+    }
+    
+    // A unit's stats with its status effects applied, for moving and fighting
+    pub fn effective_stats(world: &World, entity: Entity) -> Option<UnitStats> {
+        let mut stats = world.get_component::<UnitStats>(entity)?.clone();
+        for (definition, effect) in Self::effects(world, entity) {
+            stats.attack += definition.attack * effect.stacks;
+            stats.defense += definition.defense * effect.stacks;
+            stats.movement += definition.movement * effect.stacks;
+            stats.range += definition.range * effect.stacks;
+        }
+        
+        stats.attack = stats.attack.max(0);
+        stats.defense = stats.defense.max(0);
+        stats.movement = stats.movement.max(0);
+        stats.range = stats.range.max(1);
+        Some(stats)
+    }
+    
+    // A unit's status effects with their definitions, in the order they were gained
+    pub fn effects(world: &World, entity: Entity) -> Vec<(&StatusDefinition, &StatusEffect)> {
+        match (HexMapFactory::get_status_catalog(world), world.get_component::<StatusEffects>(entity)) {
+            (Some(catalog), Some(statuses)) => statuses.effects.iter()
+                .filter_map(|effect| catalog.get(&effect.id).map(|definition| (definition, effect)))
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+    
+    // At the start of a team's turn its units get the movement their statuses allow,
+    // so a status can add movement as well as take it away
+    pub fn start_of_turn(world: &mut World, team_id: u8) {
+        let units: Vec<(Entity, i32)> = world.query::<Team>()
+            .into_iter()
+            .filter(|(_, team)| team.team_id == team_id)
+            .filter_map(|(entity, _)| Self::effective_stats(world, entity).map(|stats| (entity, stats.movement)))
+            .collect();
+        
+        for (entity, movement) in units {
+            if let Some(state) = world.get_component_mut::<UnitState>(entity) {
+                state.movement_left = movement;
+            }
+        }
+    }
+    
+    // A unit left its hex, giving up any status that only lasts while it stays put
+    pub fn moved(world: &mut World, entity: Entity) {
+        let lost: Vec<String> = Self::effects(world, entity)
+            .into_iter()
+            .filter(|(definition, _)| definition.ends_on_move)
+            .map(|(definition, _)| definition.id.clone())
+            .collect();
+        
+        if let Some(statuses) = world.get_component_mut::<StatusEffects>(entity) {
+            statuses.effects.retain(|effect| !lost.contains(&effect.id));
+        }
+    }
+    
+    // At the end of a team's turn its units take damage over time, their statuses count down,
+    // and those that held their ground or stand by their leader gain new ones
+    pub fn end_of_turn(world: &mut World, team_id: u8) {
+        let units: Vec<Entity> = world.query::<Team>()
+            .into_iter()
+            .filter(|(entity, team)| team.team_id == team_id && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0))
+            .map(|(entity, _)| entity)
+            .collect();
+        
+        for &entity in &units {
+            Self::take_damage(world, entity);
+            if let Some(statuses) = world.get_component_mut::<StatusEffects>(entity) {
+                statuses.effects.retain_mut(|effect| {
+                    effect.turns_left -= 1;
+                    effect.turns_left > 0
+                });
+            }
+        }
+        
+        for entity in units {
+            if world.get_component::<UnitState>(entity).is_some_and(|state| state.rested) {
+                Self::apply(world, entity, ENTRENCHED, RULE_STATUS_TURNS);
+            }
+            if Self::next_to_leader(world, entity, team_id) {
+                Self::apply(world, entity, INSPIRED, RULE_STATUS_TURNS);
+            }
+        }
+    }
+    
+    // Damage over time from a unit's statuses, which wears it down but never finishes it off
+    fn take_damage(world: &mut World, entity: Entity) {
+        let damage: i32 = Self::effects(world, entity)
+            .iter()
+            .map(|(definition, effect)| definition.damage * effect.stacks)
+            .sum();
+        if damage <= 0 {
+            return;
+        }
+        
+        let amount = match world.get_component_mut::<UnitState>(entity) {
+            Some(state) => {
+                let amount = damage.min(state.health - 1).max(0);
+                state.health -= amount;
+                amount
+            },
+            None => return,
+        };
+        if let Some(at) = world.get_component::<Position>(entity).map(|position| position.coord).filter(|_| amount > 0) {
+            AnimationSystem::play(world, Animation::new(AnimationKind::Damage { at, amount }, None, DAMAGE_TIME));
+        }
+    }
+    
+    // Whether a unit stands next to a living leader of its own team
+    fn next_to_leader(world: &World, entity: Entity, team_id: u8) -> bool {
+        let coord = match world.get_component::<Position>(entity) {
+            Some(position) => position.coord,
+            None => return false,
+        };
+        
+        coord.neighbors()
+            .iter()
+            .filter_map(|neighbor| HexMapFactory::get_tile_entity(world, neighbor))
+            .any(|tile| {
+                world.get_component::<Leader>(tile).is_some()
+                    && world.get_component::<Team>(tile).is_some_and(|team| team.team_id == team_id)
+                    && world.get_component::<UnitState>(tile).is_some_and(|state| state.health > 0)
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Team 0's infantry at column 0 and team 1's at column 4 of a row of plains,
+    // with a status that adds movement alongside the bundled ones
+    fn world() -> World {
+        let mut data = GameData::load().unwrap();
+        let extra = StatusCatalog::parse(r#"
+[[status]]
+id = "forced_march"
+name = "status-forced-march"
+description = "status-forced-march-description"
+symbol = "»"
+color = [0, 0, 0]
+movement = 2
+"#, "test.toml").unwrap();
+        data.statuses.statuses.extend(extra.statuses);
+        
+        let text = r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 6
+height = 1
+terrain = "plain"
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "infantry"
+at = [0, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+"#;
+        HexMapFactory::test_world_with(text, &data)
+    }
+    
+    fn unit(world: &World, q: i32) -> Entity {
+        HexMapFactory::test_tile(world, q)
+    }
+    
+    // Status ids with their turns left and stacks
+    fn statuses(world: &World, entity: Entity) -> Vec<(String, i32, i32)> {
+        world.get_component::<StatusEffects>(entity).unwrap().effects.iter()
+            .map(|effect| (effect.id.clone(), effect.turns_left, effect.stacks))
+            .collect()
+    }
+    
+    #[test]
+    fn end_of_turn_counts_down_and_removes() {
+        let mut world = world();
+        let (blue, red) = (unit(&world, 0), unit(&world, 4));
+        // Moving this turn keeps the unit from digging in
+        world.get_component_mut::<UnitState>(blue).unwrap().rested = false;
+        StatusSystem::apply(&mut world, blue, "slowed", 2);
+        StatusSystem::apply(&mut world, blue, "inspired", 1);
+        StatusSystem::apply(&mut world, red, "slowed", 1);
+        
+        StatusSystem::end_of_turn(&mut world, 0);
+        assert_eq!(statuses(&world, blue), [("slowed".to_string(), 1, 1)]);
+        // Only the team whose turn ended counts down
+        assert_eq!(statuses(&world, red), [("slowed".to_string(), 1, 1)]);
+        
+        StatusSystem::end_of_turn(&mut world, 0);
+        assert_eq!(statuses(&world, blue), []);
+    }
+    
+    #[test]
+    fn damage_over_time_by_stacks() {
+        let mut world = world();
+        let blue = unit(&world, 0);
+        world.get_component_mut::<UnitState>(blue).unwrap().rested = false;
+        for _ in 0..4 {
+            StatusSystem::apply(&mut world, blue, "burning", 2);
+        }
+        let health = world.get_component::<UnitState>(blue).unwrap().health;
+        
+        StatusSystem::end_of_turn(&mut world, 0);
+        assert_eq!(world.get_component::<UnitState>(blue).unwrap().health, health - 3);
+        assert_eq!(statuses(&world, blue), [("burning".to_string(), 1, 3)]);
+    }
+    
+    #[test]
+    fn start_of_turn_movement_follows_statuses() {
+        let mut world = world();
+        let (blue, red) = (unit(&world, 0), unit(&world, 4));
+        let movement = world.get_component::<UnitStats>(blue).unwrap().movement;
+        StatusSystem::apply(&mut world, blue, "forced_march", 1);
+        StatusSystem::apply(&mut world, red, "slowed", 1);
+        
+        for team_id in [0, 1] {
+            HexMapFactory::reset_team_units_for_new_turn(&mut world, team_id);
+            StatusSystem::start_of_turn(&mut world, team_id);
+        }
+        assert_eq!(world.get_component::<UnitState>(blue).unwrap().movement_left, movement + 2);
+        assert_eq!(world.get_component::<UnitState>(red).unwrap().movement_left, movement - 1);
+    }
+}
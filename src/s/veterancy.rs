@@ -0,0 +1,195 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::c::*;
+
+// System for units gaining experience, ranking up and picking promotions
+pub struct VeterancySystem;
+
+impl VeterancySystem {
+    // Give a unit experience, ranking it up as many times as it has earned
+    pub fn gain_xp(world: &mut World, entity: Entity, xp: i32) {
+        let ranks = match world.get_component_mut::<Veterancy>(entity) {
+            Some(veterancy) => {
+                veterancy.xp += xp;
+                let before = veterancy.rank;
+                while veterancy.next_rank_xp().is_some_and(|needed| veterancy.xp >= needed) {
+                    veterancy.rank += 1;
+                }
+                veterancy.rank - before
+            },
+            None => return,
+        };
+        
+        for _ in 0..ranks {
+            if let Some(stats) = world.get_component_mut::<UnitStats>(entity) {
+                stats.attack += RANK_ATTACK;
+                stats.max_health += RANK_HEALTH;
+            }
+            if let Some(state) = world.get_component_mut::<UnitState>(entity) {
+                state.health += RANK_HEALTH;
+            }
+        }
+    }
+    
+    // Promotions a unit could pick now, in the order its definition lists them
+    pub fn options(world: &World, entity: Entity) -> Vec<Promotion> {
+        let veterancy = match world.get_component::<Veterancy>(entity) {
+            Some(veterancy) if veterancy.pending() > 0 => veterancy,
+            _ => return Vec::new(),
+        };
+        let definition = match world.get_component::<UnitStats>(entity)
+            .and_then(|stats| HexMapFactory::get_unit_catalog(world)?.get(&stats.unit_type))
+        {
+            Some(definition) => definition,
+            None => return Vec::new(),
+        };
+        
+        definition.promotions.iter()
+            .filter(|promotion| promotion.repeatable() || !veterancy.has(**promotion))
+            .copied()
+            .collect()
+    }
+    
+    // Pick a promotion for a unit with a rank to spend
+    pub fn promote(world: &mut World, entity: Entity, promotion: Promotion) -> bool {
+        if !Self::options(world, entity).contains(&promotion) {
+            return false;
+        }
+        
+        if let Some(veterancy) = world.get_component_mut::<Veterancy>(entity) {
+            veterancy.promotions.push(promotion);
+        }
+        let (mut stats, mut state) = match (world.get_component::<UnitStats>(entity), world.get_component::<UnitState>(entity)) {
+            (Some(stats), Some(state)) => (stats.clone(), state.clone()),
+            _ => return false,
+        };
+        promotion.apply(&mut stats, &mut state);
+        world.add_component(entity, stats);
+        world.add_component(entity, state);
+        true
+    }
+    
+    // Living units of a team with a promotion to pick, in entity order
+    pub fn awaiting_promotion(world: &World, team_id: u8) -> Vec<Entity> {
+        world.query::<Veterancy>()
+            .into_iter()
+            .filter(|(entity, veterancy)| {
+                veterancy.pending() > 0
+                    && world.get_component::<Team>(*entity).is_some_and(|team| team.team_id == team_id)
+                    && world.get_component::<UnitState>(*entity).is_some_and(|state| state.health > 0)
+            })
+            .map(|(entity, _)| entity)
+            .collect()
+    }
+    
+    // Whether the unit on a tile moves freely through enemy zones of control
+    pub fn ignores_zoc(world: &World, entity: Entity) -> bool {
+        world.get_component::<Veterancy>(entity).is_some_and(|veterancy| veterancy.has(Promotion::IgnoreZoc))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Team 0's cavalry at column 0 and team 1's infantry at column 2
+    fn world() -> World {
+        HexMapFactory::test_world(r#"
+name = "Test"
+[map]
+shape = "Parallelogram"
+width = 3
+height = 1
+terrain = "plain"
+[[team]]
+name = "Blue"
+[[team.unit]]
+type = "cavalry"
+at = [0, 0]
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [2, 0]
+"#)
+    }
+    
+    fn veterancy(world: &World, entity: Entity) -> Veterancy {
+        world.get_component::<Veterancy>(entity).unwrap().clone()
+    }
+    
+    #[test]
+    fn experience_below_the_next_rank_only_adds_up() {
+        let mut world = world();
+        let cavalry = HexMapFactory::test_tile(&world, 0);
+        
+        VeterancySystem::gain_xp(&mut world, cavalry, 5);
+        VeterancySystem::gain_xp(&mut world, cavalry, 2);
+        
+        let veterancy = veterancy(&world, cavalry);
+        assert_eq!((veterancy.xp, veterancy.rank), (7, 0));
+        assert_eq!(world.get_component::<UnitStats>(cavalry).unwrap().attack, 5);
+        assert!(VeterancySystem::options(&world, cavalry).is_empty());
+    }
+    
+    #[test]
+    fn one_big_gain_can_cross_several_ranks() {
+        let mut world = world();
+        let cavalry = HexMapFactory::test_tile(&world, 0);
+        world.get_component_mut::<UnitState>(cavalry).unwrap().health = 5;
+        
+        VeterancySystem::gain_xp(&mut world, cavalry, RANK_XP[1]);
+        
+        let ranked = veterancy(&world, cavalry);
+        assert_eq!((ranked.xp, ranked.rank, ranked.pending()), (RANK_XP[1], 2, 2));
+        let stats = world.get_component::<UnitStats>(cavalry).unwrap();
+        assert_eq!((stats.attack, stats.max_health), (5 + 2 * RANK_ATTACK, 12 + 2 * RANK_HEALTH));
+        assert_eq!(world.get_component::<UnitState>(cavalry).unwrap().health, 5 + 2 * RANK_HEALTH);
+        
+        // The top rank is as far as it goes
+        VeterancySystem::gain_xp(&mut world, cavalry, 100);
+        assert_eq!(veterancy(&world, cavalry).rank as usize, RANK_XP.len());
+        assert_eq!(veterancy(&world, cavalry).next_rank_xp(), None);
+    }
+    
+    #[test]
+    fn options_leave_out_promotions_that_only_count_once() {
+        let mut world = world();
+        let cavalry = HexMapFactory::test_tile(&world, 0);
+        VeterancySystem::gain_xp(&mut world, cavalry, RANK_XP[1]);
+        assert_eq!(VeterancySystem::options(&world, cavalry), [Promotion::Attack, Promotion::Movement, Promotion::IgnoreZoc]);
+        
+        assert!(VeterancySystem::promote(&mut world, cavalry, Promotion::Movement));
+        assert_eq!(VeterancySystem::options(&world, cavalry), [Promotion::Attack, Promotion::IgnoreZoc]);
+        
+        assert!(VeterancySystem::promote(&mut world, cavalry, Promotion::Attack));
+        assert!(VeterancySystem::options(&world, cavalry).is_empty());
+    }
+    
+    #[test]
+    fn promoting_applies_the_choice_once_per_rank() {
+        let mut world = world();
+        let cavalry = HexMapFactory::test_tile(&world, 0);
+        
+        // Nothing to spend yet
+        assert!(!VeterancySystem::promote(&mut world, cavalry, Promotion::Attack));
+        
+        VeterancySystem::gain_xp(&mut world, cavalry, RANK_XP[0]);
+        assert_eq!(VeterancySystem::awaiting_promotion(&world, 0), [cavalry]);
+        
+        // Only what the unit's definition offers
+        assert!(!VeterancySystem::promote(&mut world, cavalry, Promotion::Range));
+        assert!(VeterancySystem::promote(&mut world, cavalry, Promotion::IgnoreZoc));
+        assert!(VeterancySystem::ignores_zoc(&world, cavalry));
+        assert_eq!(veterancy(&world, cavalry).promotions, [Promotion::IgnoreZoc]);
+        assert!(VeterancySystem::awaiting_promotion(&world, 0).is_empty());
+        
+        // The rank is spent
+        assert!(!VeterancySystem::promote(&mut world, cavalry, Promotion::Movement));
+        assert_eq!(world.get_component::<UnitStats>(cavalry).unwrap().movement, 4);
+        
+        VeterancySystem::gain_xp(&mut world, cavalry, RANK_XP[1] - RANK_XP[0]);
+        assert!(VeterancySystem::promote(&mut world, cavalry, Promotion::Movement));
+        assert_eq!(world.get_component::<UnitStats>(cavalry).unwrap().movement, 5);
+    }
+}
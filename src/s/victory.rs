@@ -0,0 +1,268 @@
+use crate::e::entity::{Entity, World};
+use crate::e::factory::HexMapFactory;
+use crate::s::animation::{AnimationSystem, DEATH_TIME};
+use crate::c::*;
+
+// System that keeps track of the victory conditions in GameRules and decides when a side has won.
+// Wiping out every enemy is handled by HexMapFactory::check_game_over whatever the conditions.
+pub struct VictorySystem;
+
+impl VictorySystem {
+    // Count damage dealt towards the team's score
+    pub fn record_damage(world: &mut World, team_id: u8, damage: i32) {
+        if let Some(state) = Self::get_state_mut(world) {
+            *state.scores.entry(team_id).or_insert(0) += damage;
+        }
+    }
+    
+    // A unit of a team was destroyed. Losing the leader takes the whole team out if that rule is on.
+    pub fn unit_destroyed(world: &mut World, team_id: u8, was_leader: bool) {
+        let rules = HexMapFactory::get_game_rules(world);
+        if !was_leader || !rules.victory.contains(&VictoryCondition::KillLeader) {
+            return;
+        }
+        
+        // The rest of the army leaves the field
+        let units: Vec<Entity> = world.query::<Team>()
+            .into_iter()
+            .filter(|(_, team)| team.team_id == team_id)
+            .map(|(entity, _)| entity)
+            .collect();
+        let color = HexMapFactory::team_color(world, team_id);
+        for entity in units {
+            if let Some(at) = world.get_component::<Position>(entity).map(|position| position.coord) {
+                AnimationSystem::play(world, Animation::new(AnimationKind::Death { at, color }, None, DEATH_TIME));
+            }
+            HexMapFactory::remove_unit(world, entity);
+        }
+    }
+    
+    // Check the conditions at the end of a team's turn, returning the winners if the game is over.
+    // `round_over` is set when this was the last team to move in the round.
+    pub fn end_of_turn(world: &mut World, team_id: u8, round_over: bool) -> Option<Vec<u8>> {
+        let rules = HexMapFactory::get_game_rules(world);
+        let turn_number = HexMapFactory::get_game_state_entity(world)
+            .and_then(|entity| world.get_component::<GameState>(entity))
+            .map_or(0, |game_state| game_state.turn_number);
+        
+        // Holding every objective counts the turns the team has ended on them in a row
+        let holding = Self::holds_objectives(world, team_id);
+        if let Some(state) = Self::get_state_mut(world) {
+            let held = state.held_turns.entry(team_id).or_insert(0);
+            *held = if holding { *held + 1 } else { 0 };
+        }
+        let held = Self::get_state(world).and_then(|state| state.held_turns.get(&team_id).copied()).unwrap_or(0);
+        
+        for condition in &rules.victory {
+            match *condition {
+                VictoryCondition::HoldObjectives { turns } if holding && held >= turns => {
+                    return Some(Self::side(world, team_id));
+                },
+                VictoryCondition::SurviveUntil { team, turn } if round_over && turn_number >= turn && HexMapFactory::living_teams(world).contains(&team) => {
+                    return Some(Self::side(world, team));
+                },
+                _ => {},
+            }
+        }
+        
+        // Out of time: a draw, unless scores decide it
+        if round_over && rules.turn_limit.is_some_and(|limit| turn_number >= limit) {
+            if rules.victory.contains(&VictoryCondition::HighestScore) {
+                return Some(Self::highest_scorers(world));
+            }
+            return Some(Vec::new());
+        }
+        
+        None
+    }
+    
+    // What each team has towards a condition, for display, in turn order:
+    // turns held for HoldObjectives, score for HighestScore, teams with a living leader for KillLeader
+    pub fn progress(world: &World, condition: &VictoryCondition) -> Vec<(u8, i32)> {
+        let team_ids: Vec<u8> = HexMapFactory::get_teams(world).map_or_else(Vec::new, |teams| teams.teams.iter().map(|team| team.id).collect());
+        let state = Self::get_state(world);
+        
+        match condition {
+            VictoryCondition::HoldObjectives { .. } => team_ids.into_iter()
+                .map(|team_id| (team_id, state.and_then(|state| state.held_turns.get(&team_id).copied()).unwrap_or(0)))
+                .collect(),
+            VictoryCondition::HighestScore => team_ids.into_iter()
+                .map(|team_id| (team_id, state.and_then(|state| state.scores.get(&team_id).copied()).unwrap_or(0)))
+                .collect(),
+            VictoryCondition::KillLeader => team_ids.into_iter()
+                .filter(|&team_id| {
+                    world.query::<Leader>()
+                        .into_iter()
+                        .any(|(entity, _)| world.get_component::<Team>(entity).is_some_and(|team| team.team_id == team_id))
+                })
+                .map(|team_id| (team_id, 1))
+                .collect(),
+            VictoryCondition::Eliminate | VictoryCondition::SurviveUntil { .. } => Vec::new(),
+        }
+    }
+    
+    // Whether the team or its allies stand on every objective hex
+    fn holds_objectives(world: &World, team_id: u8) -> bool {
+        let objectives = world.query::<Objective>();
+        !objectives.is_empty() && objectives.into_iter().all(|(entity, _)| {
+            world.get_component::<UnitState>(entity).is_some_and(|state| state.health > 0)
+                && world.get_component::<Team>(entity).is_some_and(|team| !HexMapFactory::are_enemies(world, team.team_id, team_id))
+        })
+    }
+    
+    // A team and its living allies, in turn order
+    fn side(world: &World, team_id: u8) -> Vec<u8> {
+        let living = HexMapFactory::living_teams(world);
+        match HexMapFactory::get_teams(world) {
+            Some(teams) => teams.teams.iter()
+                .map(|team| team.id)
+                .filter(|&id| id == team_id || (living.contains(&id) && teams.are_allied(id, team_id)))
+                .collect(),
+            None => vec![team_id],
+        }
+    }
+    
+    // Living teams with the best score and their living allies, in turn order
+    fn highest_scorers(world: &World) -> Vec<u8> {
+        let living = HexMapFactory::living_teams(world);
+        let mut scores = Self::progress(world, &VictoryCondition::HighestScore);
+        scores.retain(|(team_id, _)| living.contains(team_id));
+        let best = match scores.iter().map(|&(_, score)| score).max() {
+            Some(best) => best,
+            None => return Vec::new(),
+        };
+        
+        let mut winners: Vec<u8> = Vec::new();
+        for &(team_id, score) in &scores {
+            if score == best {
+                winners.extend(Self::side(world, team_id));
+            }
+        }
+        scores.iter().map(|&(team_id, _)| team_id).filter(|team_id| winners.contains(team_id)).collect()
+    }
+    
+    fn get_state(world: &World) -> Option<&VictoryState> {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world)?;
+        world.get_component::<VictoryState>(game_state_entity)
+    }
+    
+    fn get_state_mut(world: &mut World) -> Option<&mut VictoryState> {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world)?;
+        world.get_component_mut::<VictoryState>(game_state_entity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    // Blue and Green are allied against Red. Green's infantry stands on the objective at column 2,
+    // the leaders are Blue's infantry at 0 and Red's at 5. `rules` goes before the map.
+    fn world(rules: &str) -> World {
+        HexMapFactory::test_world(&format!(r#"
+name = "Test"
+{}
+[map]
+shape = "Parallelogram"
+width = 6
+height = 1
+terrain = "plain"
+objectives = [[2, 0]]
+[[team]]
+name = "Blue"
+alliance = 1
+[[team.unit]]
+type = "infantry"
+at = [0, 0]
+leader = true
+[[team]]
+name = "Red"
+[[team.unit]]
+type = "infantry"
+at = [4, 0]
+[[team.unit]]
+type = "infantry"
+at = [5, 0]
+leader = true
+[[team]]
+name = "Green"
+alliance = 1
+[[team.unit]]
+type = "infantry"
+at = [2, 0]
+"#, rules))
+    }
+    
+    fn set_round(world: &mut World, turn_number: i32) {
+        let game_state_entity = HexMapFactory::get_game_state_entity(world).unwrap();
+        world.get_component_mut::<GameState>(game_state_entity).unwrap().turn_number = turn_number;
+    }
+    
+    #[test]
+    fn holding_objectives_counts_turns_in_a_row() {
+        let mut world = world("[[victory]]\ntype = \"hold_objectives\"\nturns = 2");
+        let green = HexMapFactory::test_tile(&world, 2);
+        
+        // An ally on the objective counts for Blue
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 0, false), None);
+        assert_eq!(VictorySystem::progress(&world, &VictoryCondition::HoldObjectives { turns: 2 }), [(0, 1), (1, 0), (2, 0)]);
+        
+        // Losing the hex starts the count again
+        world.get_component_mut::<UnitState>(green).unwrap().health = 0;
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 0, false), None);
+        world.get_component_mut::<UnitState>(green).unwrap().health = 5;
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 0, false), None);
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 1, false), None);
+        
+        // The whole side wins
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 0, false), Some(vec![0, 2]));
+    }
+    
+    #[test]
+    fn surviving_is_checked_once_the_round_is_over() {
+        let mut world = world("[[victory]]\ntype = \"survive_until\"\nteam = 1\nturn = 2");
+        
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 2, true), None);
+        set_round(&mut world, 2);
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 0, false), None);
+        assert_eq!(VictorySystem::end_of_turn(&mut world, 2, true), Some(vec![1]));
+    }
+    
+    #[test]
+    fn the_turn_limit_is_a_draw_unless_scores_decide_it() {
+        let mut draw = world("turn_limit = 3");
+        set_round(&mut draw, 3);
+        VictorySystem::record_damage(&mut draw, 1, 5);
+        assert_eq!(VictorySystem::end_of_turn(&mut draw, 0, false), None);
+        assert_eq!(VictorySystem::end_of_turn(&mut draw, 2, true), Some(Vec::new()));
+        
+        let mut scored = world("turn_limit = 3\n[[victory]]\ntype = \"highest_score\"");
+        set_round(&mut scored, 3);
+        VictorySystem::record_damage(&mut scored, 1, 5);
+        VictorySystem::record_damage(&mut scored, 0, 3);
+        assert_eq!(VictorySystem::end_of_turn(&mut scored, 2, true), Some(vec![1]));
+        
+        // The best scorer's allies share the win, and so does everyone on a tie
+        VictorySystem::record_damage(&mut scored, 2, 6);
+        assert_eq!(VictorySystem::end_of_turn(&mut scored, 2, true), Some(vec![0, 2]));
+        VictorySystem::record_damage(&mut scored, 1, 1);
+        assert_eq!(VictorySystem::end_of_turn(&mut scored, 2, true), Some(vec![0, 1, 2]));
+    }
+    
+    #[test]
+    fn losing_the_leader_takes_the_army_off_the_field() {
+        // Without the rule a leader is just another unit
+        let mut world_without = world("");
+        VictorySystem::unit_destroyed(&mut world_without, 1, true);
+        assert!(HexMapFactory::living_teams(&world_without).contains(&1));
+        
+        let mut world = world("[[victory]]\ntype = \"kill_leader\"");
+        VictorySystem::unit_destroyed(&mut world, 1, false);
+        assert!(HexMapFactory::living_teams(&world).contains(&1));
+        
+        VictorySystem::unit_destroyed(&mut world, 1, true);
+        assert!(world.get_component::<UnitState>(HexMapFactory::test_tile(&world, 4)).is_none());
+        assert_eq!(HexMapFactory::living_teams(&world), [0, 2].into());
+        assert_eq!(VictorySystem::progress(&world, &VictoryCondition::KillLeader), [(0, 1)]);
+    }
+}
@@ -0,0 +1,269 @@
+use serde::{Deserialize, Serialize};
+use std::fs;
+use toml::{Table, Value};
+use crate::c::{Controller, CoordSystem, HexOrientation, MapShape, MAX_TEAMS, MIN_TEAMS, TEAM_COLORS};
+use crate::e::MapGenConfig;
+use crate::s::KeyBindings;
+
+// Where settings are saved, relative to the working directory
+pub const SETTINGS_PATH: &str = "settings.toml";
+
+// One team on random maps
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TeamSettings {
+    pub name: String, // Empty uses a numbered default name
+    pub color: [u8; 3],
+    pub controller: Controller,
+    pub alliance: u8, // Teams sharing a non-zero alliance are allied
+}
+
+impl TeamSettings {
+    // Defaults for the team at a position in the list
+    pub fn numbered(index: usize) -> Self {
+        Self {
+            name: String::new(),
+            color: TEAM_COLORS[index % TEAM_COLORS.len()],
+            controller: Controller::Human,
+            alliance: 0,
+        }
+    }
+}
+
+impl Default for TeamSettings {
+    fn default() -> Self {
+        Self::numbered(0)
+    }
+}
+
+// Player settings that survive restarts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub map_size: i32,
+    pub map_shape: MapShape,
+    pub coord_labels: CoordSystem,
+    pub hex_size: f32,
+    pub hex_orientation: HexOrientation,
+    pub hex_stretch: f32,
+    pub map_gen: MapGenConfig,
+    pub language: String, // Language code, e.g. "zh-CN"
+    pub show_unit_info: bool,
+    pub show_minimap: bool,
+    pub show_debug: bool,
+    pub show_coord_labels: bool,
+    pub confirm_end_turn: bool, // Ask before ending the turn with units still idle
+    pub animation_speed: f32,   // 0 turns animations off
+    pub zone_of_control: bool,  // Zone of control rule on random maps; scenarios set their own
+    pub supply: bool,           // Supply rule on random maps, likewise
+    pub teams: Vec<TeamSettings>, // Teams on random maps, in turn order
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            map_size: 8,
+            map_shape: MapShape::RectangleOddQ,
+            coord_labels: CoordSystem::Axial,
+            hex_size: 30.0,
+            hex_orientation: HexOrientation::Flat,
+            hex_stretch: 1.0,
+            map_gen: MapGenConfig::default(),
+            language: "zh-CN".to_string(),
+            show_unit_info: true,
+            show_minimap: true,
+            show_debug: false,
+            show_coord_labels: false,
+            confirm_end_turn: true,
+            animation_speed: 1.0,
+            zone_of_control: false,
+            supply: false,
+            teams: vec![TeamSettings::numbered(0), TeamSettings::numbered(1)],
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Load saved settings. Entries that fail to parse are reported and keep their defaults.
+    pub fn load() -> Self {
+        let mut settings = match fs::read_to_string(SETTINGS_PATH) {
+            Ok(text) => {
+                let (settings, errors) = Self::parse(&text);
+                for e in errors {
+                    eprintln!("{}: {}", SETTINGS_PATH, e);
+                }
+                settings
+            },
+            // No file yet means first run, which is fine
+            Err(_) => Settings::default(),
+        };
+        
+        // A hand-edited file may have too few or too many teams
+        settings.teams.truncate(MAX_TEAMS);
+        while settings.teams.len() < MIN_TEAMS {
+            settings.teams.push(TeamSettings::numbered(settings.teams.len()));
+        }
+        
+        settings
+    }
+    
+    // Parse settings text one entry at a time, so a bad entry only loses itself
+    pub fn parse(text: &str) -> (Self, Vec<String>) {
+        let file: Table = match toml::from_str(text) {
+            Ok(file) => file,
+            Err(e) => return (Settings::default(), vec![e.to_string()]),
+        };
+        let mut merged = match Value::try_from(Settings::default()) {
+            Ok(Value::Table(table)) => table,
+            _ => Table::new(),
+        };
+        
+        let mut errors = Vec::new();
+        Self::merge(&mut merged, &mut Vec::new(), file, &mut errors);
+        let settings = Value::Table(merged).try_into().unwrap_or_else(|e: toml::de::Error| {
+            errors.push(e.to_string());
+            Settings::default()
+        });
+        
+        (settings, errors)
+    }
+    
+    // Copy entries from the file into the defaults, keeping only those that still parse
+    fn merge(merged: &mut Table, path: &mut Vec<String>, file: Table, errors: &mut Vec<String>) {
+        for (key, value) in file {
+            path.push(key);
+            let existing = Self::entry(merged, path);
+            
+            match value {
+                Value::Table(table) if matches!(existing, Some(Value::Table(_))) => {
+                    Self::merge(merged, path, table, errors);
+                },
+                Value::Array(items) if matches!(existing, Some(Value::Array(_))) => {
+                    if Self::try_set(merged, path, Value::Array(items.clone())).is_err() {
+                        // Keep the list items that parse, e.g. all but one bad key binding
+                        let mut kept = Vec::new();
+                        for (index, item) in items.into_iter().enumerate() {
+                            kept.push(item);
+                            if let Err(e) = Self::try_set(merged, path, Value::Array(kept.clone())) {
+                                kept.pop();
+                                errors.push(format!("{}[{}]: {}", path.join("."), index, e));
+                            }
+                        }
+                        let _ = Self::try_set(merged, path, Value::Array(kept));
+                    }
+                },
+                value => {
+                    if let Err(e) = Self::try_set(merged, path, value) {
+                        errors.push(format!("{}: {}", path.join("."), e));
+                    }
+                },
+            }
+            
+            path.pop();
+        }
+    }
+    
+    // Value at a dotted path, if there is one
+    fn entry<'a>(table: &'a Table, path: &[String]) -> Option<&'a Value> {
+        let (last, parents) = path.split_last()?;
+        let mut table = table;
+        for key in parents {
+            table = table.get(key)?.as_table()?;
+        }
+        table.get(last)
+    }
+    
+    // Set a value at a dotted path if the whole settings still parse with it
+    fn try_set(merged: &mut Table, path: &[String], value: Value) -> Result<(), String> {
+        let mut candidate = merged.clone();
+        let Some((last, parents)) = path.split_last() else {
+            return Ok(());
+        };
+        let mut table = &mut candidate;
+        for key in parents {
+            table = match table.get_mut(key).and_then(Value::as_table_mut) {
+                Some(table) => table,
+                None => return Ok(()),
+            };
+        }
+        table.insert(last.clone(), value);
+        
+        Value::Table(candidate.clone()).try_into::<Settings>().map_err(|e| e.message().to_string())?;
+        *merged = candidate;
+        Ok(())
+    }
+    
+    // Write settings back to disk
+    pub fn save(&self) -> Result<(), String> {
+        let text = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(SETTINGS_PATH, text).map_err(|e| format!("{}: {}", SETTINGS_PATH, e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eframe::egui::Key;
+    use crate::s::InputAction;
+    
+    #[test]
+    fn bad_entry_keeps_the_rest() {
+        let (settings, errors) = Settings::parse(r#"
+            map_size = "big"
+            hex_size = 42.0
+            show_minimap = false
+            
+            [map_gen]
+            water_percent = "lots"
+            forest_percent = 40.0
+        "#);
+        
+        assert_eq!(errors.len(), 2, "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("map_size:")), "{:?}", errors);
+        assert!(errors.iter().any(|e| e.starts_with("map_gen.water_percent:")), "{:?}", errors);
+        assert_eq!(settings.map_size, Settings::default().map_size);
+        assert_eq!(settings.hex_size, 42.0);
+        assert!(!settings.show_minimap);
+        assert_eq!(settings.map_gen.water_percent, MapGenConfig::default().water_percent);
+        assert_eq!(settings.map_gen.forest_percent, 40.0);
+    }
+    
+    #[test]
+    fn bad_list_item_keeps_the_others() {
+        let (settings, errors) = Settings::parse(r#"
+            [[key_bindings]]
+            action = "EndTurn"
+            key = "Enter"
+            
+            [[key_bindings]]
+            action = "NoSuchAction"
+            key = "F"
+        "#);
+        
+        assert_eq!(errors.len(), 1, "{:?}", errors);
+        assert!(errors[0].starts_with("key_bindings[1]"), "{:?}", errors);
+        assert_eq!(settings.key_bindings.key(InputAction::EndTurn), Some(Key::Enter));
+    }
+    
+    #[test]
+    fn syntax_error_is_reported() {
+        let (settings, errors) = Settings::parse("map_size = = 3");
+        
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("line 1"), "{:?}", errors);
+        assert_eq!(settings.map_size, Settings::default().map_size);
+    }
+    
+    #[test]
+    fn saved_settings_load_without_errors() {
+        let mut saved = Settings { map_size: 12, ..Default::default() };
+        saved.teams.push(TeamSettings::numbered(2));
+        
+        let (settings, errors) = Settings::parse(&toml::to_string_pretty(&saved).unwrap());
+        assert!(errors.is_empty(), "{:?}", errors);
+        assert_eq!(settings.map_size, 12);
+        assert_eq!(settings.teams, saved.teams);
+    }
+}